@@ -0,0 +1,408 @@
+//! Micro-batching of concurrent classification requests
+//!
+//! Model-backed classifiers run a forward pass per call. Under load,
+//! [`BatchingClassifier`] collects concurrent requests for the same model for
+//! a short window and hands them to [`Classifier::classify_batch`] together,
+//! so a backend with a batched implementation does one padded pass instead of
+//! one per request.
+
+use crate::classifier::{ClassificationResult, Classifier, ClassifierTier};
+use checkstream_core::Result;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot, Semaphore};
+
+/// Micro-batching settings (`batching:` section of the classifiers config)
+///
+/// The maximum batch size comes from each model's `inference.batch_size` in
+/// the model registry; models with a batch size of 1 are never batched.
+/// Batching is off unless enabled: it adds up to `max_wait_ms` to every call
+/// and only pays off under concurrent load.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchingConfig {
+    /// Batch concurrent requests for plugin-backed models
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// How long the first request in a batch waits for others to join
+    #[serde(default = "default_max_wait_ms")]
+    pub max_wait_ms: u64,
+
+    /// Batches of one model that may run at the same time
+    #[serde(default = "default_max_concurrent_batches")]
+    pub max_concurrent_batches: usize,
+}
+
+impl Default for BatchingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_wait_ms: default_max_wait_ms(),
+            max_concurrent_batches: default_max_concurrent_batches(),
+        }
+    }
+}
+
+fn default_max_wait_ms() -> u64 {
+    2
+}
+
+fn default_max_concurrent_batches() -> usize {
+    4
+}
+
+struct BatchRequest {
+    text: String,
+    reply: oneshot::Sender<Result<ClassificationResult>>,
+}
+
+/// Classifier that groups concurrent calls into batches for an inner classifier
+///
+/// A background task owns the queue: it takes the first waiting request,
+/// collects more until `max_batch_size` is reached or `max_wait` elapses, and
+/// runs them through the inner classifier's `classify_batch`, with up to
+/// `max_concurrent_batches` batches in flight. The task is spawned on the
+/// first call, so the classifier can be built outside a Tokio runtime.
+/// Publishes `checkstream_classifier_batch_queue_depth` and
+/// `checkstream_classifier_batch_size`, labelled by classifier.
+pub struct BatchingClassifier {
+    inner: Arc<dyn Classifier>,
+    sender: mpsc::UnboundedSender<BatchRequest>,
+    receiver: Mutex<Option<mpsc::UnboundedReceiver<BatchRequest>>>,
+    queue_depth: Arc<AtomicUsize>,
+    max_batch_size: usize,
+    max_wait: Duration,
+    max_concurrent_batches: usize,
+}
+
+impl BatchingClassifier {
+    /// Wrap `inner`, running one batch at a time
+    pub fn new(inner: Arc<dyn Classifier>, max_batch_size: usize, max_wait: Duration) -> Self {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        Self {
+            inner,
+            sender,
+            receiver: Mutex::new(Some(receiver)),
+            queue_depth: Arc::new(AtomicUsize::new(0)),
+            max_batch_size: max_batch_size.max(1),
+            max_wait,
+            max_concurrent_batches: 1,
+        }
+    }
+
+    /// Let up to `max` batches run through the inner classifier at once
+    pub fn with_max_concurrent_batches(mut self, max: usize) -> Self {
+        self.max_concurrent_batches = max.max(1);
+        self
+    }
+
+    /// Largest batch handed to the inner classifier
+    pub fn max_batch_size(&self) -> usize {
+        self.max_batch_size
+    }
+
+    /// Requests currently waiting for a batch
+    pub fn queue_depth(&self) -> usize {
+        self.queue_depth.load(Ordering::Relaxed)
+    }
+
+    /// Spawn the batching task on the caller's runtime if it is not running yet
+    fn ensure_started(&self) {
+        let receiver = self
+            .receiver
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .take();
+        if let Some(receiver) = receiver {
+            tokio::spawn(run_batches(
+                self.inner.clone(),
+                receiver,
+                self.queue_depth.clone(),
+                self.max_batch_size,
+                self.max_wait,
+                Arc::new(Semaphore::new(self.max_concurrent_batches)),
+            ));
+        }
+    }
+}
+
+async fn run_batches(
+    inner: Arc<dyn Classifier>,
+    mut receiver: mpsc::UnboundedReceiver<BatchRequest>,
+    queue_depth: Arc<AtomicUsize>,
+    max_batch_size: usize,
+    max_wait: Duration,
+    in_flight: Arc<Semaphore>,
+) {
+    let classifier = inner.name().to_string();
+
+    while let Some(first) = receiver.recv().await {
+        let mut batch = vec![first];
+        let deadline = tokio::time::Instant::now() + max_wait;
+        while batch.len() < max_batch_size {
+            match tokio::time::timeout_at(deadline, receiver.recv()).await {
+                Ok(Some(request)) => batch.push(request),
+                Ok(None) | Err(_) => break,
+            }
+        }
+
+        // Requests keep queueing while every batch slot is busy
+        let Ok(permit) = in_flight.clone().acquire_owned().await else {
+            return;
+        };
+
+        let depth = queue_depth.fetch_sub(batch.len(), Ordering::Relaxed) - batch.len();
+        metrics::gauge!(
+            "checkstream_classifier_batch_queue_depth",
+            "classifier" => classifier.clone()
+        )
+        .set(depth as f64);
+        metrics::histogram!(
+            "checkstream_classifier_batch_size",
+            "classifier" => classifier.clone()
+        )
+        .record(batch.len() as f64);
+
+        let inner = inner.clone();
+        let classifier = classifier.clone();
+        tokio::spawn(async move {
+            run_batch(inner.as_ref(), &classifier, batch).await;
+            drop(permit);
+        });
+    }
+}
+
+async fn run_batch(inner: &dyn Classifier, classifier: &str, batch: Vec<BatchRequest>) {
+    let texts: Vec<&str> = batch.iter().map(|request| request.text.as_str()).collect();
+    match inner.classify_batch(&texts).await {
+        Ok(results) if results.len() == batch.len() => {
+            for (request, result) in batch.into_iter().zip(results) {
+                let _ = request.reply.send(Ok(result));
+            }
+        }
+        Ok(results) => {
+            let message = format!(
+                "Classifier '{}' returned {} results for a batch of {}",
+                classifier,
+                results.len(),
+                batch.len()
+            );
+            for request in batch {
+                let _ = request
+                    .reply
+                    .send(Err(checkstream_core::Error::classifier(message.clone())));
+            }
+        }
+        Err(e) => {
+            let message = e.to_string();
+            for request in batch {
+                let _ = request
+                    .reply
+                    .send(Err(checkstream_core::Error::classifier(message.clone())));
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Classifier for BatchingClassifier {
+    async fn classify(&self, text: &str) -> Result<ClassificationResult> {
+        self.ensure_started();
+        let (reply, response) = oneshot::channel();
+        let depth = self.queue_depth.fetch_add(1, Ordering::Relaxed) + 1;
+        metrics::gauge!(
+            "checkstream_classifier_batch_queue_depth",
+            "classifier" => self.inner.name().to_string()
+        )
+        .set(depth as f64);
+
+        self.sender
+            .send(BatchRequest {
+                text: text.to_string(),
+                reply,
+            })
+            .map_err(|_| {
+                self.queue_depth.fetch_sub(1, Ordering::Relaxed);
+                checkstream_core::Error::classifier(format!(
+                    "Batching task for '{}' has stopped",
+                    self.inner.name()
+                ))
+            })?;
+
+        response.await.map_err(|_| {
+            checkstream_core::Error::classifier(format!(
+                "Batching task for '{}' dropped the request",
+                self.inner.name()
+            ))
+        })?
+    }
+
+    async fn classify_batch(&self, texts: &[&str]) -> Result<Vec<ClassificationResult>> {
+        self.inner.classify_batch(texts).await
+    }
+
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn tier(&self) -> ClassifierTier {
+        self.inner.tier()
+    }
+
+    fn normalized_input(&self) -> bool {
+        self.inner.normalized_input()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Records the size of every batch it is given
+    struct RecordingClassifier {
+        batches: Mutex<Vec<usize>>,
+    }
+
+    #[async_trait::async_trait]
+    impl Classifier for RecordingClassifier {
+        async fn classify(&self, text: &str) -> Result<ClassificationResult> {
+            Ok(ClassificationResult::new(text, 1.0))
+        }
+
+        async fn classify_batch(&self, texts: &[&str]) -> Result<Vec<ClassificationResult>> {
+            self.batches.lock().unwrap().push(texts.len());
+            Ok(texts
+                .iter()
+                .map(|text| ClassificationResult::new(*text, 1.0))
+                .collect())
+        }
+
+        fn name(&self) -> &str {
+            "recording"
+        }
+
+        fn tier(&self) -> ClassifierTier {
+            ClassifierTier::B
+        }
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_requests_share_a_batch() {
+        let inner = Arc::new(RecordingClassifier {
+            batches: Mutex::new(Vec::new()),
+        });
+        let batching = Arc::new(BatchingClassifier::new(
+            inner.clone(),
+            4,
+            Duration::from_millis(50),
+        ));
+
+        let handles: Vec<_> = (0..6)
+            .map(|i| {
+                let batching = batching.clone();
+                tokio::spawn(async move { batching.classify(&format!("text-{}", i)).await })
+            })
+            .collect();
+
+        for (i, handle) in handles.into_iter().enumerate() {
+            let result = handle.await.unwrap().unwrap();
+            assert_eq!(result.label, format!("text-{}", i));
+        }
+
+        let batches = inner.batches.lock().unwrap().clone();
+        assert_eq!(batches.iter().sum::<usize>(), 6);
+        assert!(batches.iter().all(|&size| size <= 4));
+        assert!(batches.len() < 6);
+        assert_eq!(batching.queue_depth(), 0);
+    }
+
+    /// Tracks how many batches run at the same time
+    struct SlowClassifier {
+        running: AtomicUsize,
+        peak: AtomicUsize,
+    }
+
+    #[async_trait::async_trait]
+    impl Classifier for SlowClassifier {
+        async fn classify(&self, text: &str) -> Result<ClassificationResult> {
+            Ok(ClassificationResult::new(text, 1.0))
+        }
+
+        async fn classify_batch(&self, texts: &[&str]) -> Result<Vec<ClassificationResult>> {
+            let running = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+            self.peak.fetch_max(running, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            self.running.fetch_sub(1, Ordering::SeqCst);
+            Ok(texts
+                .iter()
+                .map(|text| ClassificationResult::new(*text, 1.0))
+                .collect())
+        }
+
+        fn name(&self) -> &str {
+            "slow"
+        }
+
+        fn tier(&self) -> ClassifierTier {
+            ClassifierTier::B
+        }
+    }
+
+    #[test]
+    fn test_new_outside_runtime() {
+        let inner = Arc::new(RecordingClassifier {
+            batches: Mutex::new(Vec::new()),
+        });
+        let batching = BatchingClassifier::new(inner, 4, Duration::from_millis(1));
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .unwrap();
+        let result = runtime.block_on(batching.classify("hello")).unwrap();
+        assert_eq!(result.label, "hello");
+    }
+
+    #[tokio::test]
+    async fn test_batches_run_concurrently() {
+        let inner = Arc::new(SlowClassifier {
+            running: AtomicUsize::new(0),
+            peak: AtomicUsize::new(0),
+        });
+        let batching = Arc::new(
+            BatchingClassifier::new(inner.clone(), 2, Duration::from_millis(1))
+                .with_max_concurrent_batches(2),
+        );
+
+        let handles: Vec<_> = (0..8)
+            .map(|i| {
+                let batching = batching.clone();
+                tokio::spawn(async move { batching.classify(&format!("text-{}", i)).await })
+            })
+            .collect();
+        for handle in handles {
+            handle.await.unwrap().unwrap();
+        }
+
+        assert_eq!(inner.peak.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_default_classify_batch_loops() {
+        let classifier = crate::patterns::PatternClassifier::new(
+            "greeting",
+            vec![("greeting".to_string(), "hello".to_string())],
+        )
+        .unwrap();
+
+        let results = classifier
+            .classify_batch(&["hello there", "goodbye"])
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].label, "greeting");
+        assert_eq!(results[1].label, "clean");
+    }
+}
@@ -0,0 +1,413 @@
+//! Score calibration
+//!
+//! Lexicon classifiers and model probabilities score on different scales, so
+//! a policy threshold of 0.75 means something different for each. A
+//! [`Calibration`] maps a classifier's raw score onto an estimated probability
+//! that the input is positive. Calibrations are configured per classifier in
+//! the `calibration:` section of the classifiers config and can be fitted to a
+//! labelled dataset with [`Calibration::fit`].
+
+use crate::classifier::{ClassificationResult, Classifier, ClassifierTier};
+use checkstream_core::Result;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Bounds keeping log-odds finite for scores of exactly 0.0 or 1.0
+const EPSILON: f64 = 1e-6;
+
+/// Mapping from a raw classifier score to a calibrated probability
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "method", rename_all = "snake_case")]
+pub enum Calibration {
+    /// Logistic fit on the raw score: `1 / (1 + exp(-(a * score + b)))`
+    Platt { a: f32, b: f32 },
+
+    /// Monotone piecewise-linear map through `[raw, calibrated]` points,
+    /// sorted by raw score; scores outside the points are clamped
+    Isotonic { points: Vec<[f32; 2]> },
+
+    /// Divide the score's log-odds by `temperature` (> 1 softens, < 1 sharpens)
+    Temperature { temperature: f32 },
+}
+
+/// Calibration method to fit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CalibrationMethod {
+    Platt,
+    Isotonic,
+    Temperature,
+}
+
+impl CalibrationMethod {
+    /// Parse a method name (`platt`, `isotonic` or `temperature`)
+    pub fn parse(name: &str) -> Result<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "platt" => Ok(Self::Platt),
+            "isotonic" => Ok(Self::Isotonic),
+            "temperature" => Ok(Self::Temperature),
+            other => Err(checkstream_core::Error::config(format!(
+                "Unknown calibration method '{}' (expected platt, isotonic or temperature)",
+                other
+            ))),
+        }
+    }
+}
+
+impl Calibration {
+    /// Calibrate a raw score
+    pub fn apply(&self, score: f32) -> f32 {
+        match self {
+            Self::Platt { a, b } => sigmoid(*a as f64 * score as f64 + *b as f64) as f32,
+            Self::Isotonic { points } => interpolate(points, score),
+            Self::Temperature { temperature } => {
+                (sigmoid(logit(score as f64) / *temperature as f64)) as f32
+            }
+        }
+    }
+
+    /// Check the parameters are usable
+    pub fn validate(&self) -> Result<()> {
+        let invalid = |message: &str| Err(checkstream_core::Error::config(message.to_string()));
+        match self {
+            Self::Platt { a, b } if !a.is_finite() || !b.is_finite() => {
+                invalid("Platt calibration parameters must be finite")
+            }
+            Self::Temperature { temperature }
+                if !temperature.is_finite() || *temperature <= 0.0 =>
+            {
+                invalid("Calibration temperature must be greater than 0")
+            }
+            Self::Isotonic { points } if points.is_empty() => {
+                invalid("Isotonic calibration needs at least one point")
+            }
+            Self::Isotonic { points }
+                if points
+                    .windows(2)
+                    .any(|pair| pair[1][0] < pair[0][0] || pair[1][1] < pair[0][1]) =>
+            {
+                invalid("Isotonic calibration points must be increasing")
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Fit a calibration to `(raw score, is positive)` samples
+    pub fn fit(method: CalibrationMethod, samples: &[(f32, bool)]) -> Result<Self> {
+        let positives = samples.iter().filter(|(_, positive)| *positive).count();
+        if positives == 0 || positives == samples.len() {
+            return Err(checkstream_core::Error::config(
+                "Calibration needs both positive and negative examples",
+            ));
+        }
+
+        Ok(match method {
+            CalibrationMethod::Platt => fit_platt(samples),
+            CalibrationMethod::Isotonic => fit_isotonic(samples),
+            CalibrationMethod::Temperature => fit_temperature(samples),
+        })
+    }
+}
+
+fn sigmoid(x: f64) -> f64 {
+    1.0 / (1.0 + (-x).exp())
+}
+
+fn logit(p: f64) -> f64 {
+    let p = p.clamp(EPSILON, 1.0 - EPSILON);
+    (p / (1.0 - p)).ln()
+}
+
+fn interpolate(points: &[[f32; 2]], score: f32) -> f32 {
+    let Some(first) = points.first() else {
+        return score;
+    };
+    if score <= first[0] {
+        return first[1];
+    }
+    for pair in points.windows(2) {
+        let ([x0, y0], [x1, y1]) = (pair[0], pair[1]);
+        if score <= x1 {
+            if x1 <= x0 {
+                return y1;
+            }
+            return y0 + (y1 - y0) * (score - x0) / (x1 - x0);
+        }
+    }
+    points[points.len() - 1][1]
+}
+
+/// Logistic regression on the raw score by Newton's method, with Platt's
+/// smoothed targets to avoid overconfident fits on small datasets
+fn fit_platt(samples: &[(f32, bool)]) -> Calibration {
+    let positives = samples.iter().filter(|(_, positive)| *positive).count() as f64;
+    let negatives = samples.len() as f64 - positives;
+    let high = (positives + 1.0) / (positives + 2.0);
+    let low = 1.0 / (negatives + 2.0);
+
+    let (mut a, mut b) = (1.0f64, 0.0f64);
+    for _ in 0..100 {
+        let (mut ga, mut gb, mut haa, mut hab, mut hbb) = (0.0, 0.0, 0.0, 0.0, 0.0);
+        for &(score, positive) in samples {
+            let x = score as f64;
+            let target = if positive { high } else { low };
+            let p = sigmoid(a * x + b);
+            let w = (p * (1.0 - p)).max(1e-12);
+            ga += (p - target) * x;
+            gb += p - target;
+            haa += w * x * x;
+            hab += w * x;
+            hbb += w;
+        }
+        // Small ridge term keeps the Hessian invertible for separable data
+        haa += 1e-6;
+        hbb += 1e-6;
+        let det = haa * hbb - hab * hab;
+        if det.abs() < 1e-18 {
+            break;
+        }
+        let da = (hbb * ga - hab * gb) / det;
+        let db = (haa * gb - hab * ga) / det;
+        a -= da;
+        b -= db;
+        if da.abs() < 1e-9 && db.abs() < 1e-9 {
+            break;
+        }
+    }
+
+    Calibration::Platt {
+        a: a as f32,
+        b: b as f32,
+    }
+}
+
+/// Pool-adjacent-violators regression of labels on raw scores
+fn fit_isotonic(samples: &[(f32, bool)]) -> Calibration {
+    let mut sorted = samples.to_vec();
+    sorted.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+
+    // Tied scores form one starting block of (min score, max score, label sum, count)
+    let mut ties: Vec<(f32, f32, f64, f64)> = Vec::new();
+    for (score, positive) in sorted {
+        let label = if positive { 1.0 } else { 0.0 };
+        match ties.last_mut() {
+            Some(last) if last.1 == score => {
+                last.2 += label;
+                last.3 += 1.0;
+            }
+            _ => ties.push((score, score, label, 1.0)),
+        }
+    }
+
+    let mut blocks: Vec<(f32, f32, f64, f64)> = Vec::new();
+    for block in ties {
+        blocks.push(block);
+        while blocks.len() > 1 {
+            let n = blocks.len();
+            let (prev, last) = (blocks[n - 2], blocks[n - 1]);
+            if prev.2 / prev.3 < last.2 / last.3 {
+                break;
+            }
+            blocks[n - 2] = (prev.0, last.1, prev.2 + last.2, prev.3 + last.3);
+            blocks.pop();
+        }
+    }
+
+    let mut points: Vec<[f32; 2]> = Vec::new();
+    for (min, max, sum, count) in blocks {
+        let value = (sum / count) as f32;
+        points.push([min, value]);
+        if max > min {
+            points.push([max, value]);
+        }
+    }
+    Calibration::Isotonic { points }
+}
+
+/// Temperature minimizing log loss, by golden-section search over log(T)
+fn fit_temperature(samples: &[(f32, bool)]) -> Calibration {
+    let loss = |log_t: f64| {
+        let t = log_t.exp();
+        samples
+            .iter()
+            .map(|&(score, positive)| {
+                let p = sigmoid(logit(score as f64) / t).clamp(EPSILON, 1.0 - EPSILON);
+                if positive {
+                    -p.ln()
+                } else {
+                    -(1.0 - p).ln()
+                }
+            })
+            .sum::<f64>()
+    };
+
+    let ratio = (5f64.sqrt() - 1.0) / 2.0;
+    let (mut lo, mut hi) = (0.01f64.ln(), 100f64.ln());
+    for _ in 0..100 {
+        let left = hi - ratio * (hi - lo);
+        let right = lo + ratio * (hi - lo);
+        if loss(left) < loss(right) {
+            hi = right;
+        } else {
+            lo = left;
+        }
+    }
+
+    Calibration::Temperature {
+        temperature: ((lo + hi) / 2.0).exp() as f32,
+    }
+}
+
+/// Classifier whose scores pass through a [`Calibration`]
+///
+/// Only the top-level score is calibrated; the raw score is kept in
+/// `metadata.extra` as `raw_score`, and labels, spans and per-label scores are
+/// left as the inner classifier reported them.
+pub struct CalibratedClassifier {
+    inner: Arc<dyn Classifier>,
+    calibration: Calibration,
+}
+
+impl CalibratedClassifier {
+    /// Wrap `inner` with a validated calibration
+    pub fn new(inner: Arc<dyn Classifier>, calibration: Calibration) -> Result<Self> {
+        calibration.validate()?;
+        Ok(Self { inner, calibration })
+    }
+
+    fn calibrate(&self, mut result: ClassificationResult) -> ClassificationResult {
+        result
+            .metadata
+            .extra
+            .push(("raw_score".to_string(), result.score.to_string()));
+        result.score = self.calibration.apply(result.score);
+        result
+    }
+}
+
+#[async_trait::async_trait]
+impl Classifier for CalibratedClassifier {
+    async fn classify(&self, text: &str) -> Result<ClassificationResult> {
+        Ok(self.calibrate(self.inner.classify(text).await?))
+    }
+
+    async fn classify_batch(&self, texts: &[&str]) -> Result<Vec<ClassificationResult>> {
+        Ok(self
+            .inner
+            .classify_batch(texts)
+            .await?
+            .into_iter()
+            .map(|result| self.calibrate(result))
+            .collect())
+    }
+
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn tier(&self) -> ClassifierTier {
+        self.inner.tier()
+    }
+
+    fn normalized_input(&self) -> bool {
+        self.inner.normalized_input()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Raw scores that overstate the positive rate: a score of 0.8 is
+    /// positive only half the time
+    fn overconfident() -> Vec<(f32, bool)> {
+        let mut samples = Vec::new();
+        for i in 0..50 {
+            samples.push((0.2, i % 10 == 0));
+            samples.push((0.8, i % 2 == 0));
+            samples.push((0.95, i % 10 != 0));
+        }
+        samples
+    }
+
+    #[test]
+    fn test_apply() {
+        let platt = Calibration::Platt { a: 10.0, b: -5.0 };
+        assert!((platt.apply(0.5) - 0.5).abs() < 1e-6);
+        assert!(platt.apply(0.9) > 0.98);
+
+        let isotonic = Calibration::Isotonic {
+            points: vec![[0.2, 0.0], [0.6, 0.4], [0.8, 1.0]],
+        };
+        assert_eq!(isotonic.apply(0.1), 0.0);
+        assert!((isotonic.apply(0.4) - 0.2).abs() < 1e-6);
+        assert!((isotonic.apply(0.7) - 0.7).abs() < 1e-6);
+        assert_eq!(isotonic.apply(0.9), 1.0);
+
+        let temperature = Calibration::Temperature { temperature: 2.0 };
+        assert!((temperature.apply(0.5) - 0.5).abs() < 1e-6);
+        assert!(temperature.apply(0.9) < 0.9 && temperature.apply(0.9) > 0.5);
+    }
+
+    #[test]
+    fn test_fit_methods_track_observed_rates() {
+        let samples = overconfident();
+        for method in [
+            CalibrationMethod::Platt,
+            CalibrationMethod::Isotonic,
+            CalibrationMethod::Temperature,
+        ] {
+            let calibration = Calibration::fit(method, &samples).unwrap();
+            calibration.validate().unwrap();
+            let (low, mid, high) = (
+                calibration.apply(0.2),
+                calibration.apply(0.8),
+                calibration.apply(0.95),
+            );
+            assert!(low < mid && mid < high, "{:?}", calibration);
+            assert!(mid < 0.8, "{:?} maps 0.8 to {}", calibration, mid);
+        }
+
+        let isotonic = Calibration::fit(CalibrationMethod::Isotonic, &samples).unwrap();
+        assert!((isotonic.apply(0.8) - 0.5).abs() < 1e-6);
+
+        assert!(Calibration::fit(CalibrationMethod::Platt, &[(0.5, true)]).is_err());
+        assert!(CalibrationMethod::parse("beta").is_err());
+    }
+
+    #[test]
+    fn test_validate_and_parse_config() {
+        let calibration: Calibration =
+            serde_yaml::from_str("method: temperature\ntemperature: 0").unwrap();
+        assert!(calibration.validate().is_err());
+
+        let calibration: Calibration =
+            serde_yaml::from_str("method: isotonic\npoints: [[0.1, 0.0], [0.9, 1.0]]").unwrap();
+        calibration.validate().unwrap();
+
+        let unsorted = Calibration::Isotonic {
+            points: vec![[0.9, 1.0], [0.1, 0.0]],
+        };
+        assert!(unsorted.validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_calibrated_classifier_keeps_raw_score() {
+        let inner = Arc::new(
+            crate::patterns::PatternClassifier::new(
+                "greeting",
+                vec![("greeting".to_string(), "hello".to_string())],
+            )
+            .unwrap(),
+        );
+        let classifier =
+            CalibratedClassifier::new(inner, Calibration::Platt { a: 2.0, b: -1.0 }).unwrap();
+
+        let result = classifier.classify("hello").await.unwrap();
+        assert_eq!(result.label, "greeting");
+        assert!((result.score - 0.7311).abs() < 1e-3);
+        assert!(result
+            .metadata
+            .extra
+            .contains(&("raw_score".to_string(), "1".to_string())));
+    }
+}
@@ -1,5 +1,6 @@
 //! Classifier trait and common types
 
+use crate::incremental::IncrementalScanner;
 use async_trait::async_trait;
 use checkstream_core::Result;
 
@@ -9,11 +10,40 @@ pub trait Classifier: Send + Sync {
     /// Classify the given text
     async fn classify(&self, text: &str) -> Result<ClassificationResult>;
 
+    /// Classify several texts, returning one result per text in order
+    ///
+    /// Defaults to classifying them one at a time; model-backed classifiers
+    /// override this to run a single padded forward pass.
+    async fn classify_batch(&self, texts: &[&str]) -> Result<Vec<ClassificationResult>> {
+        let mut results = Vec::with_capacity(texts.len());
+        for text in texts {
+            results.push(self.classify(text).await?);
+        }
+        Ok(results)
+    }
+
     /// Get the classifier name
     fn name(&self) -> &str;
 
     /// Get the tier (performance category)
     fn tier(&self) -> ClassifierTier;
+
+    /// Whether pipelines should hand this classifier normalized text
+    ///
+    /// Defaults to Tier A, whose pattern matching is the easiest to evade
+    /// with obfuscated input.
+    fn normalized_input(&self) -> bool {
+        self.tier() == ClassifierTier::A
+    }
+
+    /// Scanner that classifies a stream chunk by chunk, if supported
+    ///
+    /// Streaming pipelines feed each chunk to the scanner instead of
+    /// re-running [`classify`](Classifier::classify) on the accumulated text.
+    /// Defaults to `None`; wrappers that change results should keep it so.
+    fn incremental(&self) -> Option<Box<dyn IncrementalScanner>> {
+        None
+    }
 }
 
 /// Result of classification
@@ -43,17 +73,56 @@ impl ClassificationResult {
         }
     }
 
+    /// Create a multi-label result from independent per-label scores
+    ///
+    /// The label is the highest-scoring label above its threshold, or
+    /// "negative" when none is, and the score is the highest label score.
+    pub fn multi_label(labels: Vec<LabelScore>) -> Self {
+        let by_score = |a: &&LabelScore, b: &&LabelScore| {
+            a.score
+                .partial_cmp(&b.score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        };
+        let label = labels
+            .iter()
+            .filter(|label| label.is_triggered())
+            .max_by(by_score)
+            .map_or_else(|| "negative".to_string(), |label| label.label.clone());
+        let score = labels
+            .iter()
+            .max_by(by_score)
+            .map_or(0.0, |label| label.score);
+
+        let mut result = Self::new(label, score);
+        result.metadata.all_scores = Some(
+            labels
+                .iter()
+                .map(|label| (label.label.clone(), label.score))
+                .collect(),
+        );
+        result.metadata.labels = labels;
+        result
+    }
+
     /// Check if score exceeds threshold
     pub fn exceeds_threshold(&self, threshold: f32) -> bool {
         self.score >= threshold
     }
+
+    /// Labels of a multi-label result that are above their thresholds
+    pub fn triggered_labels(&self) -> impl Iterator<Item = &LabelScore> {
+        self.metadata
+            .labels
+            .iter()
+            .filter(|label| label.is_triggered())
+    }
 }
 
 /// Metadata about classification
 #[derive(Debug, Clone, Default)]
 pub struct ClassificationMetadata {
     /// Matched spans (for pattern-based classifiers)
-    pub spans: Vec<(usize, usize)>,
+    pub spans: Vec<Span>,
 
     /// Model name or version
     pub model: Option<String>,
@@ -61,10 +130,75 @@ pub struct ClassificationMetadata {
     /// All class scores (for multi-class classifiers)
     pub all_scores: Option<Vec<(String, f32)>>,
 
+    /// Independent per-label scores (for multi-label classifiers)
+    pub labels: Vec<LabelScore>,
+
     /// Additional key-value pairs
     pub extra: Vec<(String, String)>,
 }
 
+/// Score of one label from a multi-label classifier
+#[derive(Debug, Clone, PartialEq)]
+pub struct LabelScore {
+    /// Label name
+    pub label: String,
+
+    /// Probability for this label (0.0-1.0)
+    pub score: f32,
+
+    /// Score above which this label is triggered
+    pub threshold: f32,
+}
+
+impl LabelScore {
+    /// Create a new label score
+    pub fn new(label: impl Into<String>, score: f32, threshold: f32) -> Self {
+        Self {
+            label: label.into(),
+            score,
+            threshold,
+        }
+    }
+
+    /// Check if the score is above this label's threshold
+    pub fn is_triggered(&self) -> bool {
+        self.score > self.threshold
+    }
+}
+
+/// A matched region of the classified text
+#[derive(Debug, Clone, PartialEq)]
+pub struct Span {
+    /// Byte offset of the start of the match
+    pub start: usize,
+
+    /// Byte offset one past the end of the match
+    pub end: usize,
+
+    /// What was matched (entity type, pattern or category)
+    pub label: String,
+
+    /// Confidence for this span (0.0-1.0)
+    pub score: f32,
+}
+
+impl Span {
+    /// Create a new span
+    pub fn new(start: usize, end: usize, label: impl Into<String>, score: f32) -> Self {
+        Self {
+            start,
+            end,
+            label: label.into(),
+            score,
+        }
+    }
+
+    /// Check if this span overlaps another
+    pub fn overlaps(&self, other: &Span) -> bool {
+        self.start < other.end && other.start < self.end
+    }
+}
+
 /// Classifier performance tier
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ClassifierTier {
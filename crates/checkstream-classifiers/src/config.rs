@@ -1,9 +1,16 @@
 //! Configuration for classifiers and model loading
 
-use crate::{DeviceType, ModelConfig, ModelFormat, ModelSource};
+use crate::batching::BatchingConfig;
+use crate::calibration::Calibration;
+use crate::normalize::NormalizationConfig;
+use crate::patterns::{PatternOptions, PatternRule};
+use crate::pii::PiiConfig;
+use crate::secrets::SecretsConfig;
+use crate::topic::Topic;
+use crate::{ClassifierTier, DeviceType, ModelConfig, ModelFormat, ModelSource};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// Configuration for all classifiers
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -27,6 +34,312 @@ pub struct ClassifierConfig {
     /// Model cache directory
     #[serde(default = "default_models_dir")]
     pub models_dir: PathBuf,
+
+    /// PII detector settings
+    #[serde(default)]
+    pub pii: PiiConfig,
+
+    /// Secrets detector settings
+    #[serde(default)]
+    pub secrets: SecretsConfig,
+
+    /// Text normalization applied before pipelines classify
+    #[serde(default)]
+    pub normalization: NormalizationConfig,
+
+    /// Fail initialization on unresolved classifiers instead of using no-op
+    /// fallbacks (lenient if unset; callers may pick their own default)
+    #[serde(default)]
+    pub strict: Option<bool>,
+
+    /// Model registry file read by loader plugins (`models/registry.yaml` format)
+    #[serde(default)]
+    pub model_registry: Option<PathBuf>,
+
+    /// Load plugin-backed models on first use instead of at startup
+    #[serde(default)]
+    pub lazy_load: bool,
+
+    /// Micro-batching of concurrent requests to plugin-backed models
+    #[serde(default)]
+    pub batching: BatchingConfig,
+
+    /// Score calibration by classifier name
+    #[serde(default)]
+    pub calibration: HashMap<String, Calibration>,
+
+    /// Localized phrase packs added to the built-in lexicon classifiers
+    #[serde(default)]
+    pub lexicons: Vec<PathBuf>,
+
+    /// Changes to the phrase lists of the built-in lexicon classifiers, by
+    /// classifier name
+    #[serde(default)]
+    pub builtin_phrases: HashMap<String, BuiltinPhrasesSpec>,
+
+    /// User-defined pattern classifiers by name
+    ///
+    /// A definition named like a built-in classifier replaces it.
+    #[serde(default)]
+    pub patterns: HashMap<String, PatternClassifierSpec>,
+
+    /// Topic allow-list classifiers by name
+    #[serde(default)]
+    pub topics: HashMap<String, TopicClassifierSpec>,
+}
+
+/// Changes to one built-in lexicon classifier's phrase lists
+///
+/// Both maps are keyed by the category label the classifier reports.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BuiltinPhrasesSpec {
+    /// Lists replacing the built-in ones
+    #[serde(default)]
+    pub replace: HashMap<String, Vec<String>>,
+
+    /// Phrases added to the (possibly replaced) lists
+    #[serde(default)]
+    pub extend: HashMap<String, Vec<String>>,
+}
+
+/// User-defined pattern classifier (for config files)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PatternClassifierSpec {
+    /// YAML file holding the definition instead of declaring it inline
+    #[serde(default)]
+    pub file: Option<PathBuf>,
+
+    /// Match letter case exactly
+    #[serde(default)]
+    pub case_sensitive: bool,
+
+    /// Only match whole words
+    #[serde(default)]
+    pub word_boundary: bool,
+
+    /// Patterns by the category label their matches report
+    #[serde(default)]
+    pub categories: HashMap<String, PatternCategorySpec>,
+}
+
+/// Patterns of one category
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PatternCategorySpec {
+    /// Score of a match with weight 1.0
+    #[serde(default = "default_severity")]
+    pub severity: f32,
+
+    /// Literal phrases
+    #[serde(default, alias = "keywords")]
+    pub patterns: Vec<PatternEntrySpec>,
+
+    /// Regular expressions
+    #[serde(default)]
+    pub regex: Vec<PatternEntrySpec>,
+}
+
+/// A pattern, optionally weighted
+///
+/// A match scores the category severity times the weight, capped at 1.0.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum PatternEntrySpec {
+    Plain(String),
+    Weighted {
+        pattern: String,
+        #[serde(default = "default_weight")]
+        weight: f32,
+    },
+}
+
+impl PatternEntrySpec {
+    fn parts(&self) -> (&str, f32) {
+        match self {
+            Self::Plain(pattern) => (pattern, 1.0),
+            Self::Weighted { pattern, weight } => (pattern, *weight),
+        }
+    }
+}
+
+impl PatternClassifierSpec {
+    /// The definition itself, or the one its `file` points to
+    pub fn resolve(&self) -> checkstream_core::Result<Self> {
+        let Some(path) = &self.file else {
+            return Ok(self.clone());
+        };
+        if !self.categories.is_empty() || self.case_sensitive || self.word_boundary {
+            return Err(checkstream_core::Error::config(format!(
+                "Pattern classifier from {} must be defined entirely in that file",
+                path.display()
+            )));
+        }
+
+        let spec: Self = read_spec_file(path)?;
+        if spec.file.is_some() {
+            return Err(checkstream_core::Error::config(format!(
+                "{}: pattern files cannot reference other files",
+                path.display()
+            )));
+        }
+        Ok(spec)
+    }
+
+    /// Rules and options for [`PatternClassifier::with_rules`]
+    ///
+    /// Call on a [resolved](Self::resolve) definition.
+    ///
+    /// [`PatternClassifier::with_rules`]: crate::patterns::PatternClassifier::with_rules
+    pub fn to_rules(
+        &self,
+        name: &str,
+    ) -> checkstream_core::Result<(Vec<PatternRule>, PatternOptions)> {
+        let invalid = |message: String| {
+            checkstream_core::Error::config(format!("Pattern classifier '{}': {}", name, message))
+        };
+
+        let mut categories: Vec<_> = self.categories.iter().collect();
+        categories.sort_by_key(|(label, _)| label.as_str());
+
+        let mut rules = Vec::new();
+        for (label, category) in categories {
+            if !(0.0..=1.0).contains(&category.severity) {
+                return Err(invalid(format!(
+                    "severity of '{}' must be between 0 and 1",
+                    label
+                )));
+            }
+            let entries = category
+                .patterns
+                .iter()
+                .map(|entry| (entry, false))
+                .chain(category.regex.iter().map(|entry| (entry, true)));
+            for (entry, regex) in entries {
+                let (pattern, weight) = entry.parts();
+                if weight < 0.0 {
+                    return Err(invalid(format!("negative weight for '{}'", pattern)));
+                }
+                rules.push(PatternRule {
+                    label: label.clone(),
+                    pattern: pattern.to_string(),
+                    regex,
+                    score: (category.severity * weight).min(1.0),
+                });
+            }
+        }
+        if rules.is_empty() {
+            return Err(invalid("no patterns declared".to_string()));
+        }
+
+        let options = PatternOptions {
+            case_sensitive: self.case_sensitive,
+            word_boundary: self.word_boundary,
+        };
+        Ok((rules, options))
+    }
+}
+
+fn default_severity() -> f32 {
+    1.0
+}
+
+fn default_weight() -> f32 {
+    1.0
+}
+
+/// Topic classifier (for config files)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TopicClassifierSpec {
+    /// YAML file holding the definition instead of declaring it inline
+    #[serde(default)]
+    pub file: Option<PathBuf>,
+
+    /// Sentence-transformer in the model registry that embeds exemplars
+    ///
+    /// Exemplars are compared by shared words and spellings if unset.
+    #[serde(default)]
+    pub embedding_model: Option<String>,
+
+    /// Topics the deployment may discuss, by name
+    #[serde(default)]
+    pub allowed: HashMap<String, TopicSpec>,
+
+    /// Topics the deployment must not discuss, by name
+    #[serde(default)]
+    pub denied: HashMap<String, TopicSpec>,
+}
+
+/// Description of one topic
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TopicSpec {
+    /// Words and phrases that indicate the topic
+    #[serde(default)]
+    pub keywords: Vec<String>,
+
+    /// Example texts on the topic
+    #[serde(default)]
+    pub exemplars: Vec<String>,
+}
+
+impl TopicClassifierSpec {
+    /// The definition itself, or the one its `file` points to
+    pub fn resolve(&self) -> checkstream_core::Result<Self> {
+        let Some(path) = &self.file else {
+            return Ok(self.clone());
+        };
+        if self.embedding_model.is_some() || !self.allowed.is_empty() || !self.denied.is_empty() {
+            return Err(checkstream_core::Error::config(format!(
+                "Topic classifier from {} must be defined entirely in that file",
+                path.display()
+            )));
+        }
+
+        let spec: Self = read_spec_file(path)?;
+        if spec.file.is_some() {
+            return Err(checkstream_core::Error::config(format!(
+                "{}: topic files cannot reference other files",
+                path.display()
+            )));
+        }
+        Ok(spec)
+    }
+
+    /// Topics for [`TopicClassifier::new`], allowed first, each sorted by name
+    ///
+    /// [`TopicClassifier::new`]: crate::topic::TopicClassifier::new
+    pub fn to_topics(&self) -> Vec<Topic> {
+        let mut topics = Vec::new();
+        for (allowed, specs) in [(true, &self.allowed), (false, &self.denied)] {
+            let mut specs: Vec<_> = specs.iter().collect();
+            specs.sort_by_key(|(name, _)| name.as_str());
+            for (name, spec) in specs {
+                let topic = if allowed {
+                    Topic::allowed(name.clone())
+                } else {
+                    Topic::denied(name.clone())
+                };
+                topics.push(
+                    topic
+                        .with_keywords(spec.keywords.iter().cloned())
+                        .with_exemplars(spec.exemplars.iter().cloned()),
+                );
+            }
+        }
+        topics
+    }
+}
+
+/// Parse a YAML file a classifier definition points to
+fn read_spec_file<T: serde::de::DeserializeOwned>(path: &Path) -> checkstream_core::Result<T> {
+    let contents = std::fs::read_to_string(path).map_err(|e| {
+        checkstream_core::Error::config(format!("Failed to read {}: {}", path.display(), e))
+    })?;
+    serde_yaml::from_str(&contents)
+        .map_err(|e| checkstream_core::Error::config(format!("{}: {}", path.display(), e)))
 }
 
 /// Pipeline configuration specification
@@ -37,6 +350,10 @@ pub struct PipelineConfigSpec {
 
     /// Pipeline stages
     pub stages: Vec<StageConfigSpec>,
+
+    /// Skip remaining stages once the final decision scores at least this
+    #[serde(default)]
+    pub early_exit_threshold: Option<f32>,
 }
 
 /// Stage configuration
@@ -44,29 +361,115 @@ pub struct PipelineConfigSpec {
 #[serde(tag = "type", rename_all = "lowercase")]
 pub enum StageConfigSpec {
     /// Single classifier execution
-    Single { name: String, classifier: String },
+    Single {
+        name: String,
+        classifier: String,
+        #[serde(flatten)]
+        options: StageOptionsSpec,
+    },
 
     /// Parallel execution
     Parallel {
         name: String,
         classifiers: Vec<String>,
         aggregation: AggregationStrategySpec,
+        #[serde(flatten)]
+        options: StageOptionsSpec,
     },
 
     /// Sequential execution
     Sequential {
         name: String,
         classifiers: Vec<String>,
+        #[serde(flatten)]
+        options: StageOptionsSpec,
     },
 
-    /// Conditional execution
+    /// Conditional execution of one classifier or a nested list of stages
     Conditional {
         name: String,
-        classifier: String,
+        #[serde(default)]
+        classifier: Option<String>,
+        #[serde(default)]
+        stages: Vec<StageConfigSpec>,
         condition: ConditionSpec,
+        #[serde(flatten)]
+        options: StageOptionsSpec,
+    },
+
+    /// Cheapest classifiers first, escalating only on uncertain scores
+    Cascade {
+        name: String,
+        classifiers: Vec<String>,
+        #[serde(default)]
+        uncertainty: UncertaintyBandSpec,
+        #[serde(flatten)]
+        options: StageOptionsSpec,
     },
 }
 
+/// Scores a cascade escalates to the next classifier
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct UncertaintyBandSpec {
+    /// Lowest uncertain score; anything below is clearly clean
+    #[serde(default = "default_uncertain_low")]
+    pub low: f32,
+
+    /// Lowest certain positive score
+    #[serde(default = "default_uncertain_high")]
+    pub high: f32,
+}
+
+impl Default for UncertaintyBandSpec {
+    fn default() -> Self {
+        Self {
+            low: default_uncertain_low(),
+            high: default_uncertain_high(),
+        }
+    }
+}
+
+fn default_uncertain_low() -> f32 {
+    0.2
+}
+
+fn default_uncertain_high() -> f32 {
+    0.8
+}
+
+/// Timeouts and error handling shared by every stage type
+///
+/// Classifiers without a timeout here run to completion.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StageOptionsSpec {
+    /// Deadline for the whole stage, and the default for its classifiers
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+
+    /// Timeouts for individual classifiers in the stage
+    #[serde(default)]
+    pub classifier_timeouts_ms: HashMap<String, u64>,
+
+    /// What to do when a classifier errors or times out
+    #[serde(default)]
+    pub on_error: ErrorPolicySpec,
+}
+
+/// Error handling specification for a stage
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorPolicySpec {
+    /// Abort the pipeline
+    #[default]
+    Fail,
+
+    /// Leave the classifier out of the results
+    Skip,
+
+    /// Use a fixed score in place of the missing result
+    Substitute { score: f32 },
+}
+
 /// Aggregation strategy specification
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -92,11 +495,58 @@ pub enum ConditionSpec {
     /// Execute if specific classifier triggered
     ClassifierTriggered { classifier: String },
 
+    /// Execute if a classifier's score, or one of its labels' scores, is in
+    /// `min..max` (either bound may be omitted)
+    ClassifierScore {
+        classifier: String,
+        #[serde(default)]
+        label: Option<String>,
+        #[serde(default)]
+        min: Option<f32>,
+        #[serde(default)]
+        max: Option<f32>,
+    },
+
+    /// Execute if a classifier's top label is `label`, or a multi-label
+    /// classifier triggered it
+    ClassifierLabel { classifier: String, label: String },
+
+    /// Execute if the text length in characters is within `min..=max`
+    TextLength {
+        #[serde(default)]
+        min: Option<usize>,
+        #[serde(default)]
+        max: Option<usize>,
+    },
+
+    /// Execute if the `language` attribute is one of `languages`
+    ///
+    /// With `classifier`, that classifier's label (typically the built-in
+    /// `language`) is used instead; the attribute only counts when the
+    /// classifier has no result.
+    Language {
+        languages: Vec<String>,
+        #[serde(default)]
+        classifier: Option<String>,
+    },
+
+    /// Execute if the `role` attribute is one of `roles`
+    Role { roles: Vec<String> },
+
+    /// Execute if every condition holds
+    And(Vec<ConditionSpec>),
+
+    /// Execute if any condition holds
+    Or(Vec<ConditionSpec>),
+
+    /// Execute if the condition does not hold
+    Not(Box<ConditionSpec>),
+
     /// Always execute
     Always,
 }
 
-pub type StageCondition = dyn Fn(&[crate::pipeline::PipelineResult]) -> bool + Send + Sync;
+pub type StageCondition = dyn Fn(&crate::pipeline::ConditionContext<'_>) -> bool + Send + Sync;
 
 /// Model configuration specification (for YAML/config files)
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -170,6 +620,18 @@ impl Default for ClassifierConfig {
             default_device: DeviceSpec::Cpu,
             default_quantize: false,
             models_dir: default_models_dir(),
+            pii: PiiConfig::default(),
+            secrets: SecretsConfig::default(),
+            normalization: NormalizationConfig::default(),
+            strict: None,
+            model_registry: None,
+            lazy_load: false,
+            batching: BatchingConfig::default(),
+            calibration: HashMap::new(),
+            lexicons: Vec::new(),
+            builtin_phrases: HashMap::new(),
+            patterns: HashMap::new(),
+            topics: HashMap::new(),
         }
     }
 }
@@ -234,6 +696,16 @@ impl ClassifierConfig {
         self.models.keys().cloned().collect()
     }
 
+    /// Declared latency tier of a model (Tier B if unset or unrecognised)
+    pub fn model_tier(&self, name: &str) -> ClassifierTier {
+        let tier = self.models.get(name).and_then(|spec| spec.tier.as_deref());
+        match tier.map(str::to_ascii_uppercase).as_deref() {
+            Some("A") => ClassifierTier::A,
+            Some("C") => ClassifierTier::C,
+            _ => ClassifierTier::B,
+        }
+    }
+
     /// Get all pipeline names
     pub fn pipeline_names(&self) -> Vec<String> {
         self.pipelines.keys().cloned().collect()
@@ -261,33 +733,187 @@ impl AggregationStrategySpec {
     }
 }
 
+impl StageConfigSpec {
+    /// Stage name
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Single { name, .. }
+            | Self::Parallel { name, .. }
+            | Self::Sequential { name, .. }
+            | Self::Conditional { name, .. }
+            | Self::Cascade { name, .. } => name,
+        }
+    }
+}
+
+impl StageOptionsSpec {
+    /// Convert to runtime stage options
+    pub fn to_stage_options(&self) -> crate::pipeline::StageOptions {
+        crate::pipeline::StageOptions {
+            timeout: self.timeout_ms.map(std::time::Duration::from_millis),
+            classifier_timeouts: self
+                .classifier_timeouts_ms
+                .iter()
+                .map(|(name, ms)| (name.clone(), std::time::Duration::from_millis(*ms)))
+                .collect(),
+            on_error: match self.on_error {
+                ErrorPolicySpec::Fail => crate::pipeline::ErrorPolicy::Fail,
+                ErrorPolicySpec::Skip => crate::pipeline::ErrorPolicy::Skip,
+                ErrorPolicySpec::Substitute { score } => {
+                    crate::pipeline::ErrorPolicy::Substitute(score)
+                }
+            },
+        }
+    }
+}
+
+impl UncertaintyBandSpec {
+    /// Convert to runtime uncertainty band
+    pub fn to_band(&self) -> crate::pipeline::UncertaintyBand {
+        crate::pipeline::UncertaintyBand::new(self.low, self.high)
+    }
+}
+
 impl ConditionSpec {
     /// Convert to runtime condition function
     pub fn to_condition_fn(&self) -> Box<StageCondition> {
         match self {
             Self::AnyAboveThreshold { threshold } => {
                 let threshold = *threshold;
-                Box::new(move |results| results.iter().any(|r| r.result.score > threshold))
+                Box::new(move |context| context.results.iter().any(|r| r.result.score > threshold))
             }
             Self::AllAboveThreshold { threshold } => {
                 let threshold = *threshold;
-                Box::new(move |results| {
-                    !results.is_empty() && results.iter().all(|r| r.result.score > threshold)
+                Box::new(move |context| {
+                    !context.results.is_empty()
+                        && context.results.iter().all(|r| r.result.score > threshold)
                 })
             }
             Self::ClassifierTriggered { classifier } => {
                 let classifier_name = classifier.clone();
-                Box::new(move |results| {
-                    results
+                Box::new(move |context| {
+                    context
+                        .results
                         .iter()
                         .any(|r| r.classifier_name == classifier_name && r.result.score > 0.5)
                 })
             }
+            Self::ClassifierScore {
+                classifier,
+                label,
+                min,
+                max,
+            } => {
+                let (classifier, label, min, max) = (classifier.clone(), label.clone(), *min, *max);
+                Box::new(move |context| {
+                    latest_result(context, &classifier)
+                        .and_then(|result| match &label {
+                            Some(label) => label_score(result, label),
+                            None => Some(result.score),
+                        })
+                        .is_some_and(|score| {
+                            !matches!(min, Some(min) if score < min)
+                                && !matches!(max, Some(max) if score >= max)
+                        })
+                })
+            }
+            Self::ClassifierLabel { classifier, label } => {
+                let (classifier, label) = (classifier.clone(), label.clone());
+                Box::new(move |context| {
+                    latest_result(context, &classifier).is_some_and(|result| {
+                        result.label == label
+                            || result
+                                .triggered_labels()
+                                .any(|triggered| triggered.label == label)
+                    })
+                })
+            }
+            Self::TextLength { min, max } => {
+                let (min, max) = (*min, *max);
+                Box::new(move |context| {
+                    let length = context.text.chars().count();
+                    !matches!(min, Some(min) if length < min)
+                        && !matches!(max, Some(max) if length > max)
+                })
+            }
+            Self::Language {
+                languages,
+                classifier,
+            } => {
+                let attribute = attribute_in("language", languages);
+                let Some(classifier) = classifier.clone() else {
+                    return attribute;
+                };
+                let languages = languages.clone();
+                Box::new(move |context| match latest_result(context, &classifier) {
+                    Some(result) => languages
+                        .iter()
+                        .any(|language| language.eq_ignore_ascii_case(&result.label)),
+                    None => attribute(context),
+                })
+            }
+            Self::Role { roles } => attribute_in("role", roles),
+            Self::And(conditions) => {
+                let conditions: Vec<_> = conditions.iter().map(Self::to_condition_fn).collect();
+                Box::new(move |context| conditions.iter().all(|condition| condition(context)))
+            }
+            Self::Or(conditions) => {
+                let conditions: Vec<_> = conditions.iter().map(Self::to_condition_fn).collect();
+                Box::new(move |context| conditions.iter().any(|condition| condition(context)))
+            }
+            Self::Not(condition) => {
+                let condition = condition.to_condition_fn();
+                Box::new(move |context| !condition(context))
+            }
             Self::Always => Box::new(|_| true),
         }
     }
 }
 
+/// Most recent result from a classifier, if it has run
+fn latest_result<'a>(
+    context: &crate::pipeline::ConditionContext<'a>,
+    classifier: &str,
+) -> Option<&'a crate::ClassificationResult> {
+    context
+        .results
+        .iter()
+        .rev()
+        .find(|r| r.classifier_name == classifier)
+        .map(|r| &r.result)
+}
+
+/// Score of one label from a multi-label or multi-class result
+fn label_score(result: &crate::ClassificationResult, label: &str) -> Option<f32> {
+    result
+        .metadata
+        .labels
+        .iter()
+        .find(|l| l.label == label)
+        .map(|l| l.score)
+        .or_else(|| {
+            result
+                .metadata
+                .all_scores
+                .iter()
+                .flatten()
+                .find(|(name, _)| name == label)
+                .map(|(_, score)| *score)
+        })
+}
+
+/// Condition on a request attribute matching one of `values` (case-insensitive)
+fn attribute_in(attribute: &'static str, values: &[String]) -> Box<StageCondition> {
+    let values = values.to_vec();
+    Box::new(move |context| {
+        context.attributes.get(attribute).is_some_and(|actual| {
+            values
+                .iter()
+                .any(|value| value.eq_ignore_ascii_case(actual))
+        })
+    })
+}
+
 impl DeviceSpec {
     /// Convert to DeviceType
     pub fn to_device_type(&self) -> DeviceType {
@@ -388,4 +1014,173 @@ models:
         let config = ClassifierConfig::from_yaml(yaml).unwrap();
         assert!(config.models.contains_key("test"));
     }
+
+    #[test]
+    fn test_normalization_config() {
+        let config = ClassifierConfig::from_yaml("default_device: cpu").unwrap();
+        assert!(config.normalization.enabled);
+        assert!(config.normalization.leet);
+        assert!(!config.normalization.decode_base64);
+
+        let yaml = r#"
+normalization:
+  leet: false
+  decode_base64: true
+"#;
+        let config = ClassifierConfig::from_yaml(yaml).unwrap();
+        assert!(config.normalization.nfkc);
+        assert!(!config.normalization.leet);
+        assert!(config.normalization.decode_base64);
+    }
+
+    #[test]
+    fn test_condition_expressions() {
+        use crate::pipeline::{ConditionContext, PipelineResult};
+        use crate::{ClassificationResult, LabelScore};
+
+        // Conditions appear inside stages, which is where the map form parses
+        let yaml = r#"
+type: conditional
+name: escalate
+classifier: toxicity
+condition:
+  and:
+    - or:
+        - classifier_label: {classifier: toxicity, label: threat}
+        - classifier_score: {classifier: pii, min: 0.9}
+    - classifier_score: {classifier: toxicity, label: insult, max: 0.5}
+    - not:
+        text_length: {max: 3}
+    - role: {roles: [user]}
+"#;
+        let StageConfigSpec::Conditional { condition, .. } = serde_yaml::from_str(yaml).unwrap()
+        else {
+            panic!("expected a conditional stage");
+        };
+        let condition = condition.to_condition_fn();
+
+        let results = vec![PipelineResult {
+            stage_name: "safety".to_string(),
+            classifier_name: "toxicity".to_string(),
+            result: ClassificationResult::multi_label(vec![
+                LabelScore::new("insult", 0.2, 0.5),
+                LabelScore::new("threat", 0.7, 0.5),
+            ]),
+            stage_latency_us: 0,
+        }];
+        let user = HashMap::from([("role".to_string(), "User".to_string())]);
+        let none = HashMap::new();
+        let context = |text, attributes| ConditionContext {
+            results: &results,
+            text,
+            attributes,
+        };
+
+        assert!(condition(&context("I will find you", &user)));
+        // Too short
+        assert!(!condition(&context("hey", &user)));
+        // No role attribute
+        assert!(!condition(&context("I will find you", &none)));
+
+        let language = ConditionSpec::Language {
+            languages: vec!["de".to_string(), "fr".to_string()],
+            classifier: None,
+        }
+        .to_condition_fn();
+        let french = HashMap::from([("language".to_string(), "fr".to_string())]);
+        assert!(language(&context("bonjour", &french)));
+        assert!(!language(&context("hello", &user)));
+    }
+
+    #[test]
+    fn test_language_condition_falls_back_to_classifier() {
+        use crate::pipeline::{ConditionContext, PipelineResult};
+        use crate::ClassificationResult;
+
+        let yaml = r#"
+type: conditional
+name: french
+classifier: xlm-roberta
+condition:
+  language: {languages: [fr], classifier: language}
+"#;
+        let StageConfigSpec::Conditional { condition, .. } = serde_yaml::from_str(yaml).unwrap()
+        else {
+            panic!("expected a conditional stage");
+        };
+        let condition = condition.to_condition_fn();
+
+        let results = vec![PipelineResult {
+            stage_name: "detect-language".to_string(),
+            classifier_name: "language".to_string(),
+            result: ClassificationResult {
+                label: "fr".to_string(),
+                score: 0.0,
+                metadata: Default::default(),
+                latency_us: 0,
+            },
+            stage_latency_us: 0,
+        }];
+        let context = |attributes| ConditionContext {
+            results: &results,
+            text: "bonjour tout le monde",
+            attributes,
+        };
+
+        let none = HashMap::new();
+        assert!(condition(&context(&none)));
+        // Detection wins over a claimed language
+        let english = HashMap::from([("language".to_string(), "en".to_string())]);
+        assert!(condition(&context(&english)));
+
+        // Without a detection result the attribute decides
+        let no_results = ConditionContext {
+            results: &[],
+            text: "bonjour tout le monde",
+            attributes: &english,
+        };
+        assert!(!condition(&no_results));
+    }
+
+    #[test]
+    fn test_pattern_classifier_spec_rules() {
+        let yaml = r#"
+case_sensitive: true
+categories:
+  threat:
+    severity: 0.9
+    patterns: ["kill you", {pattern: "hurt you", weight: 0.5}]
+  spam:
+    keywords: [click here]
+    regex: ['free \w+']
+"#;
+        let spec: PatternClassifierSpec = serde_yaml::from_str(yaml).unwrap();
+        let (rules, options) = spec.resolve().unwrap().to_rules("custom").unwrap();
+        assert!(options.case_sensitive);
+        assert!(!options.word_boundary);
+
+        let summary: Vec<_> = rules
+            .iter()
+            .map(|r| (r.label.as_str(), r.pattern.as_str(), r.regex, r.score))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("spam", "click here", false, 1.0),
+                ("spam", r"free \w+", true, 1.0),
+                ("threat", "kill you", false, 0.9),
+                ("threat", "hurt you", false, 0.45),
+            ]
+        );
+
+        let invalid: PatternClassifierSpec =
+            serde_yaml::from_str("categories:\n  x:\n    severity: 2.0\n    patterns: [a]\n")
+                .unwrap();
+        assert!(invalid.to_rules("custom").is_err());
+        let empty: PatternClassifierSpec = serde_yaml::from_str("categories: {}").unwrap();
+        assert!(empty.to_rules("custom").is_err());
+        let mixed: PatternClassifierSpec =
+            serde_yaml::from_str("file: p.yaml\nword_boundary: true").unwrap();
+        assert!(mixed.resolve().is_err());
+    }
 }
@@ -0,0 +1,297 @@
+//! Classifier evaluation against labelled data
+//!
+//! Scores a labelled dataset and reports precision, recall and F1, ROC and
+//! precision-recall curves, and the threshold that keeps the false-positive
+//! rate under a target. Predictions follow the policy engine: a score at or
+//! above the threshold is positive.
+
+use checkstream_core::Result;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// One labelled text from a JSONL dataset
+///
+/// Each line is an object such as `{"text": "...", "label": true}`; `label`
+/// may also be `1` or `0`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct LabelledExample {
+    /// Text to classify
+    pub text: String,
+
+    /// Whether the text should be flagged
+    #[serde(deserialize_with = "deserialize_label")]
+    pub label: bool,
+}
+
+fn deserialize_label<'de, D>(deserializer: D) -> std::result::Result<bool, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Label {
+        Bool(bool),
+        Int(i64),
+    }
+
+    match Label::deserialize(deserializer)? {
+        Label::Bool(label) => Ok(label),
+        Label::Int(0) => Ok(false),
+        Label::Int(1) => Ok(true),
+        Label::Int(other) => Err(serde::de::Error::custom(format!(
+            "label must be true, false, 1 or 0 (got {})",
+            other
+        ))),
+    }
+}
+
+/// Read a JSONL dataset, skipping blank lines
+pub fn load_dataset(path: impl AsRef<Path>) -> Result<Vec<LabelledExample>> {
+    let path = path.as_ref();
+    let contents = std::fs::read_to_string(path).map_err(|e| {
+        checkstream_core::Error::config(format!("Failed to read {}: {}", path.display(), e))
+    })?;
+
+    contents
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(idx, line)| {
+            serde_json::from_str(line).map_err(|e| {
+                checkstream_core::Error::config(format!(
+                    "{}:{}: invalid example: {}",
+                    path.display(),
+                    idx + 1,
+                    e
+                ))
+            })
+        })
+        .collect()
+}
+
+/// Confusion counts and derived metrics at one threshold
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ThresholdMetrics {
+    pub threshold: f32,
+    pub true_positives: usize,
+    pub false_positives: usize,
+    pub true_negatives: usize,
+    pub false_negatives: usize,
+    pub precision: f32,
+    pub recall: f32,
+    pub f1: f32,
+    pub false_positive_rate: f32,
+}
+
+/// Scores of a labelled dataset, ready for threshold analysis
+#[derive(Debug, Clone)]
+pub struct Evaluation {
+    /// `(score, is positive)` sorted by descending score
+    scores: Vec<(f32, bool)>,
+    positives: usize,
+}
+
+impl Evaluation {
+    /// Build an evaluation from `(score, is positive)` pairs
+    pub fn new(mut scores: Vec<(f32, bool)>) -> Self {
+        scores.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+        let positives = scores.iter().filter(|(_, positive)| *positive).count();
+        Self { scores, positives }
+    }
+
+    /// Number of examples
+    pub fn len(&self) -> usize {
+        self.scores.len()
+    }
+
+    /// Whether there are no examples
+    pub fn is_empty(&self) -> bool {
+        self.scores.is_empty()
+    }
+
+    /// Number of positive examples
+    pub fn positives(&self) -> usize {
+        self.positives
+    }
+
+    /// The `(score, is positive)` pairs, highest score first
+    pub fn scores(&self) -> &[(f32, bool)] {
+        &self.scores
+    }
+
+    /// Metrics when scores at or above `threshold` are flagged
+    pub fn metrics_at(&self, threshold: f32) -> ThresholdMetrics {
+        let flagged = self
+            .scores
+            .iter()
+            .take_while(|(score, _)| *score >= threshold);
+        let (mut tp, mut fp) = (0, 0);
+        for (_, positive) in flagged {
+            if *positive {
+                tp += 1;
+            } else {
+                fp += 1;
+            }
+        }
+        self.metrics(threshold, tp, fp)
+    }
+
+    /// Metrics at every distinct score, from the highest threshold down
+    ///
+    /// Each point gives one ROC point (`false_positive_rate`, `recall`) and
+    /// one precision-recall point.
+    pub fn curve(&self) -> Vec<ThresholdMetrics> {
+        let mut points = Vec::new();
+        let (mut tp, mut fp) = (0, 0);
+        for (idx, &(score, positive)) in self.scores.iter().enumerate() {
+            if positive {
+                tp += 1;
+            } else {
+                fp += 1;
+            }
+            if !matches!(self.scores.get(idx + 1), Some(&(next, _)) if next == score) {
+                points.push(self.metrics(score, tp, fp));
+            }
+        }
+        points
+    }
+
+    /// Area under the ROC curve (0.5 is chance)
+    pub fn roc_auc(&self) -> f32 {
+        let mut area = 0.0;
+        let (mut prev_fpr, mut prev_tpr) = (0.0, 0.0);
+        for point in self.curve() {
+            area += (point.false_positive_rate - prev_fpr) * (point.recall + prev_tpr) / 2.0;
+            prev_fpr = point.false_positive_rate;
+            prev_tpr = point.recall;
+        }
+        area + (1.0 - prev_fpr) * (1.0 + prev_tpr) / 2.0
+    }
+
+    /// Average precision: precision weighted by each recall increment
+    pub fn average_precision(&self) -> f32 {
+        let mut total = 0.0;
+        let mut prev_recall = 0.0;
+        for point in self.curve() {
+            total += (point.recall - prev_recall) * point.precision;
+            prev_recall = point.recall;
+        }
+        total
+    }
+
+    /// Lowest threshold whose false-positive rate is at most `target_fpr`
+    ///
+    /// This flags as many positives as the target allows. Returns `None` if
+    /// even the highest score exceeds the target.
+    pub fn threshold_for_fpr(&self, target_fpr: f32) -> Option<ThresholdMetrics> {
+        self.curve()
+            .into_iter()
+            .take_while(|point| point.false_positive_rate <= target_fpr)
+            .last()
+    }
+
+    /// Threshold with the highest F1
+    pub fn best_f1(&self) -> Option<ThresholdMetrics> {
+        self.curve()
+            .into_iter()
+            .fold(None, |best, point| match best {
+                Some(best) if best.f1 >= point.f1 => Some(best),
+                _ => Some(point),
+            })
+    }
+
+    fn metrics(&self, threshold: f32, tp: usize, fp: usize) -> ThresholdMetrics {
+        let negatives = self.scores.len() - self.positives;
+        let ratio = |num: usize, den: usize| {
+            if den == 0 {
+                0.0
+            } else {
+                num as f32 / den as f32
+            }
+        };
+        let precision = ratio(tp, tp + fp);
+        let recall = ratio(tp, self.positives);
+        let f1 = if precision + recall > 0.0 {
+            2.0 * precision * recall / (precision + recall)
+        } else {
+            0.0
+        };
+
+        ThresholdMetrics {
+            threshold,
+            true_positives: tp,
+            false_positives: fp,
+            true_negatives: negatives - fp,
+            false_negatives: self.positives - tp,
+            precision,
+            recall,
+            f1,
+            false_positive_rate: ratio(fp, negatives),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn evaluation() -> Evaluation {
+        Evaluation::new(vec![
+            (0.9, true),
+            (0.8, true),
+            (0.7, false),
+            (0.6, true),
+            (0.4, false),
+            (0.4, false),
+            (0.1, false),
+        ])
+    }
+
+    #[test]
+    fn test_metrics_at_threshold() {
+        let metrics = evaluation().metrics_at(0.6);
+        assert_eq!(metrics.true_positives, 3);
+        assert_eq!(metrics.false_positives, 1);
+        assert_eq!(metrics.true_negatives, 3);
+        assert_eq!(metrics.false_negatives, 0);
+        assert_eq!(metrics.precision, 0.75);
+        assert_eq!(metrics.recall, 1.0);
+        assert!((metrics.f1 - 6.0 / 7.0).abs() < 1e-6);
+        assert_eq!(metrics.false_positive_rate, 0.25);
+    }
+
+    #[test]
+    fn test_curve_and_threshold_selection() {
+        let evaluation = evaluation();
+        let curve = evaluation.curve();
+        let thresholds: Vec<f32> = curve.iter().map(|point| point.threshold).collect();
+        assert_eq!(thresholds, vec![0.9, 0.8, 0.7, 0.6, 0.4, 0.1]);
+
+        assert_eq!(evaluation.threshold_for_fpr(0.0).unwrap().threshold, 0.8);
+        assert_eq!(evaluation.threshold_for_fpr(0.3).unwrap().threshold, 0.6);
+        assert_eq!(evaluation.best_f1().unwrap().threshold, 0.6);
+
+        // One negative outranks one of three positives
+        assert!((evaluation.roc_auc() - 11.0 / 12.0).abs() < 1e-6);
+        assert!(evaluation.average_precision() > 0.9);
+    }
+
+    #[test]
+    fn test_load_dataset() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let path = file.path();
+        std::fs::write(
+            path,
+            "{\"text\": \"you idiot\", \"label\": true}\n\n{\"text\": \"hello\", \"label\": 0}\n",
+        )
+        .unwrap();
+        let examples = load_dataset(path).unwrap();
+        assert_eq!(examples.len(), 2);
+        assert!(examples[0].label);
+        assert!(!examples[1].label);
+
+        std::fs::write(path, "{\"text\": \"hello\", \"label\": 2}\n").unwrap();
+        let err = load_dataset(path).unwrap_err();
+        assert!(err.to_string().contains(":1:"));
+    }
+}
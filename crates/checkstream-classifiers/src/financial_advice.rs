@@ -15,9 +15,14 @@
 //! - COBS 4: Fair, clear, and not misleading
 //! - Consumer Duty: Acting in customers' best interests
 
-use crate::classifier::{ClassificationMetadata, ClassificationResult, Classifier, ClassifierTier};
+use crate::classifier::{
+    ClassificationMetadata, ClassificationResult, Classifier, ClassifierTier, Span,
+};
+use crate::incremental::{IncrementalScanner, SpanDetector, SpanScanner};
+use crate::lexicon::{self, CategoryPhrases, LexiconPack};
 use aho_corasick::AhoCorasick;
 use checkstream_core::Result;
+use std::collections::HashMap;
 use std::time::Instant;
 
 /// Categories of financial content based on regulatory risk
@@ -36,6 +41,15 @@ pub enum AdviceCategory {
 }
 
 impl AdviceCategory {
+    /// Every category, from highest risk to lowest
+    pub const ALL: [Self; 5] = [
+        Self::ProhibitedClaim,
+        Self::Suitability,
+        Self::PersonalAdvice,
+        Self::Guidance,
+        Self::Information,
+    ];
+
     /// Get the risk score for this category (higher = more regulatory risk)
     pub fn risk_score(&self) -> f32 {
         match self {
@@ -71,6 +85,7 @@ impl AdviceCategory {
 }
 
 /// Pattern-based financial advice classifier
+#[derive(Clone)]
 pub struct FinancialAdviceClassifier {
     name: String,
     /// Prohibited claims (highest risk)
@@ -98,6 +113,20 @@ impl FinancialAdviceClassifier {
 
     /// Create with a custom name
     pub fn with_name(name: impl Into<String>) -> Result<Self> {
+        Self::with_lexicons(name, &[])
+    }
+
+    /// Create with a custom name, adding the financial advice phrases of `packs`
+    pub fn with_lexicons(name: impl Into<String>, packs: &[LexiconPack]) -> Result<Self> {
+        let mut phrases = Self::default_phrases();
+        for pack in packs {
+            lexicon::merge_categories(&mut phrases, pack.phrases_for("financial-advice"));
+        }
+        Self::with_phrases(name, phrases)
+    }
+
+    /// Built-in phrases by category label
+    pub fn default_phrases() -> CategoryPhrases {
         // Prohibited claims - risk guarantees, unrealistic returns
         let prohibited_claims_patterns = vec![
             "guaranteed return".to_string(),
@@ -216,6 +245,45 @@ impl FinancialAdviceClassifier {
             "inheritance tax".to_string(),
         ];
 
+        HashMap::from([
+            (
+                AdviceCategory::ProhibitedClaim.label().to_string(),
+                prohibited_claims_patterns,
+            ),
+            (
+                AdviceCategory::Suitability.label().to_string(),
+                suitability_patterns,
+            ),
+            (
+                AdviceCategory::PersonalAdvice.label().to_string(),
+                personal_advice_patterns,
+            ),
+            (
+                AdviceCategory::Guidance.label().to_string(),
+                guidance_patterns,
+            ),
+            (
+                AdviceCategory::Information.label().to_string(),
+                information_patterns,
+            ),
+        ])
+    }
+
+    /// Create with a custom name and phrases by category label
+    ///
+    /// Categories missing from `phrases` match nothing.
+    pub fn with_phrases(name: impl Into<String>, mut phrases: CategoryPhrases) -> Result<Self> {
+        let name = name.into();
+        let known = AdviceCategory::ALL.map(|category| category.label());
+        lexicon::check_categories(&name, &phrases, &known)?;
+        let mut take =
+            |category: AdviceCategory| phrases.remove(category.label()).unwrap_or_default();
+        let prohibited_claims_patterns = take(AdviceCategory::ProhibitedClaim);
+        let suitability_patterns = take(AdviceCategory::Suitability);
+        let personal_advice_patterns = take(AdviceCategory::PersonalAdvice);
+        let guidance_patterns = take(AdviceCategory::Guidance);
+        let information_patterns = take(AdviceCategory::Information);
+
         let prohibited_claims = Self::build_matcher(&prohibited_claims_patterns)?;
         let suitability = Self::build_matcher(&suitability_patterns)?;
         let personal_advice = Self::build_matcher(&personal_advice_patterns)?;
@@ -223,7 +291,7 @@ impl FinancialAdviceClassifier {
         let information = Self::build_matcher(&information_patterns)?;
 
         Ok(Self {
-            name: name.into(),
+            name,
             prohibited_claims,
             prohibited_claims_patterns,
             suitability,
@@ -250,58 +318,81 @@ impl FinancialAdviceClassifier {
             })
     }
 
-    /// Detect the highest-risk category in the text
-    fn detect_category(&self, text: &str) -> (AdviceCategory, Vec<(usize, usize, String)>) {
-        let mut matches = Vec::new();
-
-        // Check categories from highest risk to lowest
-        // Prohibited claims
-        for m in self.prohibited_claims.find_iter(text) {
-            let pattern = &self.prohibited_claims_patterns[m.pattern().as_usize()];
-            matches.push((m.start(), m.end(), pattern.clone()));
-        }
-        if !matches.is_empty() {
-            return (AdviceCategory::ProhibitedClaim, matches);
+    /// Matcher and phrases for a category
+    fn matcher(&self, category: AdviceCategory) -> (&AhoCorasick, &[String]) {
+        match category {
+            AdviceCategory::ProhibitedClaim => {
+                (&self.prohibited_claims, &self.prohibited_claims_patterns)
+            }
+            AdviceCategory::Suitability => (&self.suitability, &self.suitability_patterns),
+            AdviceCategory::PersonalAdvice => {
+                (&self.personal_advice, &self.personal_advice_patterns)
+            }
+            AdviceCategory::Guidance => (&self.guidance, &self.guidance_patterns),
+            AdviceCategory::Information => (&self.information, &self.information_patterns),
         }
+    }
+}
 
-        // Suitability statements
-        for m in self.suitability.find_iter(text) {
-            let pattern = &self.suitability_patterns[m.pattern().as_usize()];
-            matches.push((m.start(), m.end(), pattern.clone()));
-        }
-        if !matches.is_empty() {
-            return (AdviceCategory::Suitability, matches);
+impl SpanDetector for FinancialAdviceClassifier {
+    /// Matches of every category, each scored with its category's risk score
+    fn detect(&self, text: &str) -> Vec<Span> {
+        let mut spans = Vec::new();
+        for category in AdviceCategory::ALL {
+            let (matcher, patterns) = self.matcher(category);
+            spans.extend(matcher.find_iter(text).map(|m| {
+                let pattern = patterns[m.pattern().as_usize()].as_str();
+                Span::new(m.start(), m.end(), pattern, category.risk_score())
+            }));
         }
+        spans
+    }
 
-        // Personal advice
-        for m in self.personal_advice.find_iter(text) {
-            let pattern = &self.personal_advice_patterns[m.pattern().as_usize()];
-            matches.push((m.start(), m.end(), pattern.clone()));
-        }
-        if !matches.is_empty() {
-            return (AdviceCategory::PersonalAdvice, matches);
-        }
+    fn summarize(&self, spans: Vec<Span>) -> ClassificationResult {
+        // The highest-risk category with a match wins; no match is information
+        let category = AdviceCategory::ALL
+            .into_iter()
+            .find(|category| spans.iter().any(|span| span.score == category.risk_score()))
+            .unwrap_or(AdviceCategory::Information);
+        let score = category.risk_score();
+        let label = category.label().to_string();
+        let spans: Vec<Span> = spans
+            .into_iter()
+            .filter(|span| span.score == score)
+            .collect();
 
-        // Guidance (lower risk but still worth noting)
-        for m in self.guidance.find_iter(text) {
-            let pattern = &self.guidance_patterns[m.pattern().as_usize()];
-            matches.push((m.start(), m.end(), pattern.clone()));
-        }
-        if !matches.is_empty() {
-            return (AdviceCategory::Guidance, matches);
+        let mut extra: Vec<(String, String)> = spans
+            .iter()
+            .map(|span| ("matched_pattern".to_string(), span.label.clone()))
+            .collect();
+        extra.push(("category".to_string(), label.clone()));
+        if let Some(fca_ref) = category.fca_reference() {
+            extra.push(("fca_reference".to_string(), fca_ref.to_string()));
         }
 
-        // Information (safe - but we note it for completeness)
-        for m in self.information.find_iter(text) {
-            let pattern = &self.information_patterns[m.pattern().as_usize()];
-            matches.push((m.start(), m.end(), pattern.clone()));
-        }
-        if !matches.is_empty() {
-            return (AdviceCategory::Information, matches);
+        ClassificationResult {
+            label,
+            score,
+            metadata: ClassificationMetadata {
+                spans,
+                extra,
+                ..Default::default()
+            },
+            latency_us: 0,
         }
+    }
 
-        // Default to information if no patterns match
-        (AdviceCategory::Information, matches)
+    fn lookback(&self) -> usize {
+        AdviceCategory::ALL
+            .into_iter()
+            .map(|category| self.matcher(category).0.max_pattern_len())
+            .max()
+            .unwrap_or(0)
+            .saturating_sub(1)
+    }
+
+    fn matches_can_grow(&self) -> bool {
+        false
     }
 }
 
@@ -310,30 +401,10 @@ impl Classifier for FinancialAdviceClassifier {
     async fn classify(&self, text: &str) -> Result<ClassificationResult> {
         let start = Instant::now();
 
-        let (category, matches) = self.detect_category(text);
-        let score = category.risk_score();
-        let label = category.label().to_string();
+        let mut result = self.summarize(self.detect(text));
+        result.latency_us = start.elapsed().as_micros() as u64;
 
-        let mut extra = Vec::new();
-        for (_, _, pattern) in &matches {
-            extra.push(("matched_pattern".to_string(), pattern.clone()));
-        }
-        extra.push(("category".to_string(), label.clone()));
-        if let Some(fca_ref) = category.fca_reference() {
-            extra.push(("fca_reference".to_string(), fca_ref.to_string()));
-        }
-        let metadata = ClassificationMetadata {
-            spans: matches.iter().map(|(s, e, _)| (*s, *e)).collect(),
-            extra,
-            ..Default::default()
-        };
-
-        Ok(ClassificationResult {
-            label,
-            score,
-            metadata,
-            latency_us: start.elapsed().as_micros() as u64,
-        })
+        Ok(result)
     }
 
     fn name(&self) -> &str {
@@ -343,6 +414,10 @@ impl Classifier for FinancialAdviceClassifier {
     fn tier(&self) -> ClassifierTier {
         ClassifierTier::A // Pattern-based, fast
     }
+
+    fn incremental(&self) -> Option<Box<dyn IncrementalScanner>> {
+        Some(Box::new(SpanScanner::new(self.clone())))
+    }
 }
 
 #[cfg(test)]
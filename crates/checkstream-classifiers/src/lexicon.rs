@@ -0,0 +1,276 @@
+//! Localized phrase packs for the lexicon classifiers
+//!
+//! The built-in toxicity, profanity, sentiment, prompt injection and financial
+//! advice classifiers match English phrases. A pack adds phrases in another
+//! language to each of them, so one classifier covers every language it has
+//! a pack for:
+//!
+//! ```yaml
+//! language: fr
+//! toxicity: [connard, "ferme ta gueule"]
+//! profanity: [merde, putain]
+//! sentiment:
+//!   positive: [excellent, merci]
+//!   negative: [nul, horrible]
+//! prompt_injection:
+//!   instruction_override: ["ignore les instructions précédentes"]
+//! financial_advice:
+//!   prohibited_claim: ["rendement garanti"]
+//! ```
+//!
+//! Categories are keyed by the label the classifier reports for them. Phrases
+//! are lowercased on load; matching is case-insensitive for ASCII letters
+//! only, so accented capitals in the text are not matched.
+//!
+//! The `builtin_phrases` section of the classifier configuration replaces or
+//! extends the same lists (see [`crate::config::BuiltinPhrasesSpec`]).
+
+use crate::financial_advice::AdviceCategory;
+use crate::normalize::TextNormalizer;
+use crate::prompt_injection::InjectionCategory;
+use checkstream_core::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Phrase lists of a lexicon classifier by category label
+pub type CategoryPhrases = HashMap<String, Vec<String>>;
+
+/// Built-in lexicon classifiers whose phrase lists can be changed
+pub const LEXICON_CLASSIFIERS: [&str; 5] = [
+    "toxicity",
+    "profanity",
+    "sentiment",
+    "prompt-injection",
+    "financial-advice",
+];
+
+/// Sentiment categories a pack may extend
+pub(crate) const SENTIMENT_CATEGORIES: [&str; 2] = ["positive", "negative"];
+
+/// Phrases in one language for the lexicon classifiers
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LexiconPack {
+    /// ISO 639-1 code of the pack's language
+    pub language: String,
+
+    /// Toxic words and phrases
+    #[serde(default)]
+    pub toxicity: Vec<String>,
+
+    /// Profanity matched by the `profanity` classifier
+    #[serde(default)]
+    pub profanity: Vec<String>,
+
+    /// Sentiment phrases by label (`positive`, `negative`)
+    #[serde(default)]
+    pub sentiment: CategoryPhrases,
+
+    /// Prompt injection phrases by category label
+    #[serde(default)]
+    pub prompt_injection: CategoryPhrases,
+
+    /// Financial advice phrases by category label
+    #[serde(default)]
+    pub financial_advice: CategoryPhrases,
+}
+
+impl LexiconPack {
+    /// Parse a pack from YAML
+    pub fn from_yaml(yaml: &str) -> Result<Self> {
+        let mut pack: Self = serde_yaml::from_str(yaml)
+            .map_err(|e| checkstream_core::Error::config(format!("Invalid lexicon pack: {}", e)))?;
+        pack.validate()?;
+        pack.lowercase();
+        Ok(pack)
+    }
+
+    /// Load a pack from a YAML file
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path).map_err(|e| {
+            checkstream_core::Error::config(format!("Failed to read {}: {}", path.display(), e))
+        })?;
+        Self::from_yaml(&contents)
+            .map_err(|e| checkstream_core::Error::config(format!("{}: {}", path.display(), e)))
+    }
+
+    /// Load every pack in `paths`, in order
+    pub fn load_all(paths: &[PathBuf]) -> Result<Vec<Self>> {
+        paths.iter().map(Self::from_file).collect()
+    }
+
+    /// Phrases the pack adds to one of [`LEXICON_CLASSIFIERS`], by category
+    ///
+    /// The toxicity and profanity lists are reported under the `toxic` and
+    /// `profanity` labels.
+    pub fn phrases_for(&self, classifier: &str) -> CategoryPhrases {
+        match classifier {
+            "toxicity" => HashMap::from([("toxic".to_string(), self.toxicity.clone())]),
+            "profanity" => HashMap::from([("profanity".to_string(), self.profanity.clone())]),
+            "sentiment" => self.sentiment.clone(),
+            "prompt-injection" => self.prompt_injection.clone(),
+            "financial-advice" => self.financial_advice.clone(),
+            _ => HashMap::new(),
+        }
+    }
+
+    /// Copy of the pack with each phrase also in its normalized form
+    ///
+    /// Lexicon classifiers in a normalizing pipeline see text with diacritics
+    /// stripped and homoglyphs folded; the folded phrases match that text.
+    pub fn folded(&self, normalizer: &TextNormalizer) -> Self {
+        let fold_map = |sections: &CategoryPhrases| {
+            let mut sections = sections.clone();
+            fold_categories(&mut sections, normalizer);
+            sections
+        };
+
+        Self {
+            language: self.language.clone(),
+            toxicity: with_folded(&self.toxicity, normalizer),
+            profanity: with_folded(&self.profanity, normalizer),
+            sentiment: fold_map(&self.sentiment),
+            prompt_injection: fold_map(&self.prompt_injection),
+            financial_advice: fold_map(&self.financial_advice),
+        }
+    }
+
+    /// Reject categories the classifiers don't have
+    fn validate(&self) -> Result<()> {
+        let injection = InjectionCategory::ATTACKS.map(|category| category.label());
+        let advice = AdviceCategory::ALL.map(|category| category.label());
+
+        for (section, categories, known) in [
+            ("sentiment", &self.sentiment, &SENTIMENT_CATEGORIES[..]),
+            ("prompt_injection", &self.prompt_injection, &injection[..]),
+            ("financial_advice", &self.financial_advice, &advice[..]),
+        ] {
+            let owner = format!("{} in '{}' lexicon pack", section, self.language);
+            check_categories(&owner, categories, known)?;
+        }
+        Ok(())
+    }
+
+    fn lowercase(&mut self) {
+        let lower = |phrases: &mut Vec<String>| {
+            for phrase in phrases {
+                *phrase = phrase.to_lowercase();
+            }
+        };
+        lower(&mut self.toxicity);
+        lower(&mut self.profanity);
+        for phrases in self
+            .sentiment
+            .values_mut()
+            .chain(self.prompt_injection.values_mut())
+            .chain(self.financial_advice.values_mut())
+        {
+            lower(phrases);
+        }
+    }
+}
+
+/// Append the phrases of `more` to the matching categories of `phrases`
+pub fn merge_categories(phrases: &mut CategoryPhrases, more: CategoryPhrases) {
+    for (category, list) in more {
+        let existing = phrases.entry(category).or_default();
+        for phrase in list {
+            if !existing.contains(&phrase) {
+                existing.push(phrase);
+            }
+        }
+    }
+}
+
+/// Add the normalized form of every phrase that normalization changes
+pub fn fold_categories(phrases: &mut CategoryPhrases, normalizer: &TextNormalizer) {
+    for list in phrases.values_mut() {
+        *list = with_folded(list, normalizer);
+    }
+}
+
+/// `phrases` followed by the normalized forms not already in it
+pub(crate) fn with_folded(phrases: &[String], normalizer: &TextNormalizer) -> Vec<String> {
+    let mut all = phrases.to_vec();
+    for phrase in phrases {
+        let folded = normalizer.fold(phrase);
+        if !all.contains(&folded) {
+            all.push(folded);
+        }
+    }
+    all
+}
+
+/// Reject categories of `phrases` that are not in `known`
+pub(crate) fn check_categories(
+    owner: &str,
+    phrases: &CategoryPhrases,
+    known: &[&str],
+) -> Result<()> {
+    match phrases.keys().find(|key| !known.contains(&key.as_str())) {
+        Some(unknown) => Err(checkstream_core::Error::config(format!(
+            "Unknown category '{}' for {} (expected one of: {})",
+            unknown,
+            owner,
+            known.join(", ")
+        ))),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FRENCH: &str = r#"
+language: fr
+toxicity: [Connard]
+prompt_injection:
+  instruction_override: ["ignore les instructions précédentes"]
+"#;
+
+    #[test]
+    fn test_parse_and_lowercase() {
+        let pack = LexiconPack::from_yaml(FRENCH).unwrap();
+        assert_eq!(pack.language, "fr");
+        assert_eq!(pack.toxicity, vec!["connard"]);
+        assert!(pack.sentiment.is_empty());
+    }
+
+    #[test]
+    fn test_unknown_category_rejected() {
+        let yaml = "language: fr\nprompt_injection:\n  overrides: [x]\n";
+        let err = LexiconPack::from_yaml(yaml).unwrap_err().to_string();
+        assert!(err.contains("overrides"), "{err}");
+
+        let yaml = "language: fr\ntoxic: [x]\n";
+        assert!(LexiconPack::from_yaml(yaml).is_err());
+    }
+
+    #[test]
+    fn test_folded_adds_normalized_phrases() {
+        let pack = LexiconPack::from_yaml(FRENCH).unwrap();
+        let folded = pack.folded(&TextNormalizer::default());
+
+        assert_eq!(
+            folded.prompt_injection["instruction_override"],
+            vec![
+                "ignore les instructions précédentes",
+                "ignore les instructions precedentes"
+            ]
+        );
+        assert_eq!(folded.toxicity, vec!["connard"]);
+    }
+
+    #[test]
+    fn test_shipped_packs_load() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../lexicons");
+        for language in ["de", "es", "fr"] {
+            let pack = LexiconPack::from_file(dir.join(format!("{language}.yaml"))).unwrap();
+            assert_eq!(pack.language, language);
+            assert!(!pack.prompt_injection.is_empty());
+        }
+    }
+}
@@ -7,44 +7,77 @@
 //! - Tier B (<5ms): Quantized neural classifiers for prompt injection, advice detection
 //! - Tier C (<10ms): Larger models for nuanced classification
 //!
-//! All classifiers are designed to run on CPU with minimal overhead.
+//! All classifiers are designed to run on CPU with minimal overhead. Pipelines
+//! can normalize obfuscated input once and share it across classifiers (see
+//! [`normalize`]).
 
+pub mod batching;
+pub mod calibration;
 pub mod classifier;
 pub mod config;
 pub mod dynamic_registry;
+pub mod evaluation;
 pub mod financial_advice;
 pub mod generic_loader;
+pub mod incremental;
+pub mod language;
+pub mod lexicon;
 pub mod loader_plugin;
+pub mod logits;
+pub mod long_text;
 pub mod model_config;
 pub mod model_loader;
+pub mod normalize;
 pub mod patterns;
 pub mod pii;
 pub mod pipeline;
 pub mod prompt_injection;
 pub mod registry;
+pub mod secrets;
 pub mod sentiment;
+pub mod similarity;
 pub mod streaming;
+pub mod topic;
 pub mod toxicity;
 
-pub use classifier::{ClassificationResult, Classifier, ClassifierTier};
+pub use batching::{BatchingClassifier, BatchingConfig};
+pub use calibration::{CalibratedClassifier, Calibration, CalibrationMethod};
+pub use classifier::{ClassificationResult, Classifier, ClassifierTier, LabelScore, Span};
 pub use config::{
-    AggregationStrategySpec, ClassifierConfig, ConditionSpec, DeviceSpec, ModelConfigSpec,
-    ModelSourceSpec, PipelineConfigSpec, StageConfigSpec,
+    AggregationStrategySpec, BuiltinPhrasesSpec, ClassifierConfig, ConditionSpec, DeviceSpec,
+    ErrorPolicySpec, ModelConfigSpec, ModelSourceSpec, PatternCategorySpec, PatternClassifierSpec,
+    PatternEntrySpec, PipelineConfigSpec, StageConfigSpec, StageOptionsSpec, TopicClassifierSpec,
+    TopicSpec, UncertaintyBandSpec,
 };
-pub use loader_plugin::ModelLoaderPlugin;
+pub use incremental::{IncrementalScanner, SpanDetector, SpanScanner};
+pub use language::LanguageClassifier;
+pub use lexicon::{CategoryPhrases, LexiconPack};
+pub use loader_plugin::{ModelLoaderPlugin, PluginClassifier};
+pub use logits::LogitDecoder;
+pub use long_text::{TokenOffsets, WindowAggregation, WindowedClassifier};
 pub use model_loader::{
     DeviceType, LoadedModel, ModelConfig, ModelFormat, ModelRegistry, ModelSource,
 };
+pub use normalize::{NormalizationConfig, NormalizedText, TextNormalizer};
+pub use patterns::{PatternClassifier, PatternOptions, PatternRule};
+pub use pii::{PiiClassifier, PiiConfig, PiiEntity};
 pub use pipeline::{
-    AggregationStrategy, ClassifierPipeline, PipelineBuilder, PipelineExecutionResult,
-    PipelineResult, PipelineStage,
+    AggregationStrategy, ClassifierFailure, ClassifierPipeline, ConditionContext, ErrorPolicy,
+    FailureKind, PipelineBuilder, PipelineExecutionResult, PipelineResult, PipelineStage,
+    ReusedResults, StageOptions, UncertaintyBand,
 };
 pub use registry::{
     build_pipeline_from_config, init_registry_from_config, init_registry_from_file, load_config,
-    ClassifierRegistry, SharedRegistry,
+    ClassifierFallback, ClassifierRegistry, FallbackReason, SharedRegistry,
 };
+pub use secrets::{SecretKind, SecretsClassifier, SecretsConfig};
 pub use sentiment::SentimentClassifier;
-pub use streaming::{StreamingBuffer, StreamingClassifier, StreamingConfig, StreamingPipeline};
+pub use similarity::{Embedder, ExemplarSet, HashingEmbedder, SimilarityClassifier, VectorIndex};
+pub use streaming::{
+    ContextWindow, EvaluationSchedule, StreamingBuffer, StreamingClassifier, StreamingConfig,
+    StreamingPipeline,
+};
+pub use topic::{Topic, TopicClassifier};
 
 /// Prelude for convenient imports
 pub mod prelude {
@@ -56,6 +89,7 @@ pub mod prelude {
     pub use crate::patterns::PatternClassifier;
     pub use crate::pii::PiiClassifier;
     pub use crate::prompt_injection::PromptInjectionClassifier;
+    pub use crate::secrets::SecretsClassifier;
     pub use crate::sentiment::SentimentClassifier;
     pub use crate::toxicity::ToxicityClassifier;
 }
@@ -1,7 +1,10 @@
 //! Extension points for model-backed classifier loading.
 
-use crate::classifier::Classifier;
+use crate::classifier::{ClassificationResult, Classifier, ClassifierTier};
+use crate::similarity::Embedder;
 use checkstream_core::Result;
+use std::sync::Arc;
+use tokio::sync::OnceCell;
 
 /// Pluggable backend for dynamic model classifier loading.
 ///
@@ -15,4 +18,130 @@ pub trait ModelLoaderPlugin: Send + Sync {
 
     /// List model names available to this loader.
     fn available_models(&self) -> Vec<String>;
+
+    /// Short name of the inference backend, reported in classification metadata.
+    fn backend(&self) -> &str {
+        "plugin"
+    }
+
+    /// Largest batch worth collecting for `name` (1 disables micro-batching).
+    fn batch_size(&self, _name: &str) -> usize {
+        1
+    }
+
+    /// Load `name` as a sentence embedder, if it is an embedding model this
+    /// backend can serve.
+    async fn load_embedder(&self, _name: &str) -> Result<Option<Arc<dyn Embedder>>> {
+        Ok(None)
+    }
+}
+
+/// Classifier served by a loader plugin, loaded at startup or on first use.
+///
+/// Results carry `<backend>:<model>` in `ClassificationMetadata.model` so
+/// callers can tell which backend produced them.
+pub struct PluginClassifier {
+    name: String,
+    backend: String,
+    tier: ClassifierTier,
+    loader: Arc<dyn ModelLoaderPlugin>,
+    inner: OnceCell<Arc<dyn Classifier>>,
+}
+
+impl PluginClassifier {
+    /// Load `name` through `loader` now.
+    pub async fn load(name: impl Into<String>, loader: Arc<dyn ModelLoaderPlugin>) -> Result<Self> {
+        let name = name.into();
+        let inner: Arc<dyn Classifier> = Arc::from(loader.load_classifier(&name).await?);
+        Ok(Self {
+            tier: inner.tier(),
+            backend: loader.backend().to_string(),
+            inner: OnceCell::new_with(Some(inner)),
+            name,
+            loader,
+        })
+    }
+
+    /// Defer loading `name` until the first classification.
+    ///
+    /// `tier` is reported until the model is loaded.
+    pub fn lazy(
+        name: impl Into<String>,
+        loader: Arc<dyn ModelLoaderPlugin>,
+        tier: ClassifierTier,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            backend: loader.backend().to_string(),
+            tier,
+            loader,
+            inner: OnceCell::new(),
+        }
+    }
+
+    /// Backend serving this classifier.
+    pub fn backend(&self) -> &str {
+        &self.backend
+    }
+
+    /// Whether the underlying model has been loaded.
+    pub fn is_loaded(&self) -> bool {
+        self.inner.initialized()
+    }
+
+    async fn inner(&self) -> Result<&Arc<dyn Classifier>> {
+        self.inner
+            .get_or_try_init(|| async {
+                tracing::info!(
+                    "Loading classifier '{}' from {} backend",
+                    self.name,
+                    self.backend
+                );
+                let classifier = self.loader.load_classifier(&self.name).await?;
+                Ok(Arc::from(classifier))
+            })
+            .await
+    }
+
+    fn tag_model(&self, result: &mut ClassificationResult) {
+        let model = result.metadata.model.take();
+        result.metadata.model = Some(format!(
+            "{}:{}",
+            self.backend,
+            model.as_deref().unwrap_or(&self.name)
+        ));
+    }
+}
+
+#[async_trait::async_trait]
+impl Classifier for PluginClassifier {
+    async fn classify(&self, text: &str) -> Result<ClassificationResult> {
+        let mut result = self.inner().await?.classify(text).await?;
+        self.tag_model(&mut result);
+        Ok(result)
+    }
+
+    async fn classify_batch(&self, texts: &[&str]) -> Result<Vec<ClassificationResult>> {
+        let mut results = self.inner().await?.classify_batch(texts).await?;
+        for result in &mut results {
+            self.tag_model(result);
+        }
+        Ok(results)
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn tier(&self) -> ClassifierTier {
+        self.inner.get().map_or(self.tier, |inner| inner.tier())
+    }
+
+    fn normalized_input(&self) -> bool {
+        self.inner
+            .get()
+            .map_or(self.tier == ClassifierTier::A, |inner| {
+                inner.normalized_input()
+            })
+    }
 }
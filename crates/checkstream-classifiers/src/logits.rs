@@ -0,0 +1,208 @@
+//! Decoding sequence-classifier logits into results
+//!
+//! Model backends produce one logit per label. [`LogitDecoder`] applies the
+//! `output` and `inference` settings of a model's registry entry so every
+//! backend labels, scores and thresholds its output the same way.
+
+use crate::classifier::{ClassificationResult, LabelScore};
+use crate::model_config::{ModelConfig, OutputConfig};
+use checkstream_core::Result;
+
+/// Turns a sequence classifier's logits into a [`ClassificationResult`]
+#[derive(Debug, Clone)]
+pub struct LogitDecoder {
+    labels: Vec<String>,
+    thresholds: Vec<f32>,
+    output: OutputConfig,
+}
+
+impl LogitDecoder {
+    /// Build a decoder for a model whose head emits one logit per label
+    ///
+    /// Fails if `inference.label_thresholds` names a label the model does not
+    /// have or holds a threshold outside 0.0-1.0.
+    pub fn from_config(config: &ModelConfig, labels: Vec<String>) -> Result<Self> {
+        for (label, threshold) in &config.inference.label_thresholds {
+            if !labels.contains(label) {
+                return Err(checkstream_core::Error::config(format!(
+                    "label_thresholds names unknown label '{}' (model labels: {})",
+                    label,
+                    labels.join(", ")
+                )));
+            }
+            if !(0.0..=1.0).contains(threshold) {
+                return Err(checkstream_core::Error::config(format!(
+                    "Threshold {} for label '{}' must be between 0.0 and 1.0",
+                    threshold, label
+                )));
+            }
+        }
+
+        Ok(Self {
+            thresholds: labels
+                .iter()
+                .map(|label| config.inference.threshold_for(label))
+                .collect(),
+            output: config.output.clone(),
+            labels,
+        })
+    }
+
+    /// Labels in logit order
+    pub fn labels(&self) -> &[String] {
+        &self.labels
+    }
+
+    /// Decode one text's logits
+    ///
+    /// Multi-label models report every label with its own threshold (see
+    /// [`ClassificationResult::multi_label`]). Binary single-label heads score
+    /// the positive (second) class and fall back to the first label at or
+    /// below its threshold; other heads report the most probable label.
+    pub fn decode(&self, logits: &[f32]) -> ClassificationResult {
+        let probs = self.output.activation_for(logits.len()).apply(logits);
+
+        if self.output.is_multi_label() {
+            return ClassificationResult::multi_label(
+                self.labels
+                    .iter()
+                    .zip(&self.thresholds)
+                    .zip(&probs)
+                    .map(|((label, &threshold), &score)| LabelScore::new(label, score, threshold))
+                    .collect(),
+            );
+        }
+
+        let (max_idx, max_prob) = probs
+            .iter()
+            .copied()
+            .enumerate()
+            .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal))
+            .unwrap_or((0, 0.0));
+
+        let binary = probs.len() == 2;
+        let (idx, score) = if binary {
+            (1, probs[1])
+        } else {
+            (max_idx, max_prob)
+        };
+        let threshold = self
+            .thresholds
+            .get(idx)
+            .copied()
+            .unwrap_or(f32::NEG_INFINITY);
+
+        let label = if binary && score <= threshold {
+            self.label(0)
+        } else if probs.len() == 1 && score <= threshold {
+            "negative".to_string()
+        } else {
+            self.label(idx)
+        };
+
+        let mut result = ClassificationResult::new(label, score);
+        result.metadata.all_scores = Some(
+            probs
+                .iter()
+                .enumerate()
+                .map(|(idx, &prob)| (self.label(idx), prob))
+                .collect(),
+        );
+        result
+    }
+
+    fn label(&self, idx: usize) -> String {
+        self.labels
+            .get(idx)
+            .cloned()
+            .unwrap_or_else(|| format!("label_{}", idx))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model_config::ModelRegistry;
+
+    fn model(output: &str) -> ModelConfig {
+        let yaml = format!(
+            r#"
+version: "1.0"
+models:
+  toxicity:
+    source:
+      type: local
+      path: "./models/toxic-bert"
+    architecture:
+      type: bert-sequence-classification
+      num_labels: 3
+    inference:
+      threshold: 0.5
+      label_thresholds:
+        threat: 0.2
+    output:
+{}
+"#,
+            output
+        );
+        let registry: ModelRegistry = serde_yaml::from_str(&yaml).unwrap();
+        registry.get_model("toxicity").unwrap().clone()
+    }
+
+    fn labels(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn test_multi_label_uses_per_label_thresholds() {
+        let decoder = LogitDecoder::from_config(
+            &model("      output_type: multi-label"),
+            labels(&["toxic", "threat", "insult"]),
+        )
+        .unwrap();
+
+        // sigmoid(-1.0) ~= 0.27: below the default 0.5, above threat's 0.2
+        let result = decoder.decode(&[-1.0, -1.0, -3.0]);
+        assert_eq!(result.label, "threat");
+        assert!((result.score - 0.2689).abs() < 1e-3);
+        let triggered: Vec<_> = result.triggered_labels().map(|l| &l.label).collect();
+        assert_eq!(triggered, vec!["threat"]);
+        assert_eq!(result.metadata.labels.len(), 3);
+
+        let clean = decoder.decode(&[-3.0, -3.0, -3.0]);
+        assert_eq!(clean.label, "negative");
+        assert_eq!(clean.triggered_labels().count(), 0);
+    }
+
+    #[test]
+    fn test_binary_head_scores_positive_class() {
+        let config = model("      output_type: single-label");
+        let decoder = LogitDecoder::from_config(&config, labels(&["safe", "threat"])).unwrap();
+
+        let result = decoder.decode(&[0.0, -1.0]);
+        assert_eq!(result.label, "threat");
+        assert!(result.score > 0.2 && result.score < 0.5);
+        assert!(result.metadata.labels.is_empty());
+
+        let sigmoid = LogitDecoder::from_config(
+            &model("      activation: sigmoid"),
+            labels(&["safe", "threat"]),
+        )
+        .unwrap();
+        let scores = sigmoid.decode(&[0.0, 0.0]).metadata.all_scores.unwrap();
+        assert_eq!(
+            scores,
+            vec![("safe".to_string(), 0.5), ("threat".to_string(), 0.5)]
+        );
+    }
+
+    #[test]
+    fn test_rejects_unknown_threshold_labels() {
+        let err = LogitDecoder::from_config(
+            &model("      output_type: multi-label"),
+            labels(&["toxic", "insult"]),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("threat"));
+    }
+}
@@ -0,0 +1,617 @@
+//! Long-text handling for token-limited model classifiers
+//!
+//! Sequence classifiers only see `max_length` tokens. [`WindowedClassifier`]
+//! splits longer text into windows according to a [`LongTextConfig`], scores
+//! all windows in one batch and combines them with a [`WindowAggregation`].
+//! Windows that score above the threshold are reported as spans over the
+//! original text. With the `tokenizers` feature, [`with_long_text`] builds
+//! the wrapper for a model from its registry config.
+
+use crate::classifier::{ClassificationResult, Classifier, ClassifierTier, LabelScore, Span};
+use crate::model_config::{LongTextConfig, LongTextStrategy};
+use checkstream_core::Result;
+use std::ops::Range;
+use std::sync::Arc;
+use tracing::warn;
+
+/// Default cap on windows per text when `max_windows` is unset
+///
+/// Text needing more windows is only scored up to the cap; each time that
+/// happens a warning is logged and `checkstream_long_text_truncated_total`
+/// is incremented.
+const DEFAULT_MAX_WINDOWS: usize = 32;
+
+/// Tokenizer hook returning byte offsets of each content token in a text
+///
+/// Offsets must exclude special tokens (`[CLS]`, `</s>`, ...).
+pub type TokenOffsets = Arc<dyn Fn(&str) -> Result<Vec<(usize, usize)>> + Send + Sync>;
+
+/// How window scores combine into one result (`output.aggregation`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowAggregation {
+    /// Highest window score
+    Max,
+    /// Mean window score (and mean per-label scores)
+    Mean,
+    /// Probability that at least one window is positive
+    Any,
+}
+
+impl WindowAggregation {
+    /// Parse an `output.aggregation` value
+    pub fn parse(name: &str) -> Result<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "max" => Ok(Self::Max),
+            "mean" => Ok(Self::Mean),
+            "any" => Ok(Self::Any),
+            other => Err(checkstream_core::Error::config(format!(
+                "Unknown aggregation '{}' (expected max, mean or any)",
+                other
+            ))),
+        }
+    }
+}
+
+/// Byte ranges of `text` to classify, given its content-token offsets
+///
+/// `budget` is the number of content tokens a window may hold (the model's
+/// `max_length` minus special tokens). Text within the budget is a single
+/// window. At most `max_windows` windows are returned.
+pub fn plan_windows(
+    text: &str,
+    offsets: &[(usize, usize)],
+    budget: usize,
+    config: &LongTextConfig,
+) -> Vec<Range<usize>> {
+    let mut windows = all_windows(text, offsets, budget, config);
+    windows.truncate(max_windows(config));
+    windows
+}
+
+fn max_windows(config: &LongTextConfig) -> usize {
+    config.max_windows.unwrap_or(DEFAULT_MAX_WINDOWS).max(1)
+}
+
+/// Every window the strategy produces, before the `max_windows` cap
+fn all_windows(
+    text: &str,
+    offsets: &[(usize, usize)],
+    budget: usize,
+    config: &LongTextConfig,
+) -> Vec<Range<usize>> {
+    let budget = budget.max(1);
+    let count = offsets.len();
+    if count <= budget {
+        return std::iter::once(0..text.len()).collect();
+    }
+
+    let token_range = |first: usize, last: usize| offsets[first].0..offsets[last].1;
+
+    match config.strategy {
+        LongTextStrategy::Head => std::iter::once(0..offsets[budget - 1].1).collect(),
+        LongTextStrategy::Tail => std::iter::once(offsets[count - budget].0..text.len()).collect(),
+        LongTextStrategy::SlidingWindow => {
+            let stride = config.stride.unwrap_or(budget / 2).clamp(1, budget);
+            let mut windows = Vec::new();
+            let mut first = 0;
+            loop {
+                let last = (first + budget).min(count) - 1;
+                windows.push(token_range(first, last));
+                if last + 1 == count {
+                    break;
+                }
+                first += stride;
+            }
+            windows
+        }
+        LongTextStrategy::Sentences => sentence_windows(text, offsets, budget),
+    }
+}
+
+/// Pack consecutive sentences into windows of at most `budget` tokens
+///
+/// A sentence longer than the budget is split into back-to-back windows.
+fn sentence_windows(text: &str, offsets: &[(usize, usize)], budget: usize) -> Vec<Range<usize>> {
+    // Token index ranges per sentence; tokens belong to the sentence their
+    // start falls in.
+    let mut sentences: Vec<Range<usize>> = Vec::new();
+    let mut token = 0;
+    for sentence in sentence_ranges(text) {
+        let first = token;
+        while token < offsets.len() && offsets[token].0 < sentence.end {
+            token += 1;
+        }
+        if token > first {
+            sentences.push(first..token);
+        }
+    }
+
+    let mut windows = Vec::new();
+    let mut current: Option<Range<usize>> = None;
+    for sentence in sentences {
+        if let Some(window) = current.take() {
+            if sentence.end - window.start <= budget {
+                current = Some(window.start..sentence.end);
+                continue;
+            }
+            windows.push(window);
+        }
+
+        let mut first = sentence.start;
+        while sentence.end - first > budget {
+            windows.push(first..first + budget);
+            first += budget;
+        }
+        current = Some(first..sentence.end);
+    }
+    windows.extend(current);
+
+    windows
+        .into_iter()
+        .map(|tokens| offsets[tokens.start].0..offsets[tokens.end - 1].1)
+        .collect()
+}
+
+/// Byte ranges of sentences, split after `.`, `!`, `?` or newlines
+pub(crate) fn sentence_ranges(text: &str) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+    while let Some((idx, c)) = chars.next() {
+        let boundary = match c {
+            '\n' => true,
+            '.' | '!' | '?' => !matches!(chars.peek(), Some((_, next)) if !next.is_whitespace()),
+            _ => false,
+        };
+        if boundary {
+            let end = idx + c.len_utf8();
+            ranges.push(start..end);
+            start = end;
+        }
+    }
+    if start < text.len() {
+        ranges.push(start..text.len());
+    }
+    ranges
+}
+
+/// Combine per-window results into one result over the whole text
+///
+/// The top-scoring window supplies the label and model metadata. Inner spans
+/// are shifted to text offsets, and every window scoring above `threshold`
+/// adds a span covering it. Returns `None` when there are no windows.
+pub fn aggregate_windows(
+    windows: Vec<(Range<usize>, ClassificationResult)>,
+    aggregation: WindowAggregation,
+    threshold: f32,
+) -> Option<ClassificationResult> {
+    let by_score = |a: &&(Range<usize>, ClassificationResult),
+                    b: &&(Range<usize>, ClassificationResult)| {
+        a.1.score
+            .partial_cmp(&b.1.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    };
+    let top = windows.iter().max_by(by_score)?.1.clone();
+    let lowest_label = windows.iter().min_by(by_score)?.1.label.clone();
+
+    let scores: Vec<f32> = windows.iter().map(|(_, result)| result.score).collect();
+    let score = match aggregation {
+        WindowAggregation::Max => top.score,
+        WindowAggregation::Mean => scores.iter().sum::<f32>() / scores.len() as f32,
+        WindowAggregation::Any => 1.0 - scores.iter().map(|s| 1.0 - s).product::<f32>(),
+    };
+
+    let mut spans = Vec::new();
+    for (range, result) in &windows {
+        spans.extend(result.metadata.spans.iter().map(|span| Span {
+            start: span.start + range.start,
+            end: span.end + range.start,
+            ..span.clone()
+        }));
+        if result.score > threshold {
+            spans.push(Span::new(
+                range.start,
+                range.end,
+                result.label.as_str(),
+                result.score,
+            ));
+        }
+    }
+
+    let mut result = top;
+    if !result.metadata.labels.is_empty() {
+        let labels = ClassificationResult::multi_label(aggregate_labels(&windows, aggregation));
+        result.label = labels.label;
+        result.score = labels.score;
+        result.metadata.labels = labels.metadata.labels;
+        result.metadata.all_scores = labels.metadata.all_scores;
+    } else {
+        if aggregation == WindowAggregation::Mean {
+            result.metadata.all_scores = mean_scores(&windows);
+        }
+        if result.score > threshold && score <= threshold {
+            result.label = lowest_label;
+        }
+        result.score = score;
+    }
+    result.metadata.spans = spans;
+    result
+        .metadata
+        .extra
+        .push(("windows".to_string(), windows.len().to_string()));
+    result.latency_us = windows
+        .iter()
+        .map(|(_, result)| result.latency_us)
+        .max()
+        .unwrap_or(0);
+    Some(result)
+}
+
+/// Combine each label of multi-label windows with `aggregation`
+fn aggregate_labels(
+    windows: &[(Range<usize>, ClassificationResult)],
+    aggregation: WindowAggregation,
+) -> Vec<LabelScore> {
+    let Some((_, first)) = windows.first() else {
+        return Vec::new();
+    };
+    first
+        .metadata
+        .labels
+        .iter()
+        .map(|label| {
+            let scores: Vec<f32> = windows
+                .iter()
+                .filter_map(|(_, result)| {
+                    result
+                        .metadata
+                        .labels
+                        .iter()
+                        .find(|other| other.label == label.label)
+                })
+                .map(|other| other.score)
+                .collect();
+            let score = match aggregation {
+                WindowAggregation::Max => scores.iter().copied().fold(0.0, f32::max),
+                WindowAggregation::Mean => scores.iter().sum::<f32>() / scores.len() as f32,
+                WindowAggregation::Any => 1.0 - scores.iter().map(|s| 1.0 - s).product::<f32>(),
+            };
+            LabelScore::new(label.label.clone(), score, label.threshold)
+        })
+        .collect()
+}
+
+fn mean_scores(windows: &[(Range<usize>, ClassificationResult)]) -> Option<Vec<(String, f32)>> {
+    let first = windows.first()?.1.metadata.all_scores.as_ref()?;
+    Some(
+        first
+            .iter()
+            .map(|(label, _)| {
+                let total: f32 = windows
+                    .iter()
+                    .filter_map(|(_, result)| result.metadata.all_scores.as_ref())
+                    .filter_map(|scores| scores.iter().find(|(l, _)| l == label))
+                    .map(|(_, score)| score)
+                    .sum();
+                (label.clone(), total / windows.len() as f32)
+            })
+            .collect(),
+    )
+}
+
+/// Classifier that scores long text window by window
+pub struct WindowedClassifier {
+    inner: Arc<dyn Classifier>,
+    tokenize: TokenOffsets,
+    budget: usize,
+    config: LongTextConfig,
+    aggregation: WindowAggregation,
+    threshold: f32,
+}
+
+impl WindowedClassifier {
+    /// Wrap `inner`, whose windows hold up to `budget` content tokens
+    pub fn new(
+        inner: Arc<dyn Classifier>,
+        tokenize: TokenOffsets,
+        budget: usize,
+        config: LongTextConfig,
+        aggregation: WindowAggregation,
+        threshold: f32,
+    ) -> Self {
+        Self {
+            inner,
+            tokenize,
+            budget,
+            config,
+            aggregation,
+            threshold,
+        }
+    }
+
+    fn windows(&self, text: &str) -> Result<Vec<Range<usize>>> {
+        let offsets = (self.tokenize)(text)?;
+        let mut windows = all_windows(text, &offsets, self.budget, &self.config);
+
+        let limit = max_windows(&self.config);
+        if windows.len() > limit {
+            warn!(
+                "Classifier '{}': text needs {} windows, scoring only the first {} (max_windows)",
+                self.name(),
+                windows.len(),
+                limit
+            );
+            metrics::counter!(
+                "checkstream_long_text_truncated_total",
+                "classifier" => self.name().to_string()
+            )
+            .increment(1);
+            windows.truncate(limit);
+        }
+        Ok(windows)
+    }
+}
+
+#[async_trait::async_trait]
+impl Classifier for WindowedClassifier {
+    async fn classify(&self, text: &str) -> Result<ClassificationResult> {
+        let mut results = self.classify_batch(&[text]).await?;
+        results.pop().ok_or_else(|| {
+            checkstream_core::Error::classifier(format!(
+                "Classifier '{}' returned no result",
+                self.name()
+            ))
+        })
+    }
+
+    async fn classify_batch(&self, texts: &[&str]) -> Result<Vec<ClassificationResult>> {
+        let plans = texts
+            .iter()
+            .map(|text| self.windows(text))
+            .collect::<Result<Vec<_>>>()?;
+
+        let slices: Vec<&str> = texts
+            .iter()
+            .zip(&plans)
+            .flat_map(|(text, windows)| windows.iter().map(|range| &text[range.clone()]))
+            .collect();
+        let mut results = self.inner.classify_batch(&slices).await?.into_iter();
+
+        plans
+            .into_iter()
+            .map(|windows| {
+                let scored: Vec<_> = windows.into_iter().zip(results.by_ref()).collect();
+                aggregate_windows(scored, self.aggregation, self.threshold).ok_or_else(|| {
+                    checkstream_core::Error::classifier(format!(
+                        "Classifier '{}' returned too few window results",
+                        self.name()
+                    ))
+                })
+            })
+            .collect()
+    }
+
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn tier(&self) -> ClassifierTier {
+        self.inner.tier()
+    }
+
+    fn normalized_input(&self) -> bool {
+        self.inner.normalized_input()
+    }
+}
+
+/// Wrap a sequence classifier so text beyond `max_length` tokens is scored
+/// in windows according to `inference.long_text`
+///
+/// `tokenizer` is the model's tokenizer; truncation and padding are turned
+/// off on this copy so windows can be planned over the whole text.
+#[cfg(feature = "tokenizers")]
+pub fn with_long_text(
+    classifier: Arc<dyn Classifier>,
+    mut tokenizer: tokenizers::Tokenizer,
+    config: &crate::model_config::ModelConfig,
+) -> Result<Box<dyn Classifier>> {
+    tokenizer.with_truncation(None).map_err(|e| {
+        checkstream_core::Error::classifier(format!("Invalid truncation settings: {}", e))
+    })?;
+    tokenizer.with_padding(None);
+    let special_tokens = tokenizer
+        .encode("", true)
+        .map_err(|e| checkstream_core::Error::classifier(format!("Tokenization failed: {}", e)))?
+        .len();
+
+    Ok(Box::new(WindowedClassifier::new(
+        classifier,
+        Arc::new(move |text: &str| {
+            let encoding = tokenizer.encode(text, false).map_err(|e| {
+                checkstream_core::Error::classifier(format!("Tokenization failed: {}", e))
+            })?;
+            Ok(encoding.get_offsets().to_vec())
+        }),
+        config.inference.max_length.saturating_sub(special_tokens),
+        config.inference.long_text.clone(),
+        WindowAggregation::parse(&config.output.aggregation)?,
+        config.inference.threshold,
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn whitespace_offsets(text: &str) -> Vec<(usize, usize)> {
+        let mut offsets = Vec::new();
+        let mut start = None;
+        for (idx, c) in text.char_indices() {
+            match (c.is_whitespace(), start) {
+                (false, None) => start = Some(idx),
+                (true, Some(s)) => {
+                    offsets.push((s, idx));
+                    start = None;
+                }
+                _ => {}
+            }
+        }
+        if let Some(s) = start {
+            offsets.push((s, text.len()));
+        }
+        offsets
+    }
+
+    fn plan(text: &str, budget: usize, strategy: LongTextStrategy) -> Vec<&str> {
+        let config = LongTextConfig {
+            strategy,
+            stride: Some(2),
+            max_windows: None,
+        };
+        plan_windows(text, &whitespace_offsets(text), budget, &config)
+            .into_iter()
+            .map(|range| &text[range])
+            .collect()
+    }
+
+    #[test]
+    fn test_plan_windows_strategies() {
+        let text = "a b c d e f";
+        assert_eq!(plan(text, 8, LongTextStrategy::Head), vec![text]);
+        assert_eq!(plan(text, 3, LongTextStrategy::Head), vec!["a b c"]);
+        assert_eq!(plan(text, 3, LongTextStrategy::Tail), vec!["d e f"]);
+        assert_eq!(
+            plan(text, 3, LongTextStrategy::SlidingWindow),
+            vec!["a b c", "c d e", "e f"]
+        );
+
+        let sentences = "One two. Three four five six. Seven.";
+        assert_eq!(
+            plan(sentences, 4, LongTextStrategy::Sentences),
+            vec!["One two.", "Three four five six.", "Seven."]
+        );
+        assert_eq!(
+            plan(sentences, 3, LongTextStrategy::Sentences),
+            vec!["One two.", "Three four five", "six. Seven."]
+        );
+    }
+
+    #[test]
+    fn test_aggregate_windows() {
+        let windows = || {
+            vec![
+                (0..5, ClassificationResult::new("safe", 0.1)),
+                (4..9, ClassificationResult::new("unsafe", 0.9)),
+                (8..12, ClassificationResult::new("safe", 0.2)),
+            ]
+        };
+
+        let max = aggregate_windows(windows(), WindowAggregation::Max, 0.5).unwrap();
+        assert_eq!(max.label, "unsafe");
+        assert_eq!(max.score, 0.9);
+        assert_eq!(max.metadata.spans, vec![Span::new(4, 9, "unsafe", 0.9)]);
+
+        let mean = aggregate_windows(windows(), WindowAggregation::Mean, 0.5).unwrap();
+        assert_eq!(mean.label, "safe");
+        assert!((mean.score - 0.4).abs() < 1e-6);
+
+        let any = aggregate_windows(windows(), WindowAggregation::Any, 0.5).unwrap();
+        assert_eq!(any.label, "unsafe");
+        assert!(any.score > 0.9);
+
+        assert!(aggregate_windows(Vec::new(), WindowAggregation::Max, 0.5).is_none());
+        assert!(WindowAggregation::parse("median").is_err());
+    }
+
+    #[test]
+    fn test_aggregate_multi_label_windows() {
+        let window = |insult: f32, threat: f32| {
+            ClassificationResult::multi_label(vec![
+                LabelScore::new("insult", insult, 0.5),
+                LabelScore::new("threat", threat, 0.3),
+            ])
+        };
+        let windows = || vec![(0..5, window(0.6, 0.1)), (4..9, window(0.2, 0.4))];
+
+        let max = aggregate_windows(windows(), WindowAggregation::Max, 0.5).unwrap();
+        assert_eq!(max.label, "insult");
+        let triggered: Vec<_> = max.triggered_labels().map(|l| l.label.as_str()).collect();
+        assert_eq!(triggered, vec!["insult", "threat"]);
+
+        let mean = aggregate_windows(windows(), WindowAggregation::Mean, 0.5).unwrap();
+        assert_eq!(mean.label, "negative");
+        assert!((mean.score - 0.4).abs() < 1e-6);
+        assert_eq!(mean.triggered_labels().count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_windowed_classifier_reports_triggering_window() {
+        let inner = Arc::new(
+            crate::patterns::PatternClassifier::new(
+                "keyword",
+                vec![("attack".to_string(), "attack".to_string())],
+            )
+            .unwrap(),
+        );
+        let classifier = WindowedClassifier::new(
+            inner,
+            Arc::new(|text: &str| Ok(whitespace_offsets(text))),
+            3,
+            LongTextConfig {
+                strategy: LongTextStrategy::SlidingWindow,
+                stride: Some(3),
+                max_windows: None,
+            },
+            WindowAggregation::Max,
+            0.5,
+        );
+
+        let text = "one two three four attack six";
+        let result = classifier.classify(text).await.unwrap();
+        assert_eq!(result.label, "attack");
+
+        let attack = text.find("attack").unwrap();
+        assert!(result
+            .metadata
+            .spans
+            .contains(&Span::new(attack, attack + 6, "attack", 1.0)));
+        assert!(result
+            .metadata
+            .spans
+            .contains(&Span::new(14, text.len(), "attack", 1.0)));
+    }
+
+    #[tokio::test]
+    async fn test_windowed_classifier_caps_windows() {
+        let inner = Arc::new(
+            crate::patterns::PatternClassifier::new(
+                "keyword",
+                vec![("attack".to_string(), "attack".to_string())],
+            )
+            .unwrap(),
+        );
+        let classifier = WindowedClassifier::new(
+            inner,
+            Arc::new(|text: &str| Ok(whitespace_offsets(text))),
+            2,
+            LongTextConfig {
+                strategy: LongTextStrategy::SlidingWindow,
+                stride: Some(2),
+                max_windows: Some(2),
+            },
+            WindowAggregation::Max,
+            0.5,
+        );
+
+        // The attack sits in the third window, past the cap
+        let result = classifier
+            .classify("one two three four attack six")
+            .await
+            .unwrap();
+        assert_ne!(result.label, "attack");
+        assert!(result
+            .metadata
+            .extra
+            .contains(&("windows".to_string(), "2".to_string())));
+    }
+}
@@ -107,9 +107,21 @@ pub enum ArchitectureConfig {
     },
 
     /// Sentence transformer (embedding-based)
+    ///
+    /// With exemplar files the model scores text by similarity to them (see
+    /// [`crate::similarity`]); `inference.threshold` and `label_thresholds`
+    /// are then cosine similarities.
     SentenceTransformer {
         #[serde(default = "default_pooling")]
         pooling: String,
+
+        /// YAML files of labelled exemplar texts
+        #[serde(default)]
+        exemplars: Vec<PathBuf>,
+
+        /// Closest exemplars averaged into each label's score
+        #[serde(default = "default_top_k")]
+        top_k: usize,
     },
 
     /// DeBERTa for sequence classification
@@ -127,6 +139,10 @@ fn default_pooling() -> String {
     "mean".to_string()
 }
 
+fn default_top_k() -> usize {
+    1
+}
+
 /// Inference configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct InferenceConfig {
@@ -146,9 +162,17 @@ pub struct InferenceConfig {
     #[serde(default = "default_threshold")]
     pub threshold: f32,
 
+    /// Per-label thresholds overriding `threshold`
+    #[serde(default)]
+    pub label_thresholds: HashMap<String, f32>,
+
     /// Quantization settings
     #[serde(default)]
     pub quantization: Option<QuantizationConfig>,
+
+    /// Handling of text longer than `max_length` tokens
+    #[serde(default)]
+    pub long_text: LongTextConfig,
 }
 
 fn default_device() -> String {
@@ -167,6 +191,16 @@ fn default_threshold() -> f32 {
     0.5
 }
 
+impl InferenceConfig {
+    /// Threshold for `label`, falling back to the model-wide threshold
+    pub fn threshold_for(&self, label: &str) -> f32 {
+        self.label_thresholds
+            .get(label)
+            .copied()
+            .unwrap_or(self.threshold)
+    }
+}
+
 impl Default for InferenceConfig {
     fn default() -> Self {
         Self {
@@ -174,11 +208,46 @@ impl Default for InferenceConfig {
             max_length: default_max_length(),
             batch_size: default_batch_size(),
             threshold: default_threshold(),
+            label_thresholds: HashMap::new(),
             quantization: None,
+            long_text: LongTextConfig::default(),
         }
     }
 }
 
+/// Long-text configuration
+///
+/// Windows are scored separately and combined with `output.aggregation`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LongTextConfig {
+    /// How text is split into `max_length` windows
+    #[serde(default)]
+    pub strategy: LongTextStrategy,
+
+    /// Tokens between sliding-window starts (half a window if unset)
+    #[serde(default)]
+    pub stride: Option<usize>,
+
+    /// Upper bound on windows per text; later windows are dropped
+    #[serde(default)]
+    pub max_windows: Option<usize>,
+}
+
+/// Strategy for text longer than the model's token limit
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LongTextStrategy {
+    /// Classify the first `max_length` tokens
+    #[default]
+    Head,
+    /// Classify the last `max_length` tokens
+    Tail,
+    /// Classify overlapping windows advancing by `stride` tokens
+    SlidingWindow,
+    /// Classify runs of whole sentences packed up to `max_length` tokens
+    Sentences,
+}
+
 /// Quantization configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct QuantizationConfig {
@@ -207,6 +276,55 @@ pub struct OutputConfig {
     /// Aggregation method for multi-label (max, mean, any)
     #[serde(default = "default_aggregation")]
     pub aggregation: String,
+
+    /// Function mapping logits to probabilities (inferred if unset)
+    #[serde(default)]
+    pub activation: Option<Activation>,
+}
+
+impl OutputConfig {
+    /// Whether each label is scored and thresholded independently
+    pub fn is_multi_label(&self) -> bool {
+        self.output_type == "multi-label"
+    }
+
+    /// Activation for a head with `num_logits` outputs
+    ///
+    /// Defaults to sigmoid for multi-label and single-logit heads and to
+    /// softmax otherwise.
+    pub fn activation_for(&self, num_logits: usize) -> Activation {
+        self.activation
+            .unwrap_or(if self.is_multi_label() || num_logits == 1 {
+                Activation::Sigmoid
+            } else {
+                Activation::Softmax
+            })
+    }
+}
+
+/// Function applied to classifier logits
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Activation {
+    /// Mutually exclusive labels whose probabilities sum to one
+    Softmax,
+    /// Independent per-label probabilities
+    Sigmoid,
+}
+
+impl Activation {
+    /// Convert logits to probabilities
+    pub fn apply(self, logits: &[f32]) -> Vec<f32> {
+        match self {
+            Self::Sigmoid => logits.iter().map(|l| 1.0 / (1.0 + (-l).exp())).collect(),
+            Self::Softmax => {
+                let max = logits.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+                let exps: Vec<f32> = logits.iter().map(|l| (l - max).exp()).collect();
+                let sum: f32 = exps.iter().sum();
+                exps.into_iter().map(|e| e / sum).collect()
+            }
+        }
+    }
 }
 
 fn default_output_type() -> String {
@@ -222,6 +340,7 @@ impl Default for OutputConfig {
         Self {
             output_type: default_output_type(),
             aggregation: default_aggregation(),
+            activation: None,
         }
     }
 }
@@ -297,6 +416,76 @@ models:
         let toxicity = registry.get_model("toxicity").unwrap();
         assert_eq!(toxicity.name, "toxic-bert");
         assert_eq!(toxicity.inference.max_length, 512);
+        assert!(toxicity.output.is_multi_label());
+        assert_eq!(toxicity.output.activation_for(6), Activation::Sigmoid);
+    }
+
+    #[test]
+    fn test_label_thresholds_and_activation() {
+        let yaml = r#"
+version: "1.0"
+models:
+  toxicity:
+    source:
+      type: local
+      path: "./models/toxic-bert"
+    architecture:
+      type: bert-sequence-classification
+      num_labels: 3
+      labels: [toxic, threat, insult]
+    inference:
+      threshold: 0.5
+      label_thresholds:
+        threat: 0.2
+    output:
+      activation: softmax
+"#;
+
+        let registry: ModelRegistry = serde_yaml::from_str(yaml).unwrap();
+        let model = registry.get_model("toxicity").unwrap();
+        assert_eq!(model.inference.threshold_for("threat"), 0.2);
+        assert_eq!(model.inference.threshold_for("insult"), 0.5);
+        assert_eq!(model.output.activation_for(3), Activation::Softmax);
+
+        let default = OutputConfig::default();
+        assert_eq!(default.activation_for(2), Activation::Softmax);
+        assert_eq!(default.activation_for(1), Activation::Sigmoid);
+
+        let probs = Activation::Softmax.apply(&[1.0, 1.0]);
+        assert!((probs[0] - 0.5).abs() < 1e-6);
+        let probs = Activation::Sigmoid.apply(&[0.0, 0.0]);
+        assert_eq!(probs, vec![0.5, 0.5]);
+    }
+
+    #[test]
+    fn test_parse_long_text_config() {
+        let yaml = r#"
+version: "1.0"
+models:
+  injection:
+    source:
+      type: local
+      path: "./models/injection"
+    architecture:
+      type: deberta-sequence-classification
+      num_labels: 2
+    inference:
+      max_length: 512
+      long_text:
+        strategy: sliding-window
+        stride: 256
+    output:
+      aggregation: any
+"#;
+
+        let registry: ModelRegistry = serde_yaml::from_str(yaml).unwrap();
+        let long_text = &registry.get_model("injection").unwrap().inference.long_text;
+        assert_eq!(long_text.strategy, LongTextStrategy::SlidingWindow);
+        assert_eq!(long_text.stride, Some(256));
+        assert_eq!(long_text.max_windows, None);
+
+        let default = InferenceConfig::default();
+        assert_eq!(default.long_text.strategy, LongTextStrategy::Head);
     }
 
     #[test]
@@ -350,4 +539,38 @@ models:
         let registry: ModelRegistry = serde_yaml::from_str(yaml).unwrap();
         assert_eq!(registry.models.len(), 2);
     }
+
+    #[test]
+    fn test_parse_sentence_transformer_exemplars() {
+        let yaml = r#"
+version: "1.0"
+models:
+  jailbreak-similarity:
+    source:
+      type: huggingface
+      repo: "sentence-transformers/all-MiniLM-L6-v2"
+    architecture:
+      type: sentence-transformer
+      exemplars: ["./exemplars/jailbreak.yaml"]
+      top_k: 3
+"#;
+
+        let registry: ModelRegistry = serde_yaml::from_str(yaml).unwrap();
+        match &registry
+            .get_model("jailbreak-similarity")
+            .unwrap()
+            .architecture
+        {
+            ArchitectureConfig::SentenceTransformer {
+                pooling,
+                exemplars,
+                top_k,
+            } => {
+                assert_eq!(pooling, "mean");
+                assert_eq!(exemplars, &[PathBuf::from("./exemplars/jailbreak.yaml")]);
+                assert_eq!(*top_k, 3);
+            }
+            other => panic!("Expected sentence transformer, got {:?}", other),
+        }
+    }
 }
@@ -1,25 +1,137 @@
 //! Pattern-based classifier (Tier A)
+//!
+//! Matches literal phrases with Aho-Corasick and, optionally, regular
+//! expressions. Each pattern carries the label and score its matches report;
+//! the result is the highest-scoring match.
 
-use crate::classifier::{ClassificationMetadata, ClassificationResult, Classifier, ClassifierTier};
+use crate::classifier::{
+    ClassificationMetadata, ClassificationResult, Classifier, ClassifierTier, Span,
+};
+use crate::incremental::{IncrementalScanner, SpanDetector, SpanScanner};
 use aho_corasick::AhoCorasick;
 use checkstream_core::Result;
+use regex::{Regex, RegexBuilder};
 use std::time::Instant;
 
+/// Longest regex match found across chunk edges when streaming
+const MAX_REGEX_MATCH_LEN: usize = 256;
+
+/// One pattern and the label and score of its matches
+#[derive(Debug, Clone)]
+pub struct PatternRule {
+    /// Label reported for matches
+    pub label: String,
+
+    /// Literal phrase or regular expression
+    pub pattern: String,
+
+    /// Whether `pattern` is a regular expression
+    pub regex: bool,
+
+    /// Score of a match
+    pub score: f32,
+}
+
+impl PatternRule {
+    /// Rule matching a literal phrase
+    pub fn literal(label: impl Into<String>, pattern: impl Into<String>, score: f32) -> Self {
+        Self {
+            label: label.into(),
+            pattern: pattern.into(),
+            regex: false,
+            score,
+        }
+    }
+
+    /// Rule matching a regular expression
+    pub fn regex(label: impl Into<String>, pattern: impl Into<String>, score: f32) -> Self {
+        Self {
+            label: label.into(),
+            pattern: pattern.into(),
+            regex: true,
+            score,
+        }
+    }
+}
+
+/// How a pattern classifier matches text
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PatternOptions {
+    /// Match letter case exactly
+    pub case_sensitive: bool,
+
+    /// Only report matches not adjoined by letters, digits or underscores
+    pub word_boundary: bool,
+}
+
 /// Fast pattern-based classifier using Aho-Corasick algorithm
+#[derive(Clone)]
 pub struct PatternClassifier {
     name: String,
     patterns: AhoCorasick,
-    pattern_labels: Vec<String>,
+
+    /// Label and score of each literal pattern
+    pattern_labels: Vec<(String, f32)>,
+
+    /// Regular expressions with their label and score
+    regexes: Vec<(Regex, String, f32)>,
+
+    word_boundary: bool,
 }
 
 impl PatternClassifier {
-    /// Create a new pattern classifier
+    /// Create a new pattern classifier from `(label, phrase)` pairs
+    ///
+    /// Matching is ASCII case-insensitive and every match scores 1.0.
     pub fn new(name: impl Into<String>, patterns: Vec<(String, String)>) -> Result<Self> {
-        let (labels, pattern_strs): (Vec<_>, Vec<_>) = patterns.into_iter().unzip();
+        let rules = patterns
+            .into_iter()
+            .map(|(label, pattern)| PatternRule::literal(label, pattern, 1.0))
+            .collect();
+        Self::with_rules(name, rules, PatternOptions::default())
+    }
+
+    /// Create a pattern classifier from rules
+    ///
+    /// Case-insensitive literals with non-ASCII letters are matched as
+    /// regexes, since the literal matcher only folds ASCII case.
+    pub fn with_rules(
+        name: impl Into<String>,
+        rules: Vec<PatternRule>,
+        options: PatternOptions,
+    ) -> Result<Self> {
+        let name = name.into();
+        let mut literals = Vec::new();
+        let mut pattern_labels = Vec::new();
+        let mut regexes = Vec::new();
+
+        for rule in rules {
+            if !rule.regex && (options.case_sensitive || rule.pattern.is_ascii()) {
+                literals.push(rule.pattern);
+                pattern_labels.push((rule.label, rule.score));
+                continue;
+            }
+
+            let pattern = if rule.regex {
+                rule.pattern
+            } else {
+                regex::escape(&rule.pattern)
+            };
+            let regex = RegexBuilder::new(&pattern)
+                .case_insensitive(!options.case_sensitive)
+                .build()
+                .map_err(|e| {
+                    checkstream_core::Error::config(format!(
+                        "Invalid pattern '{}' in classifier '{}': {}",
+                        pattern, name, e
+                    ))
+                })?;
+            regexes.push((regex, rule.label, rule.score));
+        }
 
         let ac = AhoCorasick::builder()
-            .ascii_case_insensitive(true)
-            .build(&pattern_strs)
+            .ascii_case_insensitive(!options.case_sensitive)
+            .build(&literals)
             .map_err(|e| {
                 checkstream_core::Error::classifier(format!(
                     "Failed to build pattern matcher: {}",
@@ -28,43 +140,128 @@ impl PatternClassifier {
             })?;
 
         Ok(Self {
-            name: name.into(),
+            name,
             patterns: ac,
-            pattern_labels: labels,
+            pattern_labels,
+            regexes,
+            word_boundary: options.word_boundary,
         })
     }
+
+    fn literal_span(&self, m: aho_corasick::Match) -> Span {
+        let (label, score) = &self.pattern_labels[m.pattern().as_usize()];
+        Span::new(m.start(), m.end(), label.as_str(), *score)
+    }
 }
 
-#[async_trait::async_trait]
-impl Classifier for PatternClassifier {
-    async fn classify(&self, text: &str) -> Result<ClassificationResult> {
-        let start = Instant::now();
+/// Whether `text[start..end]` is not adjoined by word characters
+fn is_whole_word(text: &str, start: usize, end: usize) -> bool {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    !text[..start].chars().next_back().is_some_and(is_word)
+        && !text[end..].chars().next().is_some_and(is_word)
+}
+
+impl SpanDetector for PatternClassifier {
+    fn detect(&self, text: &str) -> Vec<Span> {
+        let mut spans: Vec<Span> = if self.word_boundary {
+            // A rejected match may hide a whole-word match overlapping it
+            self.patterns
+                .find_overlapping_iter(text)
+                .filter(|m| is_whole_word(text, m.start(), m.end()))
+                .map(|m| self.literal_span(m))
+                .collect()
+        } else {
+            self.patterns
+                .find_iter(text)
+                .map(|m| self.literal_span(m))
+                .collect()
+        };
 
-        let matches: Vec<_> = self.patterns.find_iter(text).collect();
+        for (regex, label, score) in &self.regexes {
+            spans.extend(
+                regex
+                    .find_iter(text)
+                    .filter(|m| !self.word_boundary || is_whole_word(text, m.start(), m.end()))
+                    .map(|m| Span::new(m.start(), m.end(), label.as_str(), *score)),
+            );
+        }
 
-        let result = if matches.is_empty() {
-            ClassificationResult {
-                label: "clean".to_string(),
-                score: 0.0,
-                metadata: ClassificationMetadata::default(),
-                latency_us: start.elapsed().as_micros() as u64,
+        if self.word_boundary || !self.regexes.is_empty() {
+            // Leftmost, then longest, match wins where matches overlap
+            spans.sort_by(|a, b| a.start.cmp(&b.start).then(b.end.cmp(&a.end)));
+            let mut kept: Vec<Span> = Vec::with_capacity(spans.len());
+            for span in spans {
+                if !kept.last().is_some_and(|last| last.overlaps(&span)) {
+                    kept.push(span);
+                }
             }
-        } else {
-            let match_info = &matches[0];
-            let label = self.pattern_labels[match_info.pattern().as_usize()].clone();
+            spans = kept;
+        }
+        spans
+    }
 
-            let metadata = ClassificationMetadata {
-                spans: matches.iter().map(|m| (m.start(), m.end())).collect(),
-                ..Default::default()
-            };
+    fn summarize(&self, spans: Vec<Span>) -> ClassificationResult {
+        // First of the highest-scoring matches
+        let Some(top) = spans
+            .iter()
+            .reduce(|best, span| if span.score > best.score { span } else { best })
+        else {
+            return ClassificationResult::new("clean", 0.0);
+        };
 
-            ClassificationResult {
-                label,
-                score: 1.0, // Pattern matches are binary
-                metadata,
-                latency_us: start.elapsed().as_micros() as u64,
+        let mut all_scores: Vec<(String, f32)> = Vec::new();
+        for span in &spans {
+            match all_scores
+                .iter_mut()
+                .find(|(label, _)| *label == span.label)
+            {
+                Some((_, score)) => *score = score.max(span.score),
+                None => all_scores.push((span.label.clone(), span.score)),
             }
+        }
+
+        ClassificationResult {
+            label: top.label.clone(),
+            score: top.score,
+            metadata: ClassificationMetadata {
+                all_scores: Some(all_scores),
+                spans,
+                ..Default::default()
+            },
+            latency_us: 0,
+        }
+    }
+
+    fn lookback(&self) -> usize {
+        // A pattern split across chunks starts at most this far back, plus
+        // the character before it for the word boundary check
+        let literal = self.patterns.max_pattern_len().saturating_sub(1);
+        let longest = if self.regexes.is_empty() {
+            literal
+        } else {
+            literal.max(MAX_REGEX_MATCH_LEN)
         };
+        if self.word_boundary {
+            longest + 4
+        } else {
+            longest
+        }
+    }
+
+    fn matches_can_grow(&self) -> bool {
+        // Regex repetition can extend a match, and a letter in the next chunk
+        // can make a whole word part of a longer one
+        !self.regexes.is_empty() || self.word_boundary
+    }
+}
+
+#[async_trait::async_trait]
+impl Classifier for PatternClassifier {
+    async fn classify(&self, text: &str) -> Result<ClassificationResult> {
+        let start = Instant::now();
+
+        let mut result = self.summarize(self.detect(text));
+        result.latency_us = start.elapsed().as_micros() as u64;
 
         Ok(result)
     }
@@ -76,6 +273,10 @@ impl Classifier for PatternClassifier {
     fn tier(&self) -> ClassifierTier {
         ClassifierTier::A
     }
+
+    fn incremental(&self) -> Option<Box<dyn IncrementalScanner>> {
+        Some(Box::new(SpanScanner::new(self.clone())))
+    }
 }
 
 #[cfg(test)]
@@ -103,4 +304,51 @@ mod tests {
         assert_eq!(result.score, 1.0);
         assert!(!result.metadata.spans.is_empty());
     }
+
+    #[tokio::test]
+    async fn test_rules_scores_and_options() {
+        let rules = vec![
+            PatternRule::literal("competitor", "acme", 0.4),
+            PatternRule::regex("account", r"\b\d{8}\b", 0.9),
+            PatternRule::literal("insult", "crétin", 0.7),
+        ];
+        let options = PatternOptions {
+            case_sensitive: false,
+            word_boundary: true,
+        };
+        let classifier = PatternClassifier::with_rules("custom", rules, options).unwrap();
+
+        // Inside a longer word
+        let result = classifier.classify("acmeology").await.unwrap();
+        assert_eq!(result.label, "clean");
+
+        let result = classifier
+            .classify("ACME says send 12345678 to that CRÉTIN")
+            .await
+            .unwrap();
+        assert_eq!(result.label, "account");
+        assert_eq!(result.score, 0.9);
+        let scores = result.metadata.all_scores.unwrap();
+        assert!(scores.contains(&("competitor".to_string(), 0.4)));
+        assert!(scores.contains(&("insult".to_string(), 0.7)));
+
+        let strict = PatternClassifier::with_rules(
+            "strict",
+            vec![PatternRule::literal("brand", "Acme", 1.0)],
+            PatternOptions {
+                case_sensitive: true,
+                word_boundary: false,
+            },
+        )
+        .unwrap();
+        assert_eq!(strict.classify("acme").await.unwrap().label, "clean");
+        assert_eq!(strict.classify("Acme").await.unwrap().label, "brand");
+
+        let invalid = PatternClassifier::with_rules(
+            "invalid",
+            vec![PatternRule::regex("bad", "(unclosed", 1.0)],
+            PatternOptions::default(),
+        );
+        assert!(invalid.is_err());
+    }
 }
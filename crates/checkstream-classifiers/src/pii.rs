@@ -1,45 +1,189 @@
 //! PII detection classifier (Tier A)
+//!
+//! Detects US and UK/EU personal data with regexes, then validates candidates
+//! where the format allows it (Luhn, IBAN mod-97, NHS mod-11, address parsing).
+//! Every occurrence is returned as a typed span with its own confidence.
+//! Entities that are too generic on their own (dates of birth, passport
+//! numbers, bare sort codes) are only reported next to a context keyword.
 
-use crate::classifier::{ClassificationMetadata, ClassificationResult, Classifier, ClassifierTier};
+use crate::classifier::{
+    ClassificationMetadata, ClassificationResult, Classifier, ClassifierTier, Span,
+};
+use crate::incremental::{IncrementalScanner, SpanDetector, SpanScanner};
 use checkstream_core::Result;
 use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::net::{Ipv4Addr, Ipv6Addr};
 use std::time::Instant;
 
-/// PII detection classifier using regex patterns
+/// How far before a match to look for context keywords, in bytes
+const CONTEXT_WINDOW: usize = 40;
+
+/// Longest entity rescanned across streamed chunks, in bytes
+const MAX_ENTITY_LEN: usize = 256;
+
+const DOB_KEYWORDS: &[&str] = &["born", "dob", "d.o.b", "date of birth", "birthday"];
+const PASSPORT_KEYWORDS: &[&str] = &["passport"];
+const NHS_KEYWORDS: &[&str] = &["nhs"];
+const SORT_CODE_KEYWORDS: &[&str] = &["sort code", "sort-code", "sortcode"];
+
+/// PII entity types
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PiiEntity {
+    Email,
+    Phone,
+    Ssn,
+    CreditCard,
+    Iban,
+    UkBankAccount,
+    UkNationalInsurance,
+    NhsNumber,
+    UkPostcode,
+    Passport,
+    Ipv4,
+    Ipv6,
+    DateOfBirth,
+}
+
+impl PiiEntity {
+    /// All supported entity types
+    pub const ALL: [PiiEntity; 13] = [
+        PiiEntity::Email,
+        PiiEntity::Phone,
+        PiiEntity::Ssn,
+        PiiEntity::CreditCard,
+        PiiEntity::Iban,
+        PiiEntity::UkBankAccount,
+        PiiEntity::UkNationalInsurance,
+        PiiEntity::NhsNumber,
+        PiiEntity::UkPostcode,
+        PiiEntity::Passport,
+        PiiEntity::Ipv4,
+        PiiEntity::Ipv6,
+        PiiEntity::DateOfBirth,
+    ];
+
+    /// Label used in spans and metadata
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PiiEntity::Email => "email",
+            PiiEntity::Phone => "phone",
+            PiiEntity::Ssn => "ssn",
+            PiiEntity::CreditCard => "credit_card",
+            PiiEntity::Iban => "iban",
+            PiiEntity::UkBankAccount => "uk_bank_account",
+            PiiEntity::UkNationalInsurance => "uk_national_insurance",
+            PiiEntity::NhsNumber => "nhs_number",
+            PiiEntity::UkPostcode => "uk_postcode",
+            PiiEntity::Passport => "passport",
+            PiiEntity::Ipv4 => "ipv4",
+            PiiEntity::Ipv6 => "ipv6",
+            PiiEntity::DateOfBirth => "date_of_birth",
+        }
+    }
+}
+
+/// PII detector settings (`pii:` section of the classifiers config)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PiiConfig {
+    /// Entity types to detect (all if not set)
+    #[serde(default)]
+    pub entities: Option<Vec<PiiEntity>>,
+
+    /// Drop entities below this confidence
+    #[serde(default = "default_min_confidence")]
+    pub min_confidence: f32,
+}
+
+impl Default for PiiConfig {
+    fn default() -> Self {
+        Self {
+            entities: None,
+            min_confidence: default_min_confidence(),
+        }
+    }
+}
+
+fn default_min_confidence() -> f32 {
+    0.5
+}
+
+/// A candidate match before validation
+struct Candidate<'t> {
+    start: usize,
+    end: usize,
+    text: &'t str,
+}
+
+/// PII detection classifier using regex patterns and checksum validation
+#[derive(Clone)]
 pub struct PiiClassifier {
-    email_regex: Regex,
-    phone_regex: Regex,
-    ssn_regex: Regex,
-    credit_card_regex: Regex,
+    detectors: Vec<(PiiEntity, Regex)>,
+    min_confidence: f32,
 }
 
 impl PiiClassifier {
-    /// Create a new PII classifier
+    /// Create a PII classifier that detects every entity type
     pub fn new() -> Result<Self> {
+        Self::with_config(&PiiConfig::default())
+    }
+
+    /// Create a PII classifier from configuration
+    pub fn with_config(config: &PiiConfig) -> Result<Self> {
+        let enabled = config.entities.as_deref().unwrap_or(&PiiEntity::ALL);
+
+        let mut detectors = Vec::with_capacity(enabled.len());
+        for entity in PiiEntity::ALL {
+            if !enabled.contains(&entity) {
+                continue;
+            }
+            let regex = Regex::new(pattern(entity)).map_err(|e| {
+                checkstream_core::Error::classifier(format!(
+                    "Failed to compile {} regex: {}",
+                    entity.as_str(),
+                    e
+                ))
+            })?;
+            detectors.push((entity, regex));
+        }
+
         Ok(Self {
-            email_regex: Regex::new(r"\b[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Z|a-z]{2,}\b")
-                .map_err(|e| {
-                    checkstream_core::Error::classifier(format!(
-                        "Failed to compile email regex: {}",
-                        e
-                    ))
-                })?,
-            phone_regex: Regex::new(r"\b\d{3}[-.]?\d{3}[-.]?\d{4}\b").map_err(|e| {
-                checkstream_core::Error::classifier(format!("Failed to compile phone regex: {}", e))
-            })?,
-            ssn_regex: Regex::new(r"\b\d{3}-\d{2}-\d{4}\b").map_err(|e| {
-                checkstream_core::Error::classifier(format!("Failed to compile SSN regex: {}", e))
-            })?,
-            credit_card_regex: Regex::new(r"\b\d{4}[-\s]?\d{4}[-\s]?\d{4}[-\s]?\d{4}\b").map_err(
-                |e| {
-                    checkstream_core::Error::classifier(format!(
-                        "Failed to compile credit card regex: {}",
-                        e
-                    ))
-                },
-            )?,
+            detectors,
+            min_confidence: config.min_confidence,
         })
     }
+
+    /// Find every PII entity in `text`
+    ///
+    /// Overlapping matches are resolved in favour of the more confident one.
+    pub fn detect(&self, text: &str) -> Vec<Span> {
+        let mut found = Vec::new();
+        for (entity, regex) in &self.detectors {
+            for m in regex.find_iter(text) {
+                let candidate = Candidate {
+                    start: m.start(),
+                    end: m.end(),
+                    text: m.as_str(),
+                };
+                if let Some(confidence) = confidence(*entity, &candidate, text) {
+                    if confidence >= self.min_confidence {
+                        found.push(Span::new(m.start(), m.end(), entity.as_str(), confidence));
+                    }
+                }
+            }
+        }
+
+        found.sort_by(|a, b| b.score.total_cmp(&a.score).then(a.start.cmp(&b.start)));
+        let mut spans: Vec<Span> = Vec::with_capacity(found.len());
+        for span in found {
+            if !spans.iter().any(|kept| kept.overlaps(&span)) {
+                spans.push(span);
+            }
+        }
+        spans.sort_by_key(|span| span.start);
+        spans
+    }
 }
 
 impl Default for PiiClassifier {
@@ -48,79 +192,260 @@ impl Default for PiiClassifier {
     }
 }
 
+impl SpanDetector for PiiClassifier {
+    fn detect(&self, text: &str) -> Vec<Span> {
+        PiiClassifier::detect(self, text)
+    }
+
+    fn summarize(&self, spans: Vec<Span>) -> ClassificationResult {
+        if spans.is_empty() {
+            return ClassificationResult::new("no_pii", 0.0);
+        }
+
+        // Highest confidence per entity type, in order of first occurrence
+        let mut entity_scores: Vec<(String, f32)> = Vec::new();
+        for span in &spans {
+            match entity_scores
+                .iter_mut()
+                .find(|(label, _)| *label == span.label)
+            {
+                Some((_, score)) => *score = score.max(span.score),
+                None => entity_scores.push((span.label.clone(), span.score)),
+            }
+        }
+        let score = entity_scores.iter().map(|(_, s)| *s).fold(0.0, f32::max);
+
+        let metadata = ClassificationMetadata {
+            extra: entity_scores
+                .iter()
+                .map(|(label, _)| ("pii_type".to_string(), label.clone()))
+                .collect(),
+            all_scores: Some(entity_scores),
+            spans,
+            ..Default::default()
+        };
+
+        ClassificationResult {
+            label: "pii_detected".to_string(),
+            score,
+            metadata,
+            latency_us: 0,
+        }
+    }
+
+    fn lookback(&self) -> usize {
+        MAX_ENTITY_LEN + CONTEXT_WINDOW
+    }
+}
+
 #[async_trait::async_trait]
 impl Classifier for PiiClassifier {
     async fn classify(&self, text: &str) -> Result<ClassificationResult> {
         let start = Instant::now();
 
-        let mut pii_types = Vec::new();
-        let mut spans = Vec::new();
+        let mut result = self.summarize(self.detect(text));
+        result.latency_us = start.elapsed().as_micros() as u64;
 
-        // Check for emails
-        if let Some(mat) = self.email_regex.find(text) {
-            pii_types.push("email");
-            spans.push((mat.start(), mat.end()));
-        }
+        Ok(result)
+    }
 
-        // Check for phone numbers
-        if let Some(mat) = self.phone_regex.find(text) {
-            pii_types.push("phone");
-            spans.push((mat.start(), mat.end()));
-        }
+    fn name(&self) -> &str {
+        "pii_detector"
+    }
+
+    fn tier(&self) -> ClassifierTier {
+        ClassifierTier::A
+    }
+
+    fn incremental(&self) -> Option<Box<dyn IncrementalScanner>> {
+        Some(Box::new(SpanScanner::new(self.clone())))
+    }
+}
 
-        // Check for SSN
-        if let Some(mat) = self.ssn_regex.find(text) {
-            pii_types.push("ssn");
-            spans.push((mat.start(), mat.end()));
+/// Candidate regex for each entity type
+fn pattern(entity: PiiEntity) -> &'static str {
+    match entity {
+        PiiEntity::Email => r"\b[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}\b",
+        PiiEntity::Phone => {
+            r"\b\d{3}[-.]?\d{3}[-.]?\d{4}\b|(?:\+44\s?|\b0)7\d{3}\s?\d{3}\s?\d{3}\b"
         }
+        PiiEntity::Ssn => r"\b\d{3}-\d{2}-\d{4}\b",
+        PiiEntity::CreditCard => r"\b\d(?:[ -]?\d){12,18}\b",
+        PiiEntity::Iban => r"\b[A-Z]{2}\d{2}(?: ?[A-Z0-9]{4}){2,7}(?: ?[A-Z0-9]{1,3})?\b",
+        PiiEntity::UkBankAccount => r"\b\d{2}[- ]?\d{2}[- ]?\d{2}(?:[\s,;/]+\d{8})?\b",
+        PiiEntity::UkNationalInsurance => {
+            r"\b[A-CEGHJ-PR-TW-Z][A-CEGHJ-NPR-TW-Z] ?\d{2} ?\d{2} ?\d{2} ?[A-D]\b"
+        }
+        PiiEntity::NhsNumber => r"\b\d{3}[ -]?\d{3}[ -]?\d{4}\b",
+        PiiEntity::UkPostcode => r"\b[A-Z]{1,2}\d[A-Z\d]? ?\d[A-Z]{2}\b",
+        PiiEntity::Passport => r"\b(?:\d{9}|[A-Z]{1,2}\d{6,8})\b",
+        PiiEntity::Ipv4 => r"\b(?:\d{1,3}\.){3}\d{1,3}\b",
+        PiiEntity::Ipv6 => r"[0-9A-Fa-f]{0,4}(?::[0-9A-Fa-f]{0,4}){2,7}",
+        PiiEntity::DateOfBirth => {
+            r"(?i)\b(?:\d{1,2}[/.-]\d{1,2}[/.-](?:\d{4}|\d{2})|\d{4}-\d{2}-\d{2}|\d{1,2}(?:st|nd|rd|th)? (?:jan|feb|mar|apr|may|jun|jul|aug|sep|oct|nov|dec)[a-z]*\.? \d{4})\b"
+        }
+    }
+}
+
+/// Validate a candidate and return its confidence, or `None` to reject it
+fn confidence(entity: PiiEntity, candidate: &Candidate, text: &str) -> Option<f32> {
+    let digits = || -> Vec<u32> {
+        candidate
+            .text
+            .chars()
+            .filter_map(|c| c.to_digit(10))
+            .collect()
+    };
 
-        // Check for credit cards
-        if let Some(mat) = self.credit_card_regex.find(text) {
-            pii_types.push("credit_card");
-            spans.push((mat.start(), mat.end()));
+    match entity {
+        PiiEntity::Email => Some(0.95),
+        PiiEntity::Phone => {
+            if candidate.text.starts_with('+') || candidate.text.starts_with('0') {
+                Some(0.8)
+            } else {
+                Some(0.6)
+            }
+        }
+        PiiEntity::Ssn => {
+            let d = digits();
+            let area = d[0] * 100 + d[1] * 10 + d[2];
+            let group = d[3] * 10 + d[4];
+            let serial = d[5..].iter().fold(0, |acc, x| acc * 10 + x);
+            let valid = area != 0 && area != 666 && area < 900 && group != 0 && serial != 0;
+            valid.then_some(0.85)
         }
+        PiiEntity::CreditCard => {
+            let d = digits();
+            let known_prefix = matches!(d[0], 2..=6);
+            (known_prefix && (13..=19).contains(&d.len()) && luhn_valid(&d)).then_some(0.99)
+        }
+        PiiEntity::Iban => iban_valid(candidate.text).then_some(0.99),
+        PiiEntity::UkBankAccount => {
+            if digits().len() == 14 {
+                Some(0.9)
+            } else {
+                has_context(text, candidate.start, SORT_CODE_KEYWORDS).then_some(0.8)
+            }
+        }
+        PiiEntity::UkNationalInsurance => {
+            let prefix = &candidate.text[..2];
+            let invalid_prefix = ["BG", "GB", "KN", "NK", "NT", "TN", "ZZ"].contains(&prefix);
+            (!invalid_prefix).then_some(0.9)
+        }
+        PiiEntity::NhsNumber => {
+            if !nhs_valid(&digits()) {
+                None
+            } else if has_context(text, candidate.start, NHS_KEYWORDS) {
+                Some(0.95)
+            } else {
+                Some(0.7)
+            }
+        }
+        PiiEntity::UkPostcode => Some(0.8),
+        PiiEntity::Passport => {
+            has_context(text, candidate.start, PASSPORT_KEYWORDS).then_some(0.85)
+        }
+        PiiEntity::Ipv4 => candidate.text.parse::<Ipv4Addr>().ok().map(|_| 0.9),
+        PiiEntity::Ipv6 => {
+            let boundary_ok = !touches_word(text, candidate.start, candidate.end);
+            let valid = boundary_ok && candidate.text.parse::<Ipv6Addr>().is_ok();
+            valid.then_some(0.9)
+        }
+        PiiEntity::DateOfBirth => has_context(text, candidate.start, DOB_KEYWORDS).then_some(0.9),
+    }
+}
 
-        let result = if pii_types.is_empty() {
-            ClassificationResult {
-                label: "no_pii".to_string(),
-                score: 0.0,
-                metadata: ClassificationMetadata::default(),
-                latency_us: start.elapsed().as_micros() as u64,
+/// Luhn checksum over card digits
+fn luhn_valid(digits: &[u32]) -> bool {
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, &d)| {
+            if i % 2 == 1 {
+                let doubled = d * 2;
+                if doubled > 9 {
+                    doubled - 9
+                } else {
+                    doubled
+                }
+            } else {
+                d
             }
+        })
+        .sum();
+    sum % 10 == 0
+}
+
+/// IBAN length and ISO 13616 mod-97 check
+fn iban_valid(candidate: &str) -> bool {
+    let iban: String = candidate.chars().filter(|c| !c.is_whitespace()).collect();
+    if !(15..=34).contains(&iban.len()) {
+        return false;
+    }
+
+    // Move the country code and check digits to the end, map letters to 10..35
+    let rearranged = iban[4..].chars().chain(iban[..4].chars());
+    let mut remainder = 0u32;
+    for c in rearranged {
+        let value = match c.to_digit(36) {
+            Some(v) => v,
+            None => return false,
+        };
+        remainder = if value < 10 {
+            (remainder * 10 + value) % 97
         } else {
-            let metadata = ClassificationMetadata {
-                spans,
-                extra: pii_types
-                    .iter()
-                    .map(|t| ("pii_type".to_string(), t.to_string()))
-                    .collect(),
-                ..Default::default()
-            };
-
-            ClassificationResult {
-                label: "pii_detected".to_string(),
-                score: 1.0,
-                metadata,
-                latency_us: start.elapsed().as_micros() as u64,
-            }
+            (remainder * 100 + value) % 97
         };
-
-        Ok(result)
     }
+    remainder == 1
+}
 
-    fn name(&self) -> &str {
-        "pii_detector"
+/// NHS number modulus 11 check digit
+fn nhs_valid(digits: &[u32]) -> bool {
+    if digits.len() != 10 {
+        return false;
+    }
+    let sum: u32 = digits[..9]
+        .iter()
+        .enumerate()
+        .map(|(i, d)| d * (10 - i as u32))
+        .sum();
+    match 11 - (sum % 11) {
+        11 => digits[9] == 0,
+        10 => false,
+        check => digits[9] == check,
     }
+}
 
-    fn tier(&self) -> ClassifierTier {
-        ClassifierTier::A
+/// Whether one of `keywords` appears shortly before `start`
+fn has_context(text: &str, start: usize, keywords: &[&str]) -> bool {
+    let mut from = start.saturating_sub(CONTEXT_WINDOW);
+    while !text.is_char_boundary(from) {
+        from -= 1;
     }
+    let window = text[from..start].to_lowercase();
+    keywords.iter().any(|keyword| window.contains(keyword))
+}
+
+/// Whether the match is glued to surrounding word characters
+fn touches_word(text: &str, start: usize, end: usize) -> bool {
+    let before = text[..start].chars().next_back();
+    let after = text[end..].chars().next();
+    [before, after]
+        .into_iter()
+        .flatten()
+        .any(|c| c.is_alphanumeric() || c == '_')
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn labels(spans: &[Span]) -> Vec<&str> {
+        spans.iter().map(|s| s.label.as_str()).collect()
+    }
+
     #[tokio::test]
     async fn test_email_detection() {
         let classifier = PiiClassifier::new().unwrap();
@@ -130,7 +455,7 @@ mod tests {
             .await
             .unwrap();
         assert_eq!(result.label, "pii_detected");
-        assert_eq!(result.score, 1.0);
+        assert!(result.score >= 0.9);
     }
 
     #[tokio::test]
@@ -152,4 +477,118 @@ mod tests {
             .unwrap();
         assert_eq!(result.label, "pii_detected");
     }
+
+    #[test]
+    fn test_all_occurrences_returned() {
+        let classifier = PiiClassifier::new().unwrap();
+        let text = "Mail a@example.com or b@example.org";
+
+        let spans = classifier.detect(text);
+        assert_eq!(labels(&spans), vec!["email", "email"]);
+        assert_eq!(&text[spans[1].start..spans[1].end], "b@example.org");
+    }
+
+    #[test]
+    fn test_credit_card_requires_luhn() {
+        let classifier = PiiClassifier::new().unwrap();
+
+        let valid = classifier.detect("Card: 4111 1111 1111 1111");
+        assert_eq!(labels(&valid), vec!["credit_card"]);
+        assert_eq!(valid[0].score, 0.99);
+
+        assert!(classifier.detect("Order 4111 1111 1111 1112").is_empty());
+    }
+
+    #[test]
+    fn test_iban_mod97() {
+        let classifier = PiiClassifier::new().unwrap();
+
+        let spans = classifier.detect("Pay GB82 WEST 1234 5698 7654 32 today");
+        assert_eq!(labels(&spans), vec!["iban"]);
+        assert!(iban_valid("DE89370400440532013000"));
+        assert!(!iban_valid("GB82WEST12345698765433"));
+    }
+
+    #[test]
+    fn test_uk_identifiers() {
+        let classifier = PiiClassifier::new().unwrap();
+
+        let spans = classifier.detect("NI number AB 12 34 56 C, NHS number 943 476 5919");
+        assert_eq!(labels(&spans), vec!["uk_national_insurance", "nhs_number"]);
+        assert_eq!(spans[1].score, 0.95);
+
+        // Invalid NHS check digit
+        assert!(classifier.detect("NHS number 943 476 5918").is_empty());
+        // Disallowed NINO prefix
+        assert!(classifier.detect("ref GB 12 34 56 A").is_empty());
+    }
+
+    #[test]
+    fn test_uk_bank_account_and_postcode() {
+        let classifier = PiiClassifier::new().unwrap();
+
+        let spans = classifier.detect("Account 20-00-00 12345678, SW1A 1AA");
+        assert_eq!(labels(&spans), vec!["uk_bank_account", "uk_postcode"]);
+
+        // A bare sort code needs context
+        assert!(classifier.detect("version 20-00-00").is_empty());
+        assert_eq!(
+            labels(&classifier.detect("sort code 20-00-00")),
+            vec!["uk_bank_account"]
+        );
+    }
+
+    #[test]
+    fn test_context_entities() {
+        let classifier = PiiClassifier::new().unwrap();
+
+        assert_eq!(
+            labels(&classifier.detect("Date of birth: 14/03/1985")),
+            vec!["date_of_birth"]
+        );
+        assert!(classifier.detect("Meeting on 14/03/2025").is_empty());
+
+        assert_eq!(
+            labels(&classifier.detect("Passport no. 123456789")),
+            vec!["passport"]
+        );
+    }
+
+    #[test]
+    fn test_ip_addresses() {
+        let classifier = PiiClassifier::new().unwrap();
+
+        let spans = classifier.detect("From 192.168.1.20 and 2001:db8::8a2e:370:7334 at 10:30");
+        assert_eq!(labels(&spans), vec!["ipv4", "ipv6"]);
+        assert!(classifier.detect("Version 999.1.1.1").is_empty());
+    }
+
+    #[test]
+    fn test_enabled_entities() {
+        let config = PiiConfig {
+            entities: Some(vec![PiiEntity::Iban]),
+            ..Default::default()
+        };
+        let classifier = PiiClassifier::with_config(&config).unwrap();
+
+        let spans = classifier.detect("john@example.com GB82WEST12345698765432");
+        assert_eq!(labels(&spans), vec!["iban"]);
+    }
+
+    #[tokio::test]
+    async fn test_entity_scores_in_metadata() {
+        let classifier = PiiClassifier::new().unwrap();
+
+        let result = classifier
+            .classify("Call 555-123-4567 or mail john@example.com")
+            .await
+            .unwrap();
+        let scores = result.metadata.all_scores.unwrap();
+        assert_eq!(
+            scores,
+            vec![("phone".to_string(), 0.6), ("email".to_string(), 0.95)]
+        );
+        assert_eq!(result.score, 0.95);
+        assert_eq!(result.metadata.spans.len(), 2);
+    }
 }
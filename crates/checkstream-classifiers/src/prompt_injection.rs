@@ -11,9 +11,14 @@
 //! - Unicode obfuscation attempts
 //! - System prompt extraction attempts
 
-use crate::classifier::{ClassificationMetadata, ClassificationResult, Classifier, ClassifierTier};
+use crate::classifier::{
+    ClassificationMetadata, ClassificationResult, Classifier, ClassifierTier, Span,
+};
+use crate::incremental::{IncrementalScanner, SpanDetector, SpanScanner};
+use crate::lexicon::{self, CategoryPhrases, LexiconPack};
 use aho_corasick::AhoCorasick;
 use checkstream_core::Result;
+use std::collections::HashMap;
 use std::time::Instant;
 
 /// Categories of prompt injection attacks
@@ -34,6 +39,15 @@ pub enum InjectionCategory {
 }
 
 impl InjectionCategory {
+    /// Every attack category, in detection order
+    pub const ATTACKS: [Self; 5] = [
+        Self::Jailbreak,
+        Self::InstructionOverride,
+        Self::SystemExtraction,
+        Self::RoleSwitching,
+        Self::DelimiterAttack,
+    ];
+
     /// Get the severity score for this category
     pub fn severity(&self) -> f32 {
         match self {
@@ -60,6 +74,7 @@ impl InjectionCategory {
 }
 
 /// Pattern-based prompt injection classifier
+#[derive(Clone)]
 pub struct PromptInjectionClassifier {
     name: String,
     /// Patterns for instruction override detection
@@ -87,6 +102,20 @@ impl PromptInjectionClassifier {
 
     /// Create with a custom name
     pub fn with_name(name: impl Into<String>) -> Result<Self> {
+        Self::with_lexicons(name, &[])
+    }
+
+    /// Create with a custom name, adding the prompt injection phrases of `packs`
+    pub fn with_lexicons(name: impl Into<String>, packs: &[LexiconPack]) -> Result<Self> {
+        let mut phrases = Self::default_phrases();
+        for pack in packs {
+            lexicon::merge_categories(&mut phrases, pack.phrases_for("prompt-injection"));
+        }
+        Self::with_phrases(name, phrases)
+    }
+
+    /// Built-in phrases by category label
+    pub fn default_phrases() -> CategoryPhrases {
         // Instruction override patterns
         let instruction_override_patterns = vec![
             "ignore previous instructions".to_string(),
@@ -188,6 +217,45 @@ impl PromptInjectionClassifier {
             "</s>".to_string(),
         ];
 
+        HashMap::from([
+            (
+                InjectionCategory::InstructionOverride.label().to_string(),
+                instruction_override_patterns,
+            ),
+            (
+                InjectionCategory::RoleSwitching.label().to_string(),
+                role_switching_patterns,
+            ),
+            (
+                InjectionCategory::Jailbreak.label().to_string(),
+                jailbreak_patterns,
+            ),
+            (
+                InjectionCategory::SystemExtraction.label().to_string(),
+                system_extraction_patterns,
+            ),
+            (
+                InjectionCategory::DelimiterAttack.label().to_string(),
+                delimiter_attack_patterns,
+            ),
+        ])
+    }
+
+    /// Create with a custom name and phrases by category label
+    ///
+    /// Categories missing from `phrases` match nothing.
+    pub fn with_phrases(name: impl Into<String>, mut phrases: CategoryPhrases) -> Result<Self> {
+        let name = name.into();
+        let known = InjectionCategory::ATTACKS.map(|category| category.label());
+        lexicon::check_categories(&name, &phrases, &known)?;
+        let mut take =
+            |category: InjectionCategory| phrases.remove(category.label()).unwrap_or_default();
+        let instruction_override_patterns = take(InjectionCategory::InstructionOverride);
+        let role_switching_patterns = take(InjectionCategory::RoleSwitching);
+        let jailbreak_patterns = take(InjectionCategory::Jailbreak);
+        let system_extraction_patterns = take(InjectionCategory::SystemExtraction);
+        let delimiter_attack_patterns = take(InjectionCategory::DelimiterAttack);
+
         let instruction_override = Self::build_matcher(&instruction_override_patterns)?;
         let role_switching = Self::build_matcher(&role_switching_patterns)?;
         let jailbreak = Self::build_matcher(&jailbreak_patterns)?;
@@ -195,7 +263,7 @@ impl PromptInjectionClassifier {
         let delimiter_attack = Self::build_matcher(&delimiter_attack_patterns)?;
 
         Ok(Self {
-            name: name.into(),
+            name,
             instruction_override,
             instruction_override_patterns,
             role_switching,
@@ -222,57 +290,84 @@ impl PromptInjectionClassifier {
             })
     }
 
-    /// Check for injection patterns and return category with matches
-    fn detect_category(&self, text: &str) -> (InjectionCategory, Vec<(usize, usize, String)>) {
-        let mut matches = Vec::new();
-
-        // Check each category in order of severity
-        // Jailbreak (highest severity)
-        for m in self.jailbreak.find_iter(text) {
-            let pattern = &self.jailbreak_patterns[m.pattern().as_usize()];
-            matches.push((m.start(), m.end(), pattern.clone()));
-        }
-        if !matches.is_empty() {
-            return (InjectionCategory::Jailbreak, matches);
+    /// Matcher and phrases for an attack category
+    fn matcher(&self, category: InjectionCategory) -> (&AhoCorasick, &[String]) {
+        match category {
+            InjectionCategory::InstructionOverride => (
+                &self.instruction_override,
+                &self.instruction_override_patterns,
+            ),
+            InjectionCategory::RoleSwitching => {
+                (&self.role_switching, &self.role_switching_patterns)
+            }
+            InjectionCategory::Jailbreak => (&self.jailbreak, &self.jailbreak_patterns),
+            InjectionCategory::SystemExtraction => {
+                (&self.system_extraction, &self.system_extraction_patterns)
+            }
+            InjectionCategory::DelimiterAttack => {
+                (&self.delimiter_attack, &self.delimiter_attack_patterns)
+            }
+            InjectionCategory::Clean => unreachable!("clean has no phrases"),
         }
+    }
+}
 
-        // Instruction override
-        for m in self.instruction_override.find_iter(text) {
-            let pattern = &self.instruction_override_patterns[m.pattern().as_usize()];
-            matches.push((m.start(), m.end(), pattern.clone()));
-        }
-        if !matches.is_empty() {
-            return (InjectionCategory::InstructionOverride, matches);
+impl SpanDetector for PromptInjectionClassifier {
+    /// Matches of every category, each scored with its category's severity
+    fn detect(&self, text: &str) -> Vec<Span> {
+        let mut spans = Vec::new();
+        for category in InjectionCategory::ATTACKS {
+            let (matcher, patterns) = self.matcher(category);
+            spans.extend(matcher.find_iter(text).map(|m| {
+                let pattern = patterns[m.pattern().as_usize()].as_str();
+                Span::new(m.start(), m.end(), pattern, category.severity())
+            }));
         }
+        spans
+    }
 
-        // System extraction
-        for m in self.system_extraction.find_iter(text) {
-            let pattern = &self.system_extraction_patterns[m.pattern().as_usize()];
-            matches.push((m.start(), m.end(), pattern.clone()));
-        }
-        if !matches.is_empty() {
-            return (InjectionCategory::SystemExtraction, matches);
-        }
+    fn summarize(&self, spans: Vec<Span>) -> ClassificationResult {
+        // The first category in detection order with a match wins
+        let category = InjectionCategory::ATTACKS
+            .into_iter()
+            .find(|category| spans.iter().any(|span| span.score == category.severity()))
+            .unwrap_or(InjectionCategory::Clean);
+        let score = category.severity();
+        let label = category.label().to_string();
+        let spans: Vec<Span> = spans
+            .into_iter()
+            .filter(|span| span.score == score)
+            .collect();
 
-        // Role switching
-        for m in self.role_switching.find_iter(text) {
-            let pattern = &self.role_switching_patterns[m.pattern().as_usize()];
-            matches.push((m.start(), m.end(), pattern.clone()));
-        }
-        if !matches.is_empty() {
-            return (InjectionCategory::RoleSwitching, matches);
-        }
+        let mut extra: Vec<(String, String)> = spans
+            .iter()
+            .map(|span| ("matched_pattern".to_string(), span.label.clone()))
+            .collect();
+        extra.push(("category".to_string(), label.clone()));
 
-        // Delimiter attacks
-        for m in self.delimiter_attack.find_iter(text) {
-            let pattern = &self.delimiter_attack_patterns[m.pattern().as_usize()];
-            matches.push((m.start(), m.end(), pattern.clone()));
-        }
-        if !matches.is_empty() {
-            return (InjectionCategory::DelimiterAttack, matches);
+        ClassificationResult {
+            label,
+            score,
+            metadata: ClassificationMetadata {
+                spans,
+                extra,
+                ..Default::default()
+            },
+            latency_us: 0,
         }
+    }
 
-        (InjectionCategory::Clean, matches)
+    fn lookback(&self) -> usize {
+        InjectionCategory::ATTACKS
+            .into_iter()
+            .map(|category| self.matcher(category).0.max_pattern_len())
+            .max()
+            .unwrap_or(0)
+            .saturating_sub(1)
+    }
+
+    fn matches_can_grow(&self) -> bool {
+        false
     }
 }
 
@@ -281,27 +376,10 @@ impl Classifier for PromptInjectionClassifier {
     async fn classify(&self, text: &str) -> Result<ClassificationResult> {
         let start = Instant::now();
 
-        let (category, matches) = self.detect_category(text);
-        let score = category.severity();
-        let label = category.label().to_string();
-
-        let mut extra: Vec<(String, String)> = matches
-            .iter()
-            .map(|(_, _, pattern)| ("matched_pattern".to_string(), pattern.clone()))
-            .collect();
-        extra.push(("category".to_string(), label.clone()));
-        let metadata = ClassificationMetadata {
-            spans: matches.iter().map(|(s, e, _)| (*s, *e)).collect(),
-            extra,
-            ..Default::default()
-        };
+        let mut result = self.summarize(self.detect(text));
+        result.latency_us = start.elapsed().as_micros() as u64;
 
-        Ok(ClassificationResult {
-            label,
-            score,
-            metadata,
-            latency_us: start.elapsed().as_micros() as u64,
-        })
+        Ok(result)
     }
 
     fn name(&self) -> &str {
@@ -311,6 +389,10 @@ impl Classifier for PromptInjectionClassifier {
     fn tier(&self) -> ClassifierTier {
         ClassifierTier::A // Pattern-based, fast
     }
+
+    fn incremental(&self) -> Option<Box<dyn IncrementalScanner>> {
+        Some(Box::new(SpanScanner::new(self.clone())))
+    }
 }
 
 #[cfg(test)]
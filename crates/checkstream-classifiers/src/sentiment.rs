@@ -3,8 +3,10 @@
 //! This is a lexicon-based classifier used when no external model is loaded.
 
 use crate::classifier::{ClassificationMetadata, ClassificationResult, Classifier, ClassifierTier};
+use crate::lexicon::{self, CategoryPhrases, LexiconPack};
 use aho_corasick::AhoCorasick;
 use checkstream_core::Result;
+use std::collections::HashMap;
 use std::time::Instant;
 
 pub struct SentimentClassifier {
@@ -19,7 +21,21 @@ impl SentimentClassifier {
     }
 
     pub fn with_name(name: impl Into<String>) -> Result<Self> {
-        let positive = vec![
+        Self::with_lexicons(name, &[])
+    }
+
+    /// Create with a custom name, adding the sentiment phrases of `packs`
+    pub fn with_lexicons(name: impl Into<String>, packs: &[LexiconPack]) -> Result<Self> {
+        let mut phrases = Self::default_phrases();
+        for pack in packs {
+            lexicon::merge_categories(&mut phrases, pack.phrases_for("sentiment"));
+        }
+        Self::with_phrases(name, phrases)
+    }
+
+    /// Built-in `positive` and `negative` phrases
+    pub fn default_phrases() -> CategoryPhrases {
+        let positive = [
             "good",
             "great",
             "excellent",
@@ -31,7 +47,7 @@ impl SentimentClassifier {
             "awesome",
             "best",
         ];
-        let negative = vec![
+        let negative = [
             "bad",
             "terrible",
             "awful",
@@ -43,6 +59,18 @@ impl SentimentClassifier {
             "disappointed",
             "poor",
         ];
+        HashMap::from([
+            ("positive".to_string(), positive.map(String::from).to_vec()),
+            ("negative".to_string(), negative.map(String::from).to_vec()),
+        ])
+    }
+
+    /// Create with a custom name and `positive` and `negative` phrases
+    pub fn with_phrases(name: impl Into<String>, mut phrases: CategoryPhrases) -> Result<Self> {
+        let name = name.into();
+        lexicon::check_categories(&name, &phrases, &lexicon::SENTIMENT_CATEGORIES)?;
+        let positive = phrases.remove("positive").unwrap_or_default();
+        let negative = phrases.remove("negative").unwrap_or_default();
 
         let positive = AhoCorasick::builder()
             .ascii_case_insensitive(true)
@@ -63,7 +91,7 @@ impl SentimentClassifier {
             })?;
 
         Ok(Self {
-            name: name.into(),
+            name,
             positive,
             negative,
         })
@@ -2,33 +2,169 @@
 //!
 //! This module provides utilities for running classifiers on streaming text
 //! with the ability to see previous chunks for context.
+//!
+//! The buffer keeps the stream exactly as it arrived. The context window
+//! counts chunks, characters, tokens or sentences back from the end of the
+//! stream; it always covers the newest chunk, and `overlap` extra units before
+//! it keep matches that span the window edge whole.
+//!
+//! Classifiers that support incremental scanning (pattern, PII and lexicon
+//! matching) are fed each chunk once and resume where they left off, so they cost time
+//! proportional to the chunk rather than the accumulated text. Other
+//! classifiers are re-run on the context window when their
+//! [`EvaluationSchedule`] says so, and their latest result is reused in
+//! between.
 
-use crate::{ClassificationResult, Classifier, ClassifierPipeline, PipelineExecutionResult};
+use crate::incremental::IncrementalScanner;
+use crate::long_text::TokenOffsets;
+use crate::pipeline::ReusedResults;
+use crate::{ClassificationResult, Classifier, ClassifierPipeline, PipelineExecutionResult, Span};
 use checkstream_core::Result;
-use std::collections::VecDeque;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
 use std::sync::Arc;
+use tracing::warn;
 
 /// Configuration for streaming classification behavior
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct StreamingConfig {
-    /// Number of previous chunks to include as context
-    /// - 0 = See entire buffer (all previous chunks)
-    /// - N = See last N chunks
-    pub context_chunks: usize,
+    /// How much of the stream classifiers see
+    pub window: ContextWindow,
+
+    /// Extra window units before the window start
+    pub overlap: usize,
+
+    /// Tokenizer for [`ContextWindow::Tokens`] (whitespace-separated words
+    /// if not set)
+    pub tokenizer: Option<TokenOffsets>,
 
-    /// Maximum buffer size (to prevent unbounded memory growth)
+    /// Maximum buffer size in chunks (to prevent unbounded memory growth)
     pub max_buffer_size: usize,
 
-    /// Delimiter to join chunks (typically " " or "")
-    pub chunk_delimiter: String,
+    /// When classifiers without incremental scanning are re-run
+    pub schedule: EvaluationSchedule,
+
+    /// Schedules for individual classifiers, by the name results report
+    pub classifier_schedules: HashMap<String, EvaluationSchedule>,
+}
+
+/// When a classifier is re-run during a stream
+///
+/// Incremental scanners see every chunk regardless; this applies to
+/// classifiers that have to classify the whole context window again.
+///
+/// In config: `every_chunk`, `sentence_boundary` or `every_tokens: N`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(from = "ScheduleSpec", into = "ScheduleSpec")]
+pub enum EvaluationSchedule {
+    /// After every chunk
+    #[default]
+    EveryChunk,
+
+    /// After a chunk that ends a sentence or line (`.`, `!`, `?` or newline)
+    SentenceBoundary,
+
+    /// Once at least this many whitespace-separated tokens have streamed
+    /// since the last run
+    EveryTokens(usize),
+}
+
+/// Config form of [`EvaluationSchedule`]
+///
+/// Untagged so `every_tokens: N` parses as a plain map.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(untagged)]
+enum ScheduleSpec {
+    Named(NamedSchedule),
+    Tokens { every_tokens: usize },
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum NamedSchedule {
+    EveryChunk,
+    SentenceBoundary,
+}
+
+impl From<ScheduleSpec> for EvaluationSchedule {
+    fn from(spec: ScheduleSpec) -> Self {
+        match spec {
+            ScheduleSpec::Named(NamedSchedule::EveryChunk) => Self::EveryChunk,
+            ScheduleSpec::Named(NamedSchedule::SentenceBoundary) => Self::SentenceBoundary,
+            ScheduleSpec::Tokens { every_tokens } => Self::EveryTokens(every_tokens),
+        }
+    }
+}
+
+impl From<EvaluationSchedule> for ScheduleSpec {
+    fn from(schedule: EvaluationSchedule) -> Self {
+        match schedule {
+            EvaluationSchedule::EveryChunk => Self::Named(NamedSchedule::EveryChunk),
+            EvaluationSchedule::SentenceBoundary => Self::Named(NamedSchedule::SentenceBoundary),
+            EvaluationSchedule::EveryTokens(every_tokens) => Self::Tokens { every_tokens },
+        }
+    }
+}
+
+impl EvaluationSchedule {
+    /// Whether a classifier is due after `chunk`, given the tokens streamed
+    /// since it last ran (including the chunk's)
+    pub fn is_due(&self, chunk: &str, tokens_since: usize) -> bool {
+        match self {
+            Self::EveryChunk => true,
+            Self::SentenceBoundary => chunk.contains(['.', '!', '?', '\n']),
+            Self::EveryTokens(tokens) => tokens_since >= *tokens,
+        }
+    }
+}
+
+/// Context window for streaming classification
+///
+/// Sizes count back from the end of the stream. The window never starts
+/// after the newest chunk, so a chunk longer than the window is still seen
+/// whole.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContextWindow {
+    /// Everything in the buffer
+    All,
+
+    /// Last N chunks
+    Chunks(usize),
+
+    /// Last N characters
+    Chars(usize),
+
+    /// Last N tokens
+    Tokens(usize),
+
+    /// Last N sentences (split after `.`, `!`, `?` or newlines); an
+    /// unfinished sentence at the end counts as one
+    Sentences(usize),
+}
+
+impl fmt::Debug for StreamingConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StreamingConfig")
+            .field("window", &self.window)
+            .field("overlap", &self.overlap)
+            .field("tokenizer", &self.tokenizer.as_ref().map(|_| "custom"))
+            .field("max_buffer_size", &self.max_buffer_size)
+            .field("schedule", &self.schedule)
+            .field("classifier_schedules", &self.classifier_schedules)
+            .finish()
+    }
 }
 
 impl Default for StreamingConfig {
     fn default() -> Self {
         Self {
-            context_chunks: 3,    // Last 3 chunks by default
+            window: ContextWindow::Chunks(3), // Last 3 chunks by default
+            overlap: 0,
+            tokenizer: None,
             max_buffer_size: 100, // Max 100 chunks
-            chunk_delimiter: " ".to_string(),
+            schedule: EvaluationSchedule::default(),
+            classifier_schedules: HashMap::new(),
         }
     }
 }
@@ -37,36 +173,74 @@ impl StreamingConfig {
     /// Create config that sees entire buffer
     pub fn entire_buffer() -> Self {
         Self {
-            context_chunks: 0, // 0 = entire buffer
+            window: ContextWindow::All,
             max_buffer_size: 1000,
-            chunk_delimiter: " ".to_string(),
+            ..Self::default()
         }
     }
 
     /// Create config that only sees current chunk (no context)
     pub fn no_context() -> Self {
         Self {
-            context_chunks: 1, // Only current chunk
+            window: ContextWindow::Chunks(1), // Only current chunk
             max_buffer_size: 10,
-            chunk_delimiter: " ".to_string(),
+            ..Self::default()
         }
     }
 
-    /// Create config with specific window size
+    /// Create config with specific window size in chunks (0 = entire buffer)
     pub fn with_window(chunks: usize) -> Self {
         Self {
-            context_chunks: chunks,
+            window: match chunks {
+                0 => ContextWindow::All,
+                n => ContextWindow::Chunks(n),
+            },
             max_buffer_size: chunks.max(100),
-            chunk_delimiter: " ".to_string(),
+            ..Self::default()
         }
     }
+
+    /// Create config with a character, token or sentence window
+    pub fn with_context(window: ContextWindow, overlap: usize) -> Self {
+        Self {
+            window,
+            overlap,
+            ..Self::default()
+        }
+    }
+
+    /// Count [`ContextWindow::Tokens`] with a tokenizer
+    pub fn with_tokenizer(mut self, tokenizer: TokenOffsets) -> Self {
+        self.tokenizer = Some(tokenizer);
+        self
+    }
+
+    /// Set the schedule for classifiers without their own
+    pub fn with_schedule(mut self, schedule: EvaluationSchedule) -> Self {
+        self.schedule = schedule;
+        self
+    }
+
+    /// Schedule for the named classifier
+    pub fn schedule_for(&self, classifier: &str) -> EvaluationSchedule {
+        self.classifier_schedules
+            .get(classifier)
+            .copied()
+            .unwrap_or(self.schedule)
+    }
 }
 
 /// Buffer for streaming chunks with context awareness
 #[derive(Debug)]
 pub struct StreamingBuffer {
-    /// Buffered chunks
-    chunks: VecDeque<String>,
+    /// Buffered text, exactly as streamed
+    text: String,
+
+    /// Stream offset where each buffered chunk ends
+    chunk_ends: VecDeque<usize>,
+
+    /// Stream offset of the first buffered byte
+    offset: usize,
 
     /// Configuration
     config: StreamingConfig,
@@ -76,7 +250,9 @@ impl StreamingBuffer {
     /// Create a new streaming buffer
     pub fn new(config: StreamingConfig) -> Self {
         Self {
-            chunks: VecDeque::with_capacity(config.max_buffer_size),
+            text: String::new(),
+            chunk_ends: VecDeque::with_capacity(config.max_buffer_size.min(1024)),
+            offset: 0,
             config,
         }
     }
@@ -84,66 +260,248 @@ impl StreamingBuffer {
     /// Add a new chunk to the buffer
     pub fn push(&mut self, chunk: String) -> Result<()> {
         // Prevent unbounded growth
-        if self.chunks.len() >= self.config.max_buffer_size {
+        if self.chunk_ends.len() >= self.config.max_buffer_size {
             // Remove oldest chunk
-            self.chunks.pop_front();
+            if let Some(end) = self.chunk_ends.pop_front() {
+                self.text.drain(..end - self.offset);
+                self.offset = end;
+            }
         }
 
-        self.chunks.push_back(chunk);
+        self.text.push_str(&chunk);
+        self.chunk_ends.push_back(self.offset + self.text.len());
         Ok(())
     }
 
     /// Get text for classification (current chunk + context)
     ///
-    /// # Returns
-    /// Concatenated string with context based on config:
-    /// - If context_chunks = 0: entire buffer
-    /// - If context_chunks = N: last N chunks
+    /// A slice of the stream as it arrived, covering the configured
+    /// [`ContextWindow`] plus overlap.
     pub fn get_context_text(&self) -> String {
-        if self.chunks.is_empty() {
-            return String::new();
-        }
+        self.text[self.context_range()].to_string()
+    }
+
+    /// Byte range of the buffered text in the context window
+    ///
+    /// Walks back from the end of the stream one unit at a time, so the cost
+    /// follows the window size rather than the buffered text.
+    fn context_range(&self) -> std::ops::Range<usize> {
+        let Some(newest) = self.newest_chunk_start() else {
+            return 0..0;
+        };
 
-        let chunks_vec: Vec<&String> = if self.config.context_chunks == 0 {
-            // Entire buffer
-            self.chunks.iter().collect()
-        } else {
-            // Last N chunks
-            let start = self.chunks.len().saturating_sub(self.config.context_chunks);
-            self.chunks.iter().skip(start).collect()
+        let start = match self.config.window {
+            ContextWindow::All => Some(0),
+            ContextWindow::Chunks(size) => {
+                self.window_start(size, newest, |pos| self.chunk_start_before(pos))
+            }
+            ContextWindow::Chars(size) => self.window_start(size, newest, |pos| {
+                let c = self.text[..pos].chars().next_back()?;
+                Some(pos - c.len_utf8())
+            }),
+            ContextWindow::Tokens(size) => match &self.config.tokenizer {
+                Some(tokenizer) => self.token_window_start(size, newest, tokenizer),
+                None => self.window_start(size, newest, |pos| self.word_start_before(pos)),
+            },
+            ContextWindow::Sentences(size) => {
+                self.window_start(size, newest, |pos| self.sentence_start_before(pos))
+            }
         };
+        start.unwrap_or(0)..self.text.len()
+    }
+
+    /// Start of the last `size` units, never after the newest chunk, stepped
+    /// back `overlap` more units
+    ///
+    /// `prev` gives the last unit start before a position. `None` when the
+    /// buffer runs out of units first.
+    fn window_start(
+        &self,
+        size: usize,
+        newest: usize,
+        prev: impl Fn(usize) -> Option<usize>,
+    ) -> Option<usize> {
+        let mut start = self.text.len();
+        for _ in 0..size {
+            start = prev(start)?;
+        }
+        start = start.min(newest);
+        for _ in 0..self.config.overlap {
+            start = prev(start)?;
+        }
+        Some(start)
+    }
+
+    /// Token window start, tokenizing only a tail of the buffer
+    ///
+    /// The tail starts at roughly eight bytes per needed token and doubles
+    /// until it holds the window. Its first token may be cut, so it is not
+    /// counted unless the tail is the whole buffer.
+    fn token_window_start(
+        &self,
+        size: usize,
+        newest: usize,
+        tokenizer: &TokenOffsets,
+    ) -> Option<usize> {
+        let needed = size.saturating_add(self.config.overlap);
+        let mut tail = needed.saturating_mul(8).max(64);
+        loop {
+            let from = self.floor_char_boundary(self.text.len().saturating_sub(tail));
+            let starts: Vec<usize> = match tokenizer(&self.text[from..]) {
+                Ok(offsets) => offsets
+                    .into_iter()
+                    .skip(usize::from(from > 0))
+                    .map(|(start, _)| from + start)
+                    .collect(),
+                Err(e) => {
+                    warn!("Tokenizer failed, windowing by words: {}", e);
+                    return self.window_start(size, newest, |pos| self.word_start_before(pos));
+                }
+            };
+            let start = self.window_start(size, newest, |pos| {
+                let before = starts.partition_point(|&start| start < pos);
+                before.checked_sub(1).map(|idx| starts[idx])
+            });
+            if start.is_some() || from == 0 {
+                return start;
+            }
+            tail = tail.saturating_mul(2);
+        }
+    }
+
+    /// Buffer offset where the newest chunk starts
+    fn newest_chunk_start(&self) -> Option<usize> {
+        match self.chunk_ends.len() {
+            0 => None,
+            1 => Some(0),
+            len => Some(self.chunk_ends[len - 2] - self.offset),
+        }
+    }
 
-        chunks_vec
+    /// Last chunk start before `pos`
+    fn chunk_start_before(&self, pos: usize) -> Option<usize> {
+        if pos == 0 {
+            return None;
+        }
+        self.chunk_ends
             .iter()
-            .map(|s| s.as_str())
-            .collect::<Vec<_>>()
-            .join(&self.config.chunk_delimiter)
+            .rev()
+            .map(|end| end - self.offset)
+            .find(|&start| start < pos)
+            .or(Some(0))
+    }
+
+    /// Last whitespace-separated word start before `pos`
+    fn word_start_before(&self, pos: usize) -> Option<usize> {
+        let mut chars = self.text[..pos].char_indices().rev().peekable();
+        while let Some((idx, c)) = chars.next() {
+            let after_space = !matches!(chars.peek(), Some((_, prev)) if !prev.is_whitespace());
+            if !c.is_whitespace() && after_space {
+                return Some(idx);
+            }
+        }
+        None
+    }
+
+    /// Last sentence start before `pos`, splitting after `.`, `!`, `?` or
+    /// newlines like long-text windowing does
+    fn sentence_start_before(&self, pos: usize) -> Option<usize> {
+        if pos == 0 {
+            return None;
+        }
+        for (idx, c) in self.text[..pos].char_indices().rev() {
+            let end = idx + c.len_utf8();
+            if end >= pos {
+                continue;
+            }
+            let boundary = match c {
+                '\n' => true,
+                '.' | '!' | '?' => {
+                    !matches!(self.text[end..].chars().next(), Some(next) if !next.is_whitespace())
+                }
+                _ => false,
+            };
+            if boundary {
+                return Some(end);
+            }
+        }
+        Some(0)
+    }
+
+    /// Largest char boundary at or before `pos`
+    fn floor_char_boundary(&self, mut pos: usize) -> usize {
+        while !self.text.is_char_boundary(pos) {
+            pos -= 1;
+        }
+        pos
+    }
+
+    /// Byte offset in the stream where the context window starts
+    pub fn context_start(&self) -> usize {
+        self.offset + self.context_range().start
+    }
+
+    /// Total bytes streamed since the buffer was created or cleared
+    pub fn stream_len(&self) -> usize {
+        self.offset + self.text.len()
+    }
+
+    /// Map a span in stream offsets onto the context text
+    ///
+    /// The part of the span before the window is cut off; `None` if nothing
+    /// of it is in the window.
+    pub fn context_span(&self, span: &Span) -> Option<Span> {
+        let start = self.context_start();
+        let end = span.end.min(self.stream_len());
+        (end > start && span.start < end).then(|| {
+            Span::new(
+                span.start.saturating_sub(start),
+                end - start,
+                span.label.as_str(),
+                span.score,
+            )
+        })
     }
 
     /// Get just the current (most recent) chunk
     pub fn current_chunk(&self) -> Option<&str> {
-        self.chunks.back().map(|s| s.as_str())
+        let start = self.newest_chunk_start()?;
+        Some(&self.text[start..])
     }
 
     /// Get the number of chunks in buffer
     pub fn len(&self) -> usize {
-        self.chunks.len()
+        self.chunk_ends.len()
     }
 
     /// Check if buffer is empty
     pub fn is_empty(&self) -> bool {
-        self.chunks.is_empty()
+        self.chunk_ends.is_empty()
     }
 
     /// Clear all chunks
     pub fn clear(&mut self) {
-        self.chunks.clear();
+        self.text.clear();
+        self.chunk_ends.clear();
+        self.offset = 0;
     }
 
     /// Get configuration
     pub fn config(&self) -> &StreamingConfig {
         &self.config
     }
+
+    /// Scanner result for the context window, with spans in context offsets
+    fn scanner_result(&self, scanner: &dyn IncrementalScanner) -> ClassificationResult {
+        let mut result = scanner.result_since(self.context_start());
+        result.metadata.spans = result
+            .metadata
+            .spans
+            .iter()
+            .filter_map(|span| self.context_span(span))
+            .collect();
+        result
+    }
 }
 
 /// Streaming classifier executor with context awareness
@@ -153,12 +511,16 @@ pub struct StreamingClassifier {
 
     /// Streaming buffer
     buffer: StreamingBuffer,
+
+    /// Resumable scan of the stream, if the classifier supports it
+    scanner: Option<Box<dyn IncrementalScanner>>,
 }
 
 impl StreamingClassifier {
     /// Create a new streaming classifier
     pub fn new(classifier: Arc<dyn Classifier>, config: StreamingConfig) -> Self {
         Self {
+            scanner: classifier.incremental(),
             classifier,
             buffer: StreamingBuffer::new(config),
         }
@@ -167,8 +529,15 @@ impl StreamingClassifier {
     /// Classify a new chunk with context
     ///
     /// This adds the chunk to the buffer and classifies using the configured
-    /// context window (last N chunks or entire buffer).
+    /// context window (last N chunks or entire buffer). Incremental scanners
+    /// only scan the new chunk.
     pub async fn classify_chunk(&mut self, chunk: String) -> Result<ClassificationResult> {
+        if let Some(scanner) = &mut self.scanner {
+            scanner.feed(&chunk);
+            self.buffer.push(chunk)?;
+            return Ok(self.buffer.scanner_result(scanner.as_ref()));
+        }
+
         // Add chunk to buffer
         self.buffer.push(chunk)?;
 
@@ -187,37 +556,145 @@ impl StreamingClassifier {
     /// Clear the buffer (e.g., start of new conversation)
     pub fn reset(&mut self) {
         self.buffer.clear();
+        self.scanner = self.classifier.incremental();
     }
 }
 
 /// Streaming pipeline executor with context awareness
+///
+/// Classifiers with incremental scanning are fed every chunk; the others
+/// follow the configured [`EvaluationSchedule`], reusing their latest result
+/// (or leaving it out until they first run) when they are not due.
 pub struct StreamingPipeline {
     /// The pipeline to execute
     pipeline: ClassifierPipeline,
 
     /// Streaming buffer
     buffer: StreamingBuffer,
+
+    /// Incremental scanners, by the name results report
+    scanners: Vec<(String, Box<dyn IncrementalScanner>)>,
+
+    /// Latest result of each scheduled classifier
+    latest: HashMap<String, ClassificationResult>,
+
+    /// Tokens streamed since each scheduled classifier last ran
+    tokens_since: HashMap<String, usize>,
 }
 
 impl StreamingPipeline {
     /// Create a new streaming pipeline
     pub fn new(pipeline: ClassifierPipeline, config: StreamingConfig) -> Self {
         Self {
+            scanners: scanners_for(&pipeline),
             pipeline,
             buffer: StreamingBuffer::new(config),
+            latest: HashMap::new(),
+            tokens_since: HashMap::new(),
         }
     }
 
     /// Execute pipeline on new chunk with context
     pub async fn execute_chunk(&mut self, chunk: String) -> Result<PipelineExecutionResult> {
+        self.execute_chunk_with_attributes(chunk, &HashMap::new())
+            .await
+    }
+
+    /// Execute pipeline on new chunk, with attributes for stage conditions
+    pub async fn execute_chunk_with_attributes(
+        &mut self,
+        chunk: String,
+        attributes: &HashMap<String, String>,
+    ) -> Result<PipelineExecutionResult> {
+        for (_, scanner) in &mut self.scanners {
+            scanner.feed(&chunk);
+        }
+        let reused = self.plan(Some(&chunk));
+
         // Add chunk to buffer
         self.buffer.push(chunk)?;
 
+        self.execute_planned(reused, attributes).await
+    }
+
+    /// Run every classifier once more at the end of the stream
+    ///
+    /// Picks up matches scanners held back at the end of the text and
+    /// classifiers whose schedule was not due after the last chunk.
+    pub async fn finish(&mut self) -> Result<PipelineExecutionResult> {
+        self.finish_with_attributes(&HashMap::new()).await
+    }
+
+    /// Run every classifier at the end of the stream, with attributes for
+    /// stage conditions
+    pub async fn finish_with_attributes(
+        &mut self,
+        attributes: &HashMap<String, String>,
+    ) -> Result<PipelineExecutionResult> {
+        for (_, scanner) in &mut self.scanners {
+            scanner.finish();
+        }
+        let reused = self.plan(None);
+        self.execute_planned(reused, attributes).await
+    }
+
+    /// Decide which classifiers reuse a result instead of running
+    ///
+    /// `chunk` is `None` at the end of the stream, when everything is due.
+    fn plan(&mut self, chunk: Option<&str>) -> ReusedResults {
+        let tokens = chunk.map_or(0, |chunk| chunk.split_whitespace().count());
+        let mut reused = ReusedResults::new();
+        let mut planned = HashSet::new();
+
+        for (name, _) in self.pipeline.classifiers() {
+            if !planned.insert(name) || self.scanners.iter().any(|(scanner, _)| scanner == name) {
+                continue;
+            }
+            let tokens_since = self.tokens_since.entry(name.to_string()).or_default();
+            *tokens_since += tokens;
+
+            let due = match chunk {
+                Some(chunk) => self
+                    .buffer
+                    .config()
+                    .schedule_for(name)
+                    .is_due(chunk, *tokens_since),
+                None => true,
+            };
+            if due {
+                continue;
+            }
+            match self.latest.get(name) {
+                Some(result) => reused.reuse(name, result.clone()),
+                None => reused.defer(name),
+            }
+        }
+        reused
+    }
+
+    /// Execute on the context window with scanner results filled in
+    async fn execute_planned(
+        &mut self,
+        mut reused: ReusedResults,
+        attributes: &HashMap<String, String>,
+    ) -> Result<PipelineExecutionResult> {
+        for (name, scanner) in &self.scanners {
+            reused.reuse(name.as_str(), self.buffer.scanner_result(scanner.as_ref()));
+        }
+
         // Get text with context
         let text = self.buffer.get_context_text();
 
-        // Execute pipeline
-        self.pipeline.execute(&text).await
+        let result = self
+            .pipeline
+            .execute_reusing(&text, attributes, &reused)
+            .await?;
+
+        for (name, fresh) in reused.into_fresh() {
+            self.tokens_since.insert(name.clone(), 0);
+            self.latest.insert(name, fresh);
+        }
+        Ok(result)
     }
 
     /// Get the current buffer
@@ -228,12 +705,134 @@ impl StreamingPipeline {
     /// Clear the buffer (e.g., start of new conversation)
     pub fn reset(&mut self) {
         self.buffer.clear();
+        self.scanners = scanners_for(&self.pipeline);
+        self.latest.clear();
+        self.tokens_since.clear();
     }
 }
 
+/// Incremental scanners for the pipeline's classifiers that support them
+///
+/// Classifiers the pipeline hands normalized text get scanners that normalize
+/// each chunk; those whose scanner can't keep classifying the normalized
+/// context window.
+fn scanners_for(pipeline: &ClassifierPipeline) -> Vec<(String, Box<dyn IncrementalScanner>)> {
+    let mut scanners: Vec<(String, Box<dyn IncrementalScanner>)> = Vec::new();
+    for (name, classifier) in pipeline.classifiers() {
+        if scanners.iter().any(|(scanner, _)| scanner == name) {
+            continue;
+        }
+        let Some(mut scanner) = classifier.incremental() else {
+            continue;
+        };
+        if let Some(normalizer) = pipeline.normalizer_for(classifier.as_ref()) {
+            if !scanner.normalize_with(normalizer.clone()) {
+                continue;
+            }
+        }
+        scanners.push((name.to_string(), scanner));
+    }
+    scanners
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::classifier::ClassifierTier;
+    use crate::incremental::{SpanDetector, SpanScanner};
+    use crate::normalize::TextNormalizer;
+    use crate::patterns::PatternClassifier;
+    use crate::pipeline::PipelineBuilder;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Model-like classifier that counts how often it runs
+    struct CountingClassifier {
+        name: String,
+        calls: AtomicUsize,
+    }
+
+    impl CountingClassifier {
+        fn create(name: &str) -> Arc<Self> {
+            Arc::new(Self {
+                name: name.to_string(),
+                calls: AtomicUsize::new(0),
+            })
+        }
+
+        fn calls(&self) -> usize {
+            self.calls.load(Ordering::SeqCst)
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl Classifier for CountingClassifier {
+        async fn classify(&self, _text: &str) -> Result<ClassificationResult> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Ok(ClassificationResult::new("negative", 0.1))
+        }
+
+        fn name(&self) -> &str {
+            &self.name
+        }
+
+        fn tier(&self) -> ClassifierTier {
+            ClassifierTier::B
+        }
+    }
+
+    /// Pattern classifier that records every text it classifies or scans
+    #[derive(Clone)]
+    struct RecordingPatterns {
+        inner: PatternClassifier,
+        classified: Arc<AtomicUsize>,
+        scanned: Arc<std::sync::Mutex<Vec<String>>>,
+    }
+
+    impl SpanDetector for RecordingPatterns {
+        fn detect(&self, text: &str) -> Vec<Span> {
+            self.scanned.lock().unwrap().push(text.to_string());
+            self.inner.detect(text)
+        }
+
+        fn summarize(&self, spans: Vec<Span>) -> ClassificationResult {
+            self.inner.summarize(spans)
+        }
+
+        fn lookback(&self) -> usize {
+            self.inner.lookback()
+        }
+
+        fn matches_can_grow(&self) -> bool {
+            self.inner.matches_can_grow()
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl Classifier for RecordingPatterns {
+        async fn classify(&self, text: &str) -> Result<ClassificationResult> {
+            self.classified.fetch_add(1, Ordering::SeqCst);
+            self.inner.classify(text).await
+        }
+
+        fn name(&self) -> &str {
+            self.inner.name()
+        }
+
+        fn tier(&self) -> ClassifierTier {
+            ClassifierTier::A
+        }
+
+        fn incremental(&self) -> Option<Box<dyn IncrementalScanner>> {
+            Some(Box::new(SpanScanner::new(self.clone())))
+        }
+    }
+
+    fn spam_patterns() -> Arc<dyn Classifier> {
+        Arc::new(
+            PatternClassifier::new("spam", vec![("spam".to_string(), "click here".to_string())])
+                .unwrap(),
+        )
+    }
 
     #[test]
     fn test_streaming_buffer_window() {
@@ -249,7 +848,7 @@ mod tests {
 
         // Should see last 3 chunks
         let context = buffer.get_context_text();
-        assert_eq!(context, "chunk3 chunk4 chunk5");
+        assert_eq!(context, "chunk3chunk4chunk5");
     }
 
     #[test]
@@ -263,7 +862,7 @@ mod tests {
 
         // Should see entire buffer
         let context = buffer.get_context_text();
-        assert_eq!(context, "chunk1 chunk2 chunk3");
+        assert_eq!(context, "chunk1chunk2chunk3");
     }
 
     #[test]
@@ -283,9 +882,9 @@ mod tests {
     #[test]
     fn test_streaming_buffer_max_size() {
         let config = StreamingConfig {
-            context_chunks: 0,
+            window: ContextWindow::All,
             max_buffer_size: 3,
-            chunk_delimiter: " ".to_string(),
+            ..StreamingConfig::default()
         };
         let mut buffer = StreamingBuffer::new(config);
 
@@ -297,7 +896,7 @@ mod tests {
         // Should only have last 3
         assert_eq!(buffer.len(), 3);
         let context = buffer.get_context_text();
-        assert_eq!(context, "chunk3 chunk4 chunk5");
+        assert_eq!(context, "chunk3chunk4chunk5");
     }
 
     #[test]
@@ -309,4 +908,282 @@ mod tests {
 
         assert_eq!(buffer.current_chunk(), Some("chunk2"));
     }
+
+    #[tokio::test]
+    async fn test_scanner_finds_match_across_chunk_window() {
+        // The window only holds the current chunk, yet the split match is found
+        let pipeline = PipelineBuilder::new()
+            .single("spam", spam_patterns())
+            .build();
+        let mut streaming = StreamingPipeline::new(pipeline, StreamingConfig::no_context());
+
+        let first = streaming
+            .execute_chunk("please cli".to_string())
+            .await
+            .unwrap();
+        assert_eq!(first.final_decision.unwrap().label, "clean");
+
+        let second = streaming
+            .execute_chunk("ck here".to_string())
+            .await
+            .unwrap();
+        let decision = second.final_decision.unwrap();
+        assert_eq!(decision.label, "spam");
+        // Cut off at the window start, in context text offsets
+        assert_eq!(decision.metadata.spans[0].start, 0);
+        assert_eq!(decision.metadata.spans[0].end, 7);
+    }
+
+    #[tokio::test]
+    async fn test_schedule_defers_model_classifiers() {
+        let model = CountingClassifier::create("model");
+        let pipeline = PipelineBuilder::new()
+            .parallel(
+                "checks",
+                vec![
+                    ("spam".to_string(), spam_patterns()),
+                    ("model".to_string(), model.clone() as Arc<dyn Classifier>),
+                ],
+                crate::AggregationStrategy::All,
+            )
+            .build();
+        let config =
+            StreamingConfig::entire_buffer().with_schedule(EvaluationSchedule::SentenceBoundary);
+        let mut streaming = StreamingPipeline::new(pipeline, config);
+
+        // Not due and never run: left out
+        let result = streaming.execute_chunk("Hello".to_string()).await.unwrap();
+        assert_eq!(model.calls(), 0);
+        assert_eq!(result.results.len(), 1);
+
+        streaming.execute_chunk("there.".to_string()).await.unwrap();
+        assert_eq!(model.calls(), 1);
+
+        // Not due: the latest result is reused
+        let result = streaming.execute_chunk("More".to_string()).await.unwrap();
+        assert_eq!(model.calls(), 1);
+        assert_eq!(result.results.len(), 2);
+
+        streaming.finish().await.unwrap();
+        assert_eq!(model.calls(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_schedule_every_tokens() {
+        let model = CountingClassifier::create("model");
+        let pipeline = PipelineBuilder::new()
+            .single("model", model.clone() as Arc<dyn Classifier>)
+            .build();
+        let mut config = StreamingConfig::entire_buffer();
+        config
+            .classifier_schedules
+            .insert("model".to_string(), EvaluationSchedule::EveryTokens(4));
+        let mut streaming = StreamingPipeline::new(pipeline, config);
+
+        for chunk in ["one two", "three", "four five", "six"] {
+            streaming.execute_chunk(chunk.to_string()).await.unwrap();
+        }
+        // Due after "four five" (5 tokens), then 1 token since
+        assert_eq!(model.calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_normalized_scanner_sees_each_chunk_once() {
+        let patterns = RecordingPatterns {
+            inner: PatternClassifier::new(
+                "spam",
+                vec![("spam".to_string(), "click here".to_string())],
+            )
+            .unwrap(),
+            classified: Arc::new(AtomicUsize::new(0)),
+            scanned: Arc::default(),
+        };
+        let pipeline = PipelineBuilder::new()
+            .single("spam", Arc::new(patterns.clone()))
+            .normalizer(Arc::new(TextNormalizer::default()))
+            .build();
+        let mut streaming = StreamingPipeline::new(pipeline, StreamingConfig::entire_buffer());
+
+        let mut chunks = vec!["nothing to see. "; 40];
+        chunks.extend(["please cl", "ick h\u{200B}", "ere now"]);
+        let mut decision = None;
+        for chunk in &chunks {
+            let result = streaming.execute_chunk(chunk.to_string()).await.unwrap();
+            decision = result.final_decision;
+        }
+        streaming.finish().await.unwrap();
+
+        // The obfuscated match is found, in raw stream offsets
+        let decision = decision.unwrap();
+        assert_eq!(decision.label, "spam");
+        let span = &decision.metadata.spans[0];
+        let text = chunks.concat();
+        assert_eq!(&text[span.start..span.end], "click h\u{200B}ere");
+
+        // One scan per chunk plus the final one, each of the chunk and a
+        // bounded lookback, and never a full classification
+        assert_eq!(patterns.classified.load(Ordering::SeqCst), 0);
+        let scanned = patterns.scanned.lock().unwrap();
+        assert_eq!(scanned.len(), chunks.len() + 1);
+        let lookback = patterns.lookback();
+        for (haystack, chunk) in scanned.iter().zip(&chunks) {
+            assert!(haystack.len() <= lookback + chunk.len());
+        }
+    }
+
+    #[test]
+    fn test_context_span_mapping() {
+        let mut buffer = StreamingBuffer::new(StreamingConfig::with_window(2));
+        for chunk in ["aaaa", "bbbb", "cccc"] {
+            buffer.push(chunk.to_string()).unwrap();
+        }
+        assert_eq!(buffer.context_start(), 4);
+        assert_eq!(buffer.stream_len(), 12);
+
+        // "bbcc" in "bbbbcccc"
+        let span = buffer.context_span(&Span::new(6, 10, "x", 1.0)).unwrap();
+        assert_eq!((span.start, span.end), (2, 6));
+        assert!(buffer.context_span(&Span::new(0, 3, "x", 1.0)).is_none());
+    }
+
+    #[test]
+    fn test_schedule_from_yaml() {
+        let schedules: HashMap<String, EvaluationSchedule> = serde_yaml::from_str(
+            "toxicity: sentence_boundary\nsentiment:\n  every_tokens: 20\npii: every_chunk\n",
+        )
+        .unwrap();
+        assert_eq!(schedules["toxicity"], EvaluationSchedule::SentenceBoundary);
+        assert_eq!(schedules["sentiment"], EvaluationSchedule::EveryTokens(20));
+        assert_eq!(schedules["pii"], EvaluationSchedule::EveryChunk);
+    }
+
+    fn context(config: StreamingConfig, chunks: &[&str]) -> String {
+        let mut buffer = StreamingBuffer::new(config);
+        for chunk in chunks {
+            buffer.push(chunk.to_string()).unwrap();
+        }
+        buffer.get_context_text()
+    }
+
+    #[test]
+    fn test_concatenation_preserves_text() {
+        let text = context(StreamingConfig::entire_buffer(), &["Hel", "lo, wor", "ld"]);
+        assert_eq!(text, "Hello, world");
+    }
+
+    #[test]
+    fn test_char_window_with_overlap() {
+        let chunks = ["abcdef", "ghij"];
+        let window = ContextWindow::Chars(6);
+        assert_eq!(
+            context(StreamingConfig::with_context(window, 0), &chunks),
+            "efghij"
+        );
+        assert_eq!(
+            context(StreamingConfig::with_context(window, 2), &chunks),
+            "cdefghij"
+        );
+
+        // The newest chunk is never cut
+        let window = ContextWindow::Chars(2);
+        assert_eq!(
+            context(StreamingConfig::with_context(window, 0), &chunks),
+            "ghij"
+        );
+    }
+
+    #[test]
+    fn test_token_window() {
+        let chunks = ["the quick br", "own fox jumps"];
+        let window = ContextWindow::Tokens(3);
+        // "brown" started before the newest chunk, so the window reaches back to it
+        assert_eq!(
+            context(StreamingConfig::with_context(window, 0), &chunks),
+            "brown fox jumps"
+        );
+        assert_eq!(
+            context(StreamingConfig::with_context(window, 1), &chunks),
+            "quick brown fox jumps"
+        );
+
+        // A tokenizer that splits every two bytes
+        let tokenizer: TokenOffsets = Arc::new(|text: &str| {
+            Ok((0..text.len())
+                .step_by(2)
+                .map(|i| (i, (i + 2).min(text.len())))
+                .collect())
+        });
+        let config =
+            StreamingConfig::with_context(ContextWindow::Tokens(2), 0).with_tokenizer(tokenizer);
+        assert_eq!(context(config, &["abcdef", "gh"]), "efgh");
+    }
+
+    #[test]
+    fn test_token_window_tokenizes_tail() {
+        let longest = Arc::new(AtomicUsize::new(0));
+        let seen = longest.clone();
+        let tokenizer: TokenOffsets = Arc::new(move |text: &str| {
+            seen.fetch_max(text.len(), Ordering::SeqCst);
+            let mut offsets = Vec::new();
+            let mut start = None;
+            for (idx, c) in text.char_indices() {
+                match (c.is_whitespace(), start) {
+                    (false, None) => start = Some(idx),
+                    (true, Some(begin)) => {
+                        offsets.push((begin, idx));
+                        start = None;
+                    }
+                    _ => {}
+                }
+            }
+            offsets.extend(start.map(|begin| (begin, text.len())));
+            Ok(offsets)
+        });
+        let config =
+            StreamingConfig::with_context(ContextWindow::Tokens(3), 1).with_tokenizer(tokenizer);
+        let mut buffer = StreamingBuffer::new(config);
+        for _ in 0..200 {
+            buffer
+                .push("lorem ipsum dolor sit amet ".to_string())
+                .unwrap();
+        }
+        buffer.push("one two three".to_string()).unwrap();
+
+        assert_eq!(buffer.get_context_text(), "amet one two three");
+        assert!(longest.load(Ordering::SeqCst) < 200);
+    }
+
+    #[test]
+    fn test_sentence_window() {
+        let chunks = ["One. Two", " two. Three", " three"];
+        let window = ContextWindow::Sentences(2);
+        assert_eq!(
+            context(StreamingConfig::with_context(window, 0), &chunks),
+            " Two two. Three three"
+        );
+        assert_eq!(
+            context(StreamingConfig::with_context(window, 1), &chunks),
+            "One. Two two. Three three"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_overlap_keeps_edge_match() {
+        let patterns: Arc<dyn Classifier> = Arc::new(
+            PatternClassifier::new("spam", vec![("spam".to_string(), "click here".to_string())])
+                .unwrap(),
+        );
+        let mut streaming = StreamingClassifier::new(
+            patterns,
+            StreamingConfig::with_context(ContextWindow::Tokens(1), 1),
+        );
+
+        streaming
+            .classify_chunk("please click".to_string())
+            .await
+            .unwrap();
+        let result = streaming.classify_chunk(" here".to_string()).await.unwrap();
+        assert_eq!(result.label, "spam");
+        assert_eq!(streaming.buffer().get_context_text(), "click here");
+    }
 }
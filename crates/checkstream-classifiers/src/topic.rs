@@ -0,0 +1,343 @@
+//! Topic allow-list classifier (Tier C)
+//!
+//! Keeps a deployment on topic. Each topic is described by keywords, example
+//! texts, or both, and is either allowed (what the deployment is for) or
+//! denied (what it must never discuss). A topic scores the better of:
+//!
+//! - keywords: `1 - 0.5^n` for `n` distinct keywords found as whole words, so
+//!   one keyword scores 0.5, two 0.75, three 0.875
+//! - exemplars: cosine similarity to the closest example text, using the
+//!   classifier's [`Embedder`]
+//!
+//! The result's `score` is the off-topic score: the best denied topic's score,
+//! or one minus the best allowed topic's score if that is higher. Its `label`
+//! is the closest topic (`none` if no topic matched at all), and every topic's
+//! score is reported in `all_scores`.
+
+use crate::classifier::{ClassificationMetadata, ClassificationResult, Classifier, ClassifierTier};
+use crate::incremental::SpanDetector;
+use crate::patterns::{PatternClassifier, PatternOptions, PatternRule};
+use crate::similarity::{Embedder, VectorIndex};
+use checkstream_core::Result;
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Instant;
+
+/// Label when no topic matched
+pub const NO_TOPIC: &str = "none";
+
+/// A topic a [`TopicClassifier`] recognizes
+#[derive(Debug, Clone)]
+pub struct Topic {
+    /// Topic name, reported as the label
+    pub name: String,
+
+    /// Whether the deployment may discuss it
+    pub allowed: bool,
+
+    /// Words and phrases that indicate the topic
+    pub keywords: Vec<String>,
+
+    /// Example texts on the topic
+    pub exemplars: Vec<String>,
+}
+
+impl Topic {
+    /// Topic the deployment may discuss
+    pub fn allowed(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            allowed: true,
+            keywords: Vec::new(),
+            exemplars: Vec::new(),
+        }
+    }
+
+    /// Topic the deployment must not discuss
+    pub fn denied(name: impl Into<String>) -> Self {
+        Self {
+            allowed: false,
+            ..Self::allowed(name)
+        }
+    }
+
+    /// Add keywords
+    pub fn with_keywords<S: Into<String>>(mut self, keywords: impl IntoIterator<Item = S>) -> Self {
+        self.keywords.extend(keywords.into_iter().map(Into::into));
+        self
+    }
+
+    /// Add example texts
+    pub fn with_exemplars<S: Into<String>>(
+        mut self,
+        exemplars: impl IntoIterator<Item = S>,
+    ) -> Self {
+        self.exemplars.extend(exemplars.into_iter().map(Into::into));
+        self
+    }
+}
+
+/// Classifier scoring how far text strays from its allowed topics
+pub struct TopicClassifier {
+    name: String,
+    topics: Vec<Topic>,
+
+    /// Keywords labelled with the index of their topic
+    keywords: Option<PatternClassifier>,
+
+    embedder: Arc<dyn Embedder>,
+    index: VectorIndex,
+
+    /// Topic of each indexed exemplar, by index position
+    exemplar_topics: Vec<usize>,
+}
+
+impl TopicClassifier {
+    /// Build the classifier, embedding every topic's exemplars
+    pub fn new(
+        name: impl Into<String>,
+        topics: Vec<Topic>,
+        embedder: Arc<dyn Embedder>,
+    ) -> Result<Self> {
+        let name = name.into();
+        let invalid = |message: String| {
+            checkstream_core::Error::config(format!("Topic classifier '{}': {}", name, message))
+        };
+
+        if topics.is_empty() {
+            return Err(invalid("no topics".to_string()));
+        }
+        let mut names = HashSet::new();
+        for topic in &topics {
+            if !names.insert(topic.name.as_str()) {
+                return Err(invalid(format!("topic '{}' is defined twice", topic.name)));
+            }
+            if topic.keywords.is_empty() && topic.exemplars.is_empty() {
+                return Err(invalid(format!(
+                    "topic '{}' has no keywords or exemplars",
+                    topic.name
+                )));
+            }
+        }
+
+        let rules: Vec<PatternRule> = topics
+            .iter()
+            .enumerate()
+            .flat_map(|(i, topic)| {
+                topic
+                    .keywords
+                    .iter()
+                    .map(move |keyword| PatternRule::literal(i.to_string(), keyword.clone(), 1.0))
+            })
+            .collect();
+        let keywords = if rules.is_empty() {
+            None
+        } else {
+            let options = PatternOptions {
+                case_sensitive: false,
+                word_boundary: true,
+            };
+            Some(PatternClassifier::with_rules(name.clone(), rules, options)?)
+        };
+
+        let mut index = VectorIndex::new();
+        let mut exemplar_topics = Vec::new();
+        for (i, topic) in topics.iter().enumerate() {
+            for exemplar in &topic.exemplars {
+                index.insert(embedder.embed(exemplar)?)?;
+                exemplar_topics.push(i);
+            }
+        }
+
+        Ok(Self {
+            name,
+            topics,
+            keywords,
+            embedder,
+            index,
+            exemplar_topics,
+        })
+    }
+
+    /// Score of each topic, in topic order
+    fn topic_scores(&self, text: &str) -> Result<Vec<f32>> {
+        let mut scores = vec![0.0_f32; self.topics.len()];
+
+        if let Some(keywords) = &self.keywords {
+            let mut found: Vec<HashSet<String>> = vec![HashSet::new(); self.topics.len()];
+            for span in keywords.detect(text) {
+                if let Ok(topic) = span.label.parse::<usize>() {
+                    found[topic].insert(text[span.start..span.end].to_lowercase());
+                }
+            }
+            for (score, keywords) in scores.iter_mut().zip(&found) {
+                *score = 1.0 - 0.5_f32.powi(keywords.len() as i32);
+            }
+        }
+
+        if !self.index.is_empty() {
+            let similarities = self.index.similarities(&self.embedder.embed(text)?)?;
+            for (topic, similarity) in self.exemplar_topics.iter().zip(similarities) {
+                scores[*topic] = scores[*topic].max(similarity.clamp(0.0, 1.0));
+            }
+        }
+
+        Ok(scores)
+    }
+}
+
+#[async_trait::async_trait]
+impl Classifier for TopicClassifier {
+    async fn classify(&self, text: &str) -> Result<ClassificationResult> {
+        let start = Instant::now();
+
+        let scores = self.topic_scores(text)?;
+
+        let best = |allowed: bool| {
+            self.topics
+                .iter()
+                .zip(&scores)
+                .filter(|(topic, _)| topic.allowed == allowed)
+                .map(|(_, score)| *score)
+                .fold(None, |best: Option<f32>, score| {
+                    Some(best.map_or(score, |best| best.max(score)))
+                })
+        };
+        let denied = best(false).unwrap_or(0.0);
+        let off_topic = match best(true) {
+            Some(allowed) => denied.max(1.0 - allowed),
+            None => denied,
+        };
+
+        // First of the highest-scoring topics
+        let closest = self
+            .topics
+            .iter()
+            .zip(&scores)
+            .filter(|(_, score)| **score > 0.0)
+            .fold(
+                None,
+                |best: Option<(&Topic, f32)>, (topic, score)| match best {
+                    Some((_, best_score)) if best_score >= *score => best,
+                    _ => Some((topic, *score)),
+                },
+            );
+        let (label, allowed) = match closest {
+            Some((topic, _)) => (topic.name.clone(), topic.allowed.to_string()),
+            None => (NO_TOPIC.to_string(), "false".to_string()),
+        };
+
+        Ok(ClassificationResult {
+            label,
+            score: off_topic,
+            metadata: ClassificationMetadata {
+                model: Some(self.name.clone()),
+                all_scores: Some(
+                    self.topics
+                        .iter()
+                        .zip(scores)
+                        .map(|(topic, score)| (topic.name.clone(), score))
+                        .collect(),
+                ),
+                extra: vec![("allowed".to_string(), allowed)],
+                ..Default::default()
+            },
+            latency_us: start.elapsed().as_micros() as u64,
+        })
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn tier(&self) -> ClassifierTier {
+        ClassifierTier::C
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::similarity::HashingEmbedder;
+
+    fn banking() -> TopicClassifier {
+        let topics = vec![
+            Topic::allowed("accounts")
+                .with_keywords(["account", "balance", "statement", "overdraft"])
+                .with_exemplars(["How do I check the balance of my current account?"]),
+            Topic::allowed("cards").with_keywords(["card", "pin", "contactless"]),
+            Topic::denied("investment_advice").with_keywords(["stocks", "crypto", "bitcoin"]),
+        ];
+        TopicClassifier::new("banking", topics, Arc::new(HashingEmbedder::default())).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_on_and_off_topic() {
+        let classifier = banking();
+
+        let result = classifier
+            .classify("Why is my account balance lower than my statement?")
+            .await
+            .unwrap();
+        assert_eq!(result.label, "accounts");
+        assert!(result.score <= 0.125, "{}", result.score);
+
+        let result = classifier
+            .classify("What's a good recipe for lasagne?")
+            .await
+            .unwrap();
+        assert!(result.score > 0.8, "{}", result.score);
+
+        // A denied topic is off-topic even alongside an allowed one
+        let result = classifier
+            .classify("Should I buy bitcoin with my card?")
+            .await
+            .unwrap();
+        assert_eq!(result.score, 0.5);
+        let scores = result.metadata.all_scores.unwrap();
+        assert!(scores.contains(&("investment_advice".to_string(), 0.5)));
+        assert!(scores.contains(&("cards".to_string(), 0.5)));
+    }
+
+    #[tokio::test]
+    async fn test_keywords_match_whole_words_once() {
+        let classifier = banking();
+
+        let result = classifier.classify("card card CARD").await.unwrap();
+        assert_eq!(result.label, "cards");
+        assert_eq!(result.score, 0.5);
+
+        let result = classifier
+            .classify("cardigans and pineapples")
+            .await
+            .unwrap();
+        let scores = result.metadata.all_scores.unwrap();
+        assert!(scores.contains(&("cards".to_string(), 0.0)));
+
+        let keywords_only = TopicClassifier::new(
+            "keywords",
+            vec![Topic::allowed("cards").with_keywords(["card"])],
+            Arc::new(HashingEmbedder::default()),
+        )
+        .unwrap();
+        let result = keywords_only.classify("hello there").await.unwrap();
+        assert_eq!(result.label, NO_TOPIC);
+        assert_eq!(result.score, 1.0);
+    }
+
+    #[test]
+    fn test_invalid_topics_rejected() {
+        let embedder: Arc<dyn Embedder> = Arc::new(HashingEmbedder::default());
+        assert!(TopicClassifier::new("t", Vec::new(), Arc::clone(&embedder)).is_err());
+        assert!(
+            TopicClassifier::new("t", vec![Topic::allowed("empty")], Arc::clone(&embedder))
+                .is_err()
+        );
+
+        let twice = vec![
+            Topic::allowed("cards").with_keywords(["card"]),
+            Topic::denied("cards").with_keywords(["crypto"]),
+        ];
+        assert!(TopicClassifier::new("t", twice, embedder).is_err());
+    }
+}
@@ -1,41 +1,113 @@
 //! Toxicity detection classifier (Tier B)
 
-use crate::classifier::{ClassificationMetadata, ClassificationResult, Classifier, ClassifierTier};
+use crate::classifier::{
+    ClassificationMetadata, ClassificationResult, Classifier, ClassifierTier, Span,
+};
+use crate::incremental::{IncrementalScanner, SpanDetector, SpanScanner};
+use crate::lexicon::{self, CategoryPhrases, LexiconPack};
+use aho_corasick::AhoCorasick;
 use checkstream_core::Result;
+use std::collections::HashMap;
 use std::time::Instant;
 
 /// Toxicity detection classifier.
 ///
 /// This implementation is intentionally dependency-light for production
 /// hardening and deterministic operation.
+#[derive(Clone)]
 pub struct ToxicityClassifier {
     name: String,
+
+    /// Matcher for the lowercase toxic words and phrases
+    patterns: AhoCorasick,
 }
 
 impl ToxicityClassifier {
     /// Create a new toxicity classifier.
     pub fn new() -> Result<Self> {
-        Ok(Self {
-            name: "toxicity".to_string(),
-        })
+        Self::with_lexicons(&[])
     }
 
-    fn classify_with_patterns(&self, text: &str) -> f32 {
-        let text_lower = text.to_lowercase();
+    /// Create a toxicity classifier that also matches the phrases of `packs`.
+    pub fn with_lexicons(packs: &[LexiconPack]) -> Result<Self> {
+        let mut phrases = Self::default_phrases();
+        for pack in packs {
+            lexicon::merge_categories(&mut phrases, pack.phrases_for("toxicity"));
+        }
+        Self::with_phrases(phrases)
+    }
 
-        let toxic_patterns = [
+    /// Built-in phrases under the `toxic` label.
+    pub fn default_phrases() -> CategoryPhrases {
+        let toxic = [
             "hate", "stupid", "idiot", "dumb", "kill", "die", "worst", "terrible", "awful",
             "sucks", "garbage", "trash", "shit", "fuck", "damn", "hell", "asshole", "bastard",
             "bitch",
         ];
+        HashMap::from([("toxic".to_string(), toxic.map(String::from).to_vec())])
+    }
 
-        let matches = toxic_patterns
+    /// Create a toxicity classifier matching the phrases listed under `toxic`.
+    pub fn with_phrases(mut phrases: CategoryPhrases) -> Result<Self> {
+        lexicon::check_categories("toxicity", &phrases, &["toxic"])?;
+        let toxic: Vec<String> = phrases
+            .remove("toxic")
+            .unwrap_or_default()
             .iter()
-            .filter(|pattern| text_lower.contains(**pattern))
-            .count() as f32;
+            .map(|phrase| phrase.to_lowercase())
+            .collect();
+        let patterns = AhoCorasick::builder()
+            .ascii_case_insensitive(true)
+            .build(&toxic)
+            .map_err(|e| {
+                checkstream_core::Error::classifier(format!(
+                    "Failed to build toxicity pattern matcher: {}",
+                    e
+                ))
+            })?;
+
+        Ok(Self {
+            name: "toxicity".to_string(),
+            patterns,
+        })
+    }
+}
+
+impl SpanDetector for ToxicityClassifier {
+    /// Every occurrence of every phrase, labelled with the phrase index
+    fn detect(&self, text: &str) -> Vec<Span> {
+        self.patterns
+            .find_overlapping_iter(text)
+            .map(|m| Span::new(m.start(), m.end(), m.pattern().as_usize().to_string(), 0.35))
+            .collect()
+    }
+
+    fn summarize(&self, spans: Vec<Span>) -> ClassificationResult {
+        let mut phrases: Vec<&str> = spans.iter().map(|span| span.label.as_str()).collect();
+        phrases.sort_unstable();
+        phrases.dedup();
 
         // Keep confidence bounded for lexicon-only approach.
-        (matches * 0.35).clamp(0.0, 0.95)
+        let score = (phrases.len() as f32 * 0.35).clamp(0.0, 0.95);
+        let label = if score > 0.5 { "toxic" } else { "safe" };
+
+        ClassificationResult {
+            label: label.to_string(),
+            score,
+            metadata: ClassificationMetadata {
+                model: Some("toxicity-lexicon".to_string()),
+                ..Default::default()
+            },
+            latency_us: 0,
+        }
+    }
+
+    fn lookback(&self) -> usize {
+        self.patterns.max_pattern_len().saturating_sub(1)
+    }
+
+    fn matches_can_grow(&self) -> bool {
+        false
     }
 }
 
@@ -50,18 +122,10 @@ impl Classifier for ToxicityClassifier {
     async fn classify(&self, text: &str) -> Result<ClassificationResult> {
         let start = Instant::now();
 
-        let score = self.classify_with_patterns(text);
-        let label = if score > 0.5 { "toxic" } else { "safe" };
+        let mut result = self.summarize(self.detect(text));
+        result.latency_us = start.elapsed().as_micros() as u64;
 
-        Ok(ClassificationResult {
-            label: label.to_string(),
-            score,
-            metadata: ClassificationMetadata {
-                model: Some("toxicity-lexicon".to_string()),
-                ..Default::default()
-            },
-            latency_us: start.elapsed().as_micros() as u64,
-        })
+        Ok(result)
     }
 
     fn name(&self) -> &str {
@@ -71,6 +135,15 @@ impl Classifier for ToxicityClassifier {
     fn tier(&self) -> ClassifierTier {
         ClassifierTier::B
     }
+
+    fn normalized_input(&self) -> bool {
+        // Lexicon matching is as easy to evade as any Tier A pattern
+        true
+    }
+
+    fn incremental(&self) -> Option<Box<dyn IncrementalScanner>> {
+        Some(Box::new(SpanScanner::new(self.clone())))
+    }
 }
 
 #[cfg(test)]
@@ -54,6 +54,16 @@ pub enum Action {
         value: f32,
     },
 
+    /// Replace detected PII with reversible placeholders (ingress only)
+    ///
+    /// The originals are restored in the backend response before it
+    /// reaches the client.
+    Pseudonymize {
+        /// PII entity types to replace (all detected types if empty)
+        #[serde(default)]
+        entities: Vec<String>,
+    },
+
     /// Mark for audit
     Audit {
         /// Audit category
@@ -150,4 +160,17 @@ mod tests {
             _ => panic!("Wrong action type"),
         }
     }
+
+    #[test]
+    fn test_pseudonymize_action() {
+        let json = r#"{"type": "pseudonymize", "entities": ["email", "iban"]}"#;
+        let action: Action = serde_json::from_str(json).unwrap();
+
+        match action {
+            Action::Pseudonymize { entities } => {
+                assert_eq!(entities, vec!["email", "iban"]);
+            }
+            _ => panic!("Wrong action type"),
+        }
+    }
 }
@@ -15,6 +15,8 @@ pub struct PolicyEngine {
     regex_cache: HashMap<String, Regex>,
     /// Classifier scores from external evaluation (injected before evaluate)
     classifier_scores: HashMap<String, f32>,
+    /// Structured request context (injected before evaluate)
+    context: HashMap<String, String>,
 }
 
 impl PolicyEngine {
@@ -24,6 +26,7 @@ impl PolicyEngine {
             policies: Vec::new(),
             regex_cache: HashMap::new(),
             classifier_scores: HashMap::new(),
+            context: HashMap::new(),
         }
     }
 
@@ -97,6 +100,12 @@ impl PolicyEngine {
         self.classifier_scores.clear();
     }
 
+    /// Set structured request context before evaluation
+    /// Context triggers match these fields before falling back to the text
+    pub fn set_context(&mut self, context: HashMap<String, String>) {
+        self.context = context;
+    }
+
     /// Evaluate text against all policies
     pub fn evaluate_text(&self, text: &str) -> Vec<EvaluationResult> {
         let mut results = Vec::new();
@@ -206,10 +215,14 @@ impl PolicyEngine {
             }
 
             Trigger::Context { field, value } => {
-                // Context triggers check for field=value in the text
-                // This is a simple implementation - in practice would check structured context
-                let pattern = format!("{}:{}", field, value);
-                let triggered = text.to_lowercase().contains(&pattern.to_lowercase());
+                // Prefer structured context; otherwise look for field:value in the text
+                let triggered = match self.context.get(field) {
+                    Some(actual) => actual.eq_ignore_ascii_case(value),
+                    None => {
+                        let pattern = format!("{}:{}", field, value);
+                        text.to_lowercase().contains(&pattern.to_lowercase())
+                    }
+                };
                 Some((
                     triggered,
                     if triggered { 1.0 } else { 0.0 },
@@ -258,6 +271,29 @@ impl PolicyEngine {
     pub fn policies(&self) -> &[Policy] {
         &self.policies
     }
+
+    /// Enable or disable a rule at runtime
+    ///
+    /// When `policy_name` is `None` every rule with a matching name is updated.
+    /// Returns the number of rules that were changed.
+    pub fn set_rule_enabled(
+        &mut self,
+        policy_name: Option<&str>,
+        rule_name: &str,
+        enabled: bool,
+    ) -> usize {
+        let mut updated = 0;
+        for policy in &mut self.policies {
+            if policy_name.is_some_and(|name| name != policy.name) {
+                continue;
+            }
+            for rule in policy.rules.iter_mut().filter(|r| r.name == rule_name) {
+                rule.enabled = enabled;
+                updated += 1;
+            }
+        }
+        updated
+    }
 }
 
 impl Default for PolicyEngine {
@@ -539,6 +575,66 @@ mod tests {
         assert!(results.is_empty());
     }
 
+    #[test]
+    fn test_context_trigger_uses_structured_context() {
+        let mut engine = PolicyEngine::new();
+        engine.add_policy(create_test_policy(vec![Rule {
+            name: "retail-only".to_string(),
+            description: "Match retail customers".to_string(),
+            trigger: Trigger::Context {
+                field: "segment".to_string(),
+                value: "retail".to_string(),
+            },
+            actions: vec![],
+            regulation: None,
+            enabled: true,
+        }]));
+
+        // Falls back to text matching without structured context
+        assert_eq!(engine.evaluate_text("segment:retail").len(), 1);
+
+        let mut context = HashMap::new();
+        context.insert("segment".to_string(), "Retail".to_string());
+        engine.set_context(context);
+        assert_eq!(engine.evaluate_text("unrelated").len(), 1);
+
+        let mut context = HashMap::new();
+        context.insert("segment".to_string(), "professional".to_string());
+        engine.set_context(context);
+        assert!(engine.evaluate_text("segment:retail").is_empty());
+    }
+
+    #[test]
+    fn test_set_rule_enabled() {
+        let mut engine = PolicyEngine::new();
+        engine.add_policy(create_test_policy(vec![Rule {
+            name: "unsafe-content".to_string(),
+            description: "Detect unsafe content".to_string(),
+            trigger: Trigger::Pattern {
+                pattern: "unsafe".to_string(),
+                case_insensitive: true,
+            },
+            actions: vec![],
+            regulation: None,
+            enabled: true,
+        }]));
+
+        assert_eq!(engine.set_rule_enabled(None, "unsafe-content", false), 1);
+        assert!(engine.evaluate_text("This is unsafe content").is_empty());
+
+        assert_eq!(
+            engine.set_rule_enabled(Some("other-policy"), "unsafe-content", true),
+            0
+        );
+        assert_eq!(engine.set_rule_enabled(None, "missing-rule", true), 0);
+
+        assert_eq!(
+            engine.set_rule_enabled(Some("test-policy"), "unsafe-content", true),
+            1
+        );
+        assert_eq!(engine.evaluate_text("This is unsafe content").len(), 1);
+    }
+
     #[test]
     fn test_multiple_rules_trigger() {
         let mut engine = PolicyEngine::new();
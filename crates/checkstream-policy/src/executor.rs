@@ -6,6 +6,7 @@
 //! - Redacting sensitive content
 //! - Injecting warnings or disclaimers
 //! - Adapting generation parameters
+//! - Pseudonymizing PII before it reaches the backend
 //! - Audit trail recording
 
 use crate::action::{Action, AuditSeverity, InjectPosition, LogLevel};
@@ -33,6 +34,9 @@ pub struct ActionOutcome {
 
     /// Parameter adaptations for generation
     pub adaptations: Vec<ParameterAdaptation>,
+
+    /// PII entity types to pseudonymize, if requested (empty means all)
+    pub pseudonymize: Option<Vec<String>>,
 }
 
 impl ActionOutcome {
@@ -47,6 +51,7 @@ impl ActionOutcome {
             || !self.modifications.is_empty()
             || !self.audit_records.is_empty()
             || !self.adaptations.is_empty()
+            || self.pseudonymize.is_some()
     }
 
     /// Merge another outcome into this one
@@ -61,6 +66,20 @@ impl ActionOutcome {
         self.modifications.extend(other.modifications);
         self.audit_records.extend(other.audit_records);
         self.adaptations.extend(other.adaptations);
+
+        // An empty entity list means "everything", so it absorbs any other list
+        self.pseudonymize = match (self.pseudonymize.take(), other.pseudonymize) {
+            (Some(mut ours), Some(theirs)) if !ours.is_empty() && !theirs.is_empty() => {
+                for entity in theirs {
+                    if !ours.contains(&entity) {
+                        ours.push(entity);
+                    }
+                }
+                Some(ours)
+            }
+            (Some(_), Some(_)) => Some(Vec::new()),
+            (ours, theirs) => ours.or(theirs),
+        };
     }
 }
 
@@ -244,6 +263,18 @@ impl ActionExecutor {
                     );
                 }
 
+                Action::Pseudonymize { entities } => {
+                    let mut requested = ActionOutcome::new();
+                    requested.pseudonymize = Some(entities.clone());
+                    outcome.merge(requested);
+
+                    debug!(
+                        rule = %result.rule_name,
+                        entities = ?entities,
+                        "Pseudonymizing PII"
+                    );
+                }
+
                 Action::Audit { category, severity } => {
                     outcome.audit_records.push(AuditRecord {
                         rule_name: result.rule_name.clone(),
@@ -520,4 +551,26 @@ mod tests {
         assert_eq!(outcome1.stop_message, Some("Stopped".to_string()));
         assert_eq!(outcome1.audit_records.len(), 1);
     }
+
+    #[test]
+    fn test_pseudonymize_entities_merge() {
+        let executor = ActionExecutor::new();
+        let email = create_test_result(vec![Action::Pseudonymize {
+            entities: vec!["email".to_string()],
+        }]);
+        let iban = create_test_result(vec![Action::Pseudonymize {
+            entities: vec!["iban".to_string(), "email".to_string()],
+        }]);
+
+        let outcome = executor.execute(&[email.clone(), iban]);
+        assert!(outcome.has_actions());
+        assert_eq!(
+            outcome.pseudonymize,
+            Some(vec!["email".to_string(), "iban".to_string()])
+        );
+
+        let all = create_test_result(vec![Action::Pseudonymize { entities: vec![] }]);
+        let outcome = executor.execute(&[email, all]);
+        assert_eq!(outcome.pseudonymize, Some(Vec::new()));
+    }
 }
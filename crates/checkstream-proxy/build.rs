@@ -0,0 +1,22 @@
+//! Generates the gRPC messages and service stubs for `proto/checkstream.proto`.
+//!
+//! Uses the vendored `protoc` binary unless `PROTOC` points at another one, so
+//! the build does not need protobuf installed.
+
+const PROTO: &str = "proto/checkstream.proto";
+
+fn main() {
+    println!("cargo:rerun-if-changed={}", PROTO);
+    println!("cargo:rerun-if-env-changed=PROTOC");
+
+    if std::env::var_os("PROTOC").is_none() {
+        let protoc = protoc_bin_vendored::protoc_bin_path()
+            .expect("No vendored protoc for this platform; set PROTOC");
+        std::env::set_var("PROTOC", protoc);
+    }
+
+    tonic_build::configure()
+        .build_client(false)
+        .compile_protos(&[PROTO], &["proto"])
+        .expect("Failed to compile proto/checkstream.proto");
+}
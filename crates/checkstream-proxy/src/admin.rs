@@ -0,0 +1,564 @@
+//! Admin API for runtime inspection and management
+//!
+//! All endpoints require the admin API key (see `require_admin`).
+
+use axum::{
+    extract::{Path, Query, State},
+    http::HeaderMap,
+    routing::{delete, get, post, put},
+    Json, Router,
+};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+use crate::check;
+use crate::config::KeyScope;
+use crate::proxy::{self, AppState, Phase};
+use crate::routes::{require_admin, AppError};
+use crate::tenant::{self, TenantRuntime};
+
+/// Build the admin router (merged into the main router)
+pub(crate) fn router() -> Router<AppState> {
+    Router::new()
+        .route("/admin/tenants", get(list_tenants))
+        .route("/admin/classifiers", get(list_classifiers))
+        .route("/admin/pipelines", get(list_pipelines))
+        .route(
+            "/admin/tenants/:tenant_id/rules/:rule_name",
+            put(set_rule_enabled),
+        )
+        .route("/admin/keys", get(list_keys).post(issue_key))
+        .route("/admin/keys/:key_id", delete(revoke_key))
+        .route("/admin/reload", post(reload))
+        .route("/admin/evaluate", post(evaluate))
+}
+
+/// List tenants with their pipelines and loaded policies
+async fn list_tenants(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<serde_json::Value>, AppError> {
+    require_admin(&headers)?;
+
+    let tenants: Vec<serde_json::Value> = state
+        .tenant_resolver
+        .all_tenants()
+        .iter()
+        .map(|tenant| tenant_json(tenant))
+        .collect();
+
+    Ok(Json(json!({
+        "count": tenants.len(),
+        "tenants": tenants,
+    })))
+}
+
+fn tenant_json(tenant: &TenantRuntime) -> serde_json::Value {
+    let engine = tenant.policy_engine.read().unwrap();
+    let policies: Vec<serde_json::Value> = engine
+        .policies()
+        .iter()
+        .map(|policy| {
+            let rules: Vec<serde_json::Value> = policy
+                .rules
+                .iter()
+                .map(|rule| {
+                    json!({
+                        "name": rule.name,
+                        "description": rule.description,
+                        "enabled": rule.enabled,
+                        "regulation": rule.regulation,
+                    })
+                })
+                .collect();
+
+            json!({
+                "name": policy.name,
+                "version": policy.version,
+                "description": policy.description,
+                "regulation": policy.regulation,
+                "rules": rules,
+            })
+        })
+        .collect();
+
+    let settings = &tenant.pipeline_settings;
+    json!({
+        "id": tenant.id,
+        "name": tenant.name,
+        "backend_url": tenant.backend_url,
+        "policy_path": tenant.policy_path,
+        "pipelines": {
+            "ingress": settings.ingress_pipeline,
+            "midstream": settings.midstream_pipeline,
+            "egress": settings.egress_pipeline,
+        },
+        "noop_stages": tenant.noop_stages,
+        "policies": policies,
+    })
+}
+
+/// List registered classifiers
+async fn list_classifiers(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<serde_json::Value>, AppError> {
+    require_admin(&headers)?;
+
+    let classifiers: Vec<serde_json::Value> = state
+        .registry
+        .classifier_names()
+        .into_iter()
+        .filter_map(|name| {
+            let classifier = state.registry.get(&name)?;
+            Some(json!({
+                "name": name,
+                "implementation": classifier.name(),
+                "tier": format!("{:?}", classifier.tier()),
+                "latency_budget_us": classifier.tier().latency_budget_us(),
+                "backend": state.registry.backend(&name),
+                "noop": state.registry.is_noop(&name),
+            }))
+        })
+        .collect();
+
+    Ok(Json(json!({
+        "count": classifiers.len(),
+        "classifiers": classifiers,
+    })))
+}
+
+/// List configured pipelines with their stage layouts
+async fn list_pipelines(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<serde_json::Value>, AppError> {
+    require_admin(&headers)?;
+
+    let config = state.registry.config();
+    let mut names = config.pipeline_names();
+    names.sort();
+
+    let mut pipelines = Vec::with_capacity(names.len());
+    for name in names {
+        let spec = &config.pipelines[&name];
+        pipelines.push(json!({
+            "name": name,
+            "description": spec.description,
+            "stages": serde_json::to_value(&spec.stages)?,
+        }));
+    }
+
+    Ok(Json(json!({
+        "count": pipelines.len(),
+        "pipelines": pipelines,
+    })))
+}
+
+/// Rule toggle request body
+#[derive(Debug, Deserialize)]
+struct RuleToggleRequest {
+    /// Whether the rule should be enabled
+    enabled: bool,
+    /// Restrict the change to a single policy
+    policy: Option<String>,
+}
+
+/// Enable or disable a rule for a tenant at runtime
+async fn set_rule_enabled(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path((tenant_id, rule_name)): Path<(String, String)>,
+    Json(req): Json<RuleToggleRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    require_admin(&headers)?;
+
+    let tenant = find_tenant(&state, &tenant_id)?;
+    let updated = tenant.policy_engine.write().unwrap().set_rule_enabled(
+        req.policy.as_deref(),
+        &rule_name,
+        req.enabled,
+    );
+
+    if updated == 0 {
+        return Err(AppError::NotFound(format!(
+            "Rule '{}' not found for tenant '{}'",
+            rule_name, tenant_id
+        )));
+    }
+
+    info!(
+        "Admin set rule '{}' enabled={} for tenant {} ({} rules updated)",
+        rule_name, req.enabled, tenant.id, updated
+    );
+
+    Ok(Json(json!({
+        "tenant": tenant.id,
+        "rule": rule_name,
+        "enabled": req.enabled,
+        "updated": updated,
+    })))
+}
+
+/// List virtual API keys (hashes and plaintext are never returned)
+async fn list_keys(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<serde_json::Value>, AppError> {
+    require_admin(&headers)?;
+
+    let keys = state.virtual_keys.list();
+    Ok(Json(json!({
+        "count": keys.len(),
+        "required": state.virtual_keys.is_required(),
+        "keys": keys,
+    })))
+}
+
+/// Key issuance request body
+#[derive(Debug, Deserialize)]
+struct IssueKeyRequest {
+    /// Tenant the key belongs to
+    tenant: String,
+    /// Key ID (generated if omitted)
+    id: Option<String>,
+    /// Endpoint scopes (all if omitted)
+    scopes: Option<Vec<KeyScope>>,
+    /// Expiry time
+    expires_at: Option<DateTime<Utc>>,
+}
+
+/// Issue a virtual API key for a tenant
+///
+/// The plaintext key is returned once. Keys issued here live in memory only;
+/// add the returned `key_hash` to the tenant's `virtual_keys` to persist it.
+async fn issue_key(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<IssueKeyRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    require_admin(&headers)?;
+
+    let tenant = find_tenant(&state, &req.tenant)?;
+    let scopes = req
+        .scopes
+        .unwrap_or_else(|| vec![KeyScope::Chat, KeyScope::Check]);
+    if scopes.contains(&KeyScope::Chat) && tenant.upstream_api_key.is_none() {
+        return Err(AppError::InvalidRequest(format!(
+            "Tenant '{}' has no upstream API key for chat keys",
+            tenant.id
+        )));
+    }
+    let (plaintext, key) = state
+        .virtual_keys
+        .issue(req.id, &tenant.id, scopes, req.expires_at)
+        .map_err(|e| AppError::InvalidRequest(e.to_string()))?;
+
+    Ok(Json(json!({
+        "id": key.id,
+        "tenant": key.tenant_id,
+        "key": plaintext,
+        "key_hash": key.key_hash,
+        "scopes": key.scopes,
+        "expires_at": key.expires_at,
+    })))
+}
+
+/// Revoke a virtual API key
+async fn revoke_key(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(key_id): Path<String>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    require_admin(&headers)?;
+
+    if !state.virtual_keys.revoke(&key_id) {
+        return Err(AppError::NotFound(format!("Unknown key '{}'", key_id)));
+    }
+
+    Ok(Json(json!({ "id": key_id, "revoked": true })))
+}
+
+/// Reload request parameters
+#[derive(Debug, Deserialize)]
+struct ReloadParams {
+    /// Only reload this tenant
+    tenant: Option<String>,
+}
+
+/// Reload policies from disk for one or all tenants
+async fn reload(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(params): Query<ReloadParams>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    require_admin(&headers)?;
+
+    let tenants = match params.tenant {
+        Some(id) => vec![find_tenant(&state, &id)?],
+        None => state.tenant_resolver.all_tenants(),
+    };
+
+    let reloaded: Vec<serde_json::Value> = reload_tenant_policies(&state, &tenants)?
+        .into_iter()
+        .map(|(tenant, count)| json!({ "tenant": tenant, "policies": count }))
+        .collect();
+
+    Ok(Json(json!({ "reloaded": reloaded })))
+}
+
+/// Reload policies for the given tenants
+///
+/// Either every tenant gets its new policies or none do. Returns
+/// `(tenant id, policy count)` pairs in the order given.
+pub(crate) fn reload_tenant_policies(
+    state: &AppState,
+    tenants: &[Arc<TenantRuntime>],
+) -> Result<Vec<(String, usize)>, AppError> {
+    let reload_failed = |e: anyhow::Error| {
+        warn!("Policy reload failed, no tenant was reloaded: {}", e);
+        AppError::InternalError(format!(
+            "Policy reload failed, no tenant was reloaded: {}",
+            e
+        ))
+    };
+
+    // Keep the readiness probe's view of the default tenant in sync
+    let default_tenant = state.tenant_resolver.default_tenant();
+    let default_engine = tenants
+        .iter()
+        .any(|t| t.id == default_tenant.id)
+        .then(|| TenantRuntime::load_policy_engine(&default_tenant.policy_path))
+        .transpose()
+        .map_err(reload_failed)?;
+
+    let reloaded = tenant::reload_policies(tenants).map_err(reload_failed)?;
+    if let Some(engine) = default_engine {
+        *state.policy_engine.write().unwrap() = engine;
+    }
+
+    Ok(reloaded)
+}
+
+/// Dry-run evaluation request body
+#[derive(Debug, Deserialize)]
+struct EvaluateRequest {
+    /// Text to evaluate
+    text: String,
+    /// Tenant to evaluate against (default tenant if omitted)
+    tenant: Option<String>,
+    /// Phase whose pipeline should run
+    #[serde(default)]
+    phase: Phase,
+    /// Structured context for context triggers
+    #[serde(default)]
+    context: HashMap<String, String>,
+}
+
+/// Evaluate arbitrary text against a tenant's pipeline and policies
+///
+/// Shows what would happen to the text without calling the backend.
+async fn evaluate(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<EvaluateRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    require_admin(&headers)?;
+
+    let tenant = match &req.tenant {
+        Some(id) => find_tenant(&state, id)?,
+        None => state.tenant_resolver.default_tenant().clone(),
+    };
+
+    let evaluation =
+        proxy::evaluate_text_with_tenant(&tenant, req.phase, &req.text, &req.context).await?;
+
+    Ok(Json(check::evaluation_json(
+        &tenant.id,
+        req.phase,
+        &req.text,
+        &evaluation,
+    )))
+}
+
+pub(crate) fn find_tenant(
+    state: &AppState,
+    tenant_id: &str,
+) -> Result<Arc<TenantRuntime>, AppError> {
+    state
+        .tenant_resolver
+        .find(tenant_id)
+        .ok_or_else(|| AppError::NotFound(format!("Unknown tenant '{}'", tenant_id)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{admin_request, json_request, TestApp, POLICY};
+    use axum::http::StatusCode;
+
+    fn rules_enabled(app: &TestApp, tenant_id: &str) -> Vec<bool> {
+        let tenant = find_tenant(&app.state, tenant_id).unwrap();
+        let engine = tenant.policy_engine.read().unwrap();
+        engine
+            .policies()
+            .iter()
+            .flat_map(|policy| policy.rules.iter().map(|rule| rule.enabled))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_admin_key_required() {
+        let app = TestApp::new().await;
+
+        let (status, _) = app
+            .call(router(), json_request("GET", "/admin/tenants", &[], None))
+            .await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+
+        let wrong = [("authorization", "Bearer not-the-admin-key")];
+        let (status, _) = app
+            .call(router(), json_request("GET", "/admin/keys", &wrong, None))
+            .await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+
+        let (status, body) = app
+            .call(router(), admin_request("GET", "/admin/tenants", None))
+            .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["count"], 3);
+    }
+
+    #[tokio::test]
+    async fn test_toggle_rule() {
+        let app = TestApp::new().await;
+
+        let (status, body) = app
+            .call(
+                router(),
+                admin_request(
+                    "PUT",
+                    "/admin/tenants/acme/rules/block-secret",
+                    Some(json!({ "enabled": false })),
+                ),
+            )
+            .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["updated"], 1);
+        assert_eq!(rules_enabled(&app, "acme"), vec![false]);
+        assert_eq!(rules_enabled(&app, "globex"), vec![true]);
+
+        let (status, _) = app
+            .call(
+                router(),
+                admin_request(
+                    "PUT",
+                    "/admin/tenants/acme/rules/missing",
+                    Some(json!({ "enabled": false })),
+                ),
+            )
+            .await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_reload_is_all_or_nothing() {
+        let app = TestApp::new().await;
+        std::fs::write(
+            app.policy_path("acme"),
+            "name: empty\ndescription: empty\nrules: []\n",
+        )
+        .unwrap();
+        std::fs::write(app.policy_path("globex"), "rules: [not a rule").unwrap();
+
+        let (status, _) = app
+            .call(router(), admin_request("POST", "/admin/reload", None))
+            .await;
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(rules_enabled(&app, "acme"), vec![true]);
+
+        std::fs::write(app.policy_path("globex"), POLICY).unwrap();
+        let (status, body) = app
+            .call(router(), admin_request("POST", "/admin/reload", None))
+            .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["reloaded"].as_array().unwrap().len(), 3);
+        assert!(rules_enabled(&app, "acme").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_issue_and_revoke_key() {
+        let app = TestApp::new().await;
+
+        let (status, issued) = app
+            .call(
+                router(),
+                admin_request(
+                    "POST",
+                    "/admin/keys",
+                    Some(json!({ "tenant": "acme", "id": "ci", "scopes": ["check"] })),
+                ),
+            )
+            .await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(issued["key"].as_str().unwrap().starts_with("csk-"));
+        assert_eq!(issued["scopes"], json!(["check"]));
+
+        // Chat keys need an upstream key to send in their place
+        let (status, _) = app
+            .call(
+                router(),
+                admin_request("POST", "/admin/keys", Some(json!({ "tenant": "globex" }))),
+            )
+            .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let (status, _) = app
+            .call(router(), admin_request("DELETE", "/admin/keys/ci", None))
+            .await;
+        assert_eq!(status, StatusCode::OK);
+        let (_, listed) = app
+            .call(router(), admin_request("GET", "/admin/keys", None))
+            .await;
+        assert_eq!(listed["count"], 1);
+        assert_eq!(listed["keys"][0]["revoked"], true);
+        assert!(listed["keys"][0].get("key_hash").is_none());
+
+        let (status, _) = app
+            .call(
+                router(),
+                admin_request("DELETE", "/admin/keys/missing", None),
+            )
+            .await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_evaluate_dry_run() {
+        let app = TestApp::new().await;
+
+        let (status, body) = app
+            .call(
+                router(),
+                admin_request(
+                    "POST",
+                    "/admin/evaluate",
+                    Some(json!({ "text": "the secret code", "tenant": "acme" })),
+                ),
+            )
+            .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["tenant"], "acme");
+        assert_eq!(body["phase"], "ingress");
+        assert_eq!(body["blocked"], true);
+        assert_eq!(body["classifiers"][0]["stage"], "pii");
+        assert_eq!(body["triggered_rules"][0]["rule"], "block-secret");
+        assert_eq!(body["outcome"]["should_stop"], true);
+        // Nothing is audited for a dry run
+        assert!(body.get("request_id").is_none());
+    }
+}
@@ -0,0 +1,501 @@
+//! Client authentication with CheckStream-issued virtual API keys
+//!
+//! Virtual keys belong to a tenant and are stored only as SHA-256 hashes.
+//! A valid key selects its tenant and is never forwarded to the backend;
+//! the tenant's upstream key is sent instead.
+
+use anyhow::Result;
+use axum::http::HeaderMap;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::sync::RwLock;
+use tracing::{debug, info};
+
+use crate::config::{KeyScope, MultiTenantConfig, VirtualKeyConfig};
+
+/// Prefix for issued keys
+const KEY_PREFIX: &str = "csk-";
+
+/// Tenant ID used for keys configured on the default tenant
+const DEFAULT_TENANT_ID: &str = "_default";
+
+/// A virtual key record (never contains the plaintext key)
+#[derive(Debug, Clone, Serialize)]
+pub struct VirtualKey {
+    pub id: String,
+    pub tenant_id: String,
+    #[serde(skip)]
+    pub key_hash: String,
+    pub scopes: Vec<KeyScope>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub revoked: bool,
+}
+
+impl VirtualKey {
+    fn from_config(tenant_id: &str, config: &VirtualKeyConfig) -> Result<Self> {
+        let key_hash = config.key_hash.trim().to_ascii_lowercase();
+        if key_hash.len() != 64 || !key_hash.chars().all(|c| c.is_ascii_hexdigit()) {
+            anyhow::bail!(
+                "Virtual key '{}' for tenant '{}' must have a hex-encoded SHA-256 key_hash",
+                config.id,
+                tenant_id
+            );
+        }
+
+        Ok(Self {
+            id: config.id.clone(),
+            tenant_id: tenant_id.to_string(),
+            key_hash,
+            scopes: config.scopes.clone(),
+            expires_at: config.expires_at,
+            revoked: config.revoked,
+        })
+    }
+
+    /// Whether the key has expired at `now`
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= now)
+    }
+}
+
+/// Why a client key was rejected
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuthError {
+    /// No key was presented but one is required
+    Missing,
+    /// The key does not match any virtual key
+    Unknown,
+    /// The key has been revoked
+    Revoked,
+    /// The key has expired
+    Expired,
+    /// The key is valid but not for this endpoint
+    Scope(KeyScope),
+}
+
+impl AuthError {
+    fn reason(&self) -> &'static str {
+        match self {
+            AuthError::Missing => "missing",
+            AuthError::Unknown => "unknown",
+            AuthError::Revoked => "revoked",
+            AuthError::Expired => "expired",
+            AuthError::Scope(_) => "scope",
+        }
+    }
+}
+
+impl std::fmt::Display for AuthError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AuthError::Missing => write!(f, "API key required"),
+            AuthError::Unknown => write!(f, "Invalid API key"),
+            AuthError::Revoked => write!(f, "API key has been revoked"),
+            AuthError::Expired => write!(f, "API key has expired"),
+            AuthError::Scope(scope) => {
+                write!(
+                    f,
+                    "API key is not allowed to use the {} API",
+                    scope.as_str()
+                )
+            }
+        }
+    }
+}
+
+/// In-memory store of virtual keys indexed by hash
+///
+/// Keys issued or revoked at runtime are not written back to the
+/// configuration; persist them by adding the returned `key_hash` to config.
+pub struct VirtualKeyStore {
+    keys: RwLock<HashMap<String, VirtualKey>>,
+    require_key: bool,
+
+    /// Reject presented keys that match no virtual key, fixed at startup
+    reject_unknown: bool,
+}
+
+impl VirtualKeyStore {
+    /// Create a store from key records
+    ///
+    /// Unknown keys are rejected if keys are required or any are configured
+    /// here; keys issued later don't change that.
+    pub fn new(keys: Vec<VirtualKey>, require_key: bool) -> Self {
+        let reject_unknown = require_key || !keys.is_empty();
+        let keys = keys
+            .into_iter()
+            .map(|key| (key.key_hash.clone(), key))
+            .collect();
+        Self {
+            keys: RwLock::new(keys),
+            require_key,
+            reject_unknown,
+        }
+    }
+
+    /// Build from the default and per-tenant `virtual_keys` configuration
+    pub fn from_config(config: &MultiTenantConfig) -> Result<Self> {
+        let mut keys = Vec::new();
+        for key in &config.default.virtual_keys {
+            keys.push(VirtualKey::from_config(DEFAULT_TENANT_ID, key)?);
+        }
+        for (tenant_id, tenant) in &config.tenants {
+            for key in &tenant.virtual_keys {
+                keys.push(VirtualKey::from_config(tenant_id, key)?);
+            }
+        }
+
+        let mut ids = HashSet::new();
+        let mut hashes = HashSet::new();
+        for key in &keys {
+            if !ids.insert(key.id.as_str()) {
+                anyhow::bail!("Duplicate virtual key id '{}'", key.id);
+            }
+            if !hashes.insert(key.key_hash.as_str()) {
+                anyhow::bail!("Virtual key '{}' reuses another key's hash", key.id);
+            }
+        }
+
+        if !keys.is_empty() || config.default.auth.require_virtual_key {
+            info!(
+                "Loaded {} virtual keys (required: {})",
+                keys.len(),
+                config.default.auth.require_virtual_key
+            );
+        }
+
+        Ok(Self::new(keys, config.default.auth.require_virtual_key))
+    }
+
+    /// Whether requests without a valid virtual key are rejected
+    pub fn is_required(&self) -> bool {
+        self.require_key
+    }
+
+    /// Fail unless every tenant with chat-scoped keys has an upstream key
+    ///
+    /// Virtual keys are never forwarded, so a chat request authenticated with
+    /// one would otherwise reach the backend without credentials.
+    pub fn check_upstream_keys(&self, has_upstream_key: impl Fn(&str) -> bool) -> Result<()> {
+        let keys = self.keys.read().unwrap();
+        let mut missing: Vec<&str> = keys
+            .values()
+            .filter(|key| key.scopes.contains(&KeyScope::Chat))
+            .map(|key| key.tenant_id.as_str())
+            .filter(|tenant_id| !has_upstream_key(tenant_id))
+            .collect();
+        missing.sort_unstable();
+        missing.dedup();
+        if !missing.is_empty() {
+            anyhow::bail!(
+                "Tenants with chat virtual keys need upstream_api_key or upstream_api_key_env: {}",
+                missing.join(", ")
+            );
+        }
+        Ok(())
+    }
+
+    /// Authenticate the client key in `headers` for `scope`
+    ///
+    /// Returns `Ok(None)` when no key was presented and keys are not
+    /// required, or when the key is unknown and no virtual keys were
+    /// configured at startup, so the caller falls back to legacy tenant
+    /// resolution.
+    pub fn authenticate(
+        &self,
+        headers: &HeaderMap,
+        scope: KeyScope,
+    ) -> Result<Option<VirtualKey>, AuthError> {
+        let result = self.check(extract_client_key(headers), scope, Utc::now());
+        if let Err(e) = &result {
+            debug!("Client authentication failed: {}", e);
+            metrics::counter!("checkstream_auth_failures_total", "reason" => e.reason())
+                .increment(1);
+        }
+        result
+    }
+
+    fn check(
+        &self,
+        presented: Option<&str>,
+        scope: KeyScope,
+        now: DateTime<Utc>,
+    ) -> Result<Option<VirtualKey>, AuthError> {
+        let Some(presented) = presented else {
+            return if self.require_key {
+                Err(AuthError::Missing)
+            } else {
+                Ok(None)
+            };
+        };
+
+        let keys = self.keys.read().unwrap();
+        let Some(key) = keys.get(&hash_key(presented)) else {
+            return if self.reject_unknown {
+                Err(AuthError::Unknown)
+            } else {
+                Ok(None)
+            };
+        };
+
+        if key.revoked {
+            Err(AuthError::Revoked)
+        } else if key.is_expired(now) {
+            Err(AuthError::Expired)
+        } else if !key.scopes.contains(&scope) {
+            Err(AuthError::Scope(scope))
+        } else {
+            Ok(Some(key.clone()))
+        }
+    }
+
+    /// Issue a new key for a tenant, returning the plaintext key once
+    pub fn issue(
+        &self,
+        id: Option<String>,
+        tenant_id: &str,
+        scopes: Vec<KeyScope>,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Result<(String, VirtualKey)> {
+        let id = id.unwrap_or_else(|| format!("key-{}", uuid::Uuid::new_v4().simple()));
+        let mut keys = self.keys.write().unwrap();
+        if keys.values().any(|key| key.id == id) {
+            anyhow::bail!("Virtual key id '{}' already exists", id);
+        }
+
+        let plaintext = generate_key();
+        let key = VirtualKey {
+            id,
+            tenant_id: tenant_id.to_string(),
+            key_hash: hash_key(&plaintext),
+            scopes,
+            expires_at,
+            revoked: false,
+        };
+        keys.insert(key.key_hash.clone(), key.clone());
+        info!("Issued virtual key '{}' for tenant {}", key.id, tenant_id);
+
+        Ok((plaintext, key))
+    }
+
+    /// Revoke a key by ID; returns false if no such key exists
+    pub fn revoke(&self, id: &str) -> bool {
+        let mut keys = self.keys.write().unwrap();
+        match keys.values_mut().find(|key| key.id == id) {
+            Some(key) => {
+                key.revoked = true;
+                info!("Revoked virtual key '{}' for tenant {}", id, key.tenant_id);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// All keys, sorted by tenant then ID
+    pub fn list(&self) -> Vec<VirtualKey> {
+        let mut keys: Vec<VirtualKey> = self.keys.read().unwrap().values().cloned().collect();
+        keys.sort_by(|a, b| (&a.tenant_id, &a.id).cmp(&(&b.tenant_id, &b.id)));
+        keys
+    }
+}
+
+/// Hex-encoded SHA-256 hash of a key
+pub fn hash_key(key: &str) -> String {
+    format!("{:x}", Sha256::digest(key.as_bytes()))
+}
+
+/// Generate a new random key
+pub fn generate_key() -> String {
+    format!(
+        "{}{}{}",
+        KEY_PREFIX,
+        uuid::Uuid::new_v4().simple(),
+        uuid::Uuid::new_v4().simple()
+    )
+}
+
+/// Extract the client key from `Authorization: Bearer` or `x-api-key`
+fn extract_client_key(headers: &HeaderMap) -> Option<&str> {
+    let bearer = headers
+        .get("authorization")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().strip_prefix("Bearer "))
+        .map(str::trim);
+
+    bearer
+        .or_else(|| headers.get("x-api-key").and_then(|v| v.to_str().ok()))
+        .filter(|key| !key.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn key(id: &str, plaintext: &str, scopes: Vec<KeyScope>) -> VirtualKey {
+        VirtualKey {
+            id: id.to_string(),
+            tenant_id: "acme".to_string(),
+            key_hash: hash_key(plaintext),
+            scopes,
+            expires_at: None,
+            revoked: false,
+        }
+    }
+
+    #[test]
+    fn test_hash_key() {
+        assert_eq!(
+            hash_key("csk-test"),
+            format!("{:x}", Sha256::digest(b"csk-test"))
+        );
+        assert_eq!(hash_key("csk-test").len(), 64);
+        assert!(generate_key().starts_with(KEY_PREFIX));
+    }
+
+    #[test]
+    fn test_check_valid_and_scoped() {
+        let store = VirtualKeyStore::new(vec![key("k1", "csk-one", vec![KeyScope::Chat])], true);
+        let now = Utc::now();
+
+        let found = store.check(Some("csk-one"), KeyScope::Chat, now).unwrap();
+        assert_eq!(found.unwrap().tenant_id, "acme");
+        assert_eq!(
+            store
+                .check(Some("csk-one"), KeyScope::Check, now)
+                .unwrap_err(),
+            AuthError::Scope(KeyScope::Check)
+        );
+    }
+
+    #[test]
+    fn test_check_unknown_and_missing() {
+        let required = VirtualKeyStore::new(vec![key("k1", "csk-one", vec![KeyScope::Chat])], true);
+        let now = Utc::now();
+        assert_eq!(
+            required
+                .check(Some("sk-other"), KeyScope::Chat, now)
+                .unwrap_err(),
+            AuthError::Unknown
+        );
+        assert_eq!(
+            required.check(None, KeyScope::Chat, now).unwrap_err(),
+            AuthError::Missing
+        );
+
+        // With virtual keys configured, unknown keys are rejected even when not required
+        let optional =
+            VirtualKeyStore::new(vec![key("k1", "csk-one", vec![KeyScope::Chat])], false);
+        assert_eq!(
+            optional
+                .check(Some("sk-other"), KeyScope::Chat, now)
+                .unwrap_err(),
+            AuthError::Unknown
+        );
+        assert!(optional.check(None, KeyScope::Chat, now).unwrap().is_none());
+
+        // Without any virtual keys clients fall back to legacy resolution
+        let legacy = VirtualKeyStore::new(vec![], false);
+        assert!(legacy
+            .check(Some("sk-other"), KeyScope::Chat, now)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_issued_key_keeps_passthrough_clients() {
+        let store = VirtualKeyStore::new(vec![], false);
+        let (plaintext, _) = store
+            .issue(Some("ci".to_string()), "acme", vec![KeyScope::Chat], None)
+            .unwrap();
+        let now = Utc::now();
+
+        // Backend keys of legacy clients still pass through
+        assert!(store
+            .check(Some("sk-backend"), KeyScope::Chat, now)
+            .unwrap()
+            .is_none());
+        let found = store.check(Some(&plaintext), KeyScope::Chat, now).unwrap();
+        assert_eq!(found.unwrap().id, "ci");
+    }
+
+    #[test]
+    fn test_check_expired_and_revoked() {
+        let mut expired = key("k1", "csk-old", vec![KeyScope::Chat]);
+        expired.expires_at = Some(Utc::now() - Duration::hours(1));
+        let store = VirtualKeyStore::new(
+            vec![expired, key("k2", "csk-live", vec![KeyScope::Chat])],
+            false,
+        );
+        let now = Utc::now();
+
+        assert_eq!(
+            store
+                .check(Some("csk-old"), KeyScope::Chat, now)
+                .unwrap_err(),
+            AuthError::Expired
+        );
+        assert!(store.revoke("k2"));
+        assert!(!store.revoke("missing"));
+        assert_eq!(
+            store
+                .check(Some("csk-live"), KeyScope::Chat, now)
+                .unwrap_err(),
+            AuthError::Revoked
+        );
+    }
+
+    #[test]
+    fn test_check_upstream_keys() {
+        let store = VirtualKeyStore::new(
+            vec![
+                key("k1", "csk-one", vec![KeyScope::Chat]),
+                key("k2", "csk-two", vec![KeyScope::Check]),
+            ],
+            false,
+        );
+        assert!(store.check_upstream_keys(|tenant| tenant == "acme").is_ok());
+        let err = store
+            .check_upstream_keys(|_| false)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("acme"), "{err}");
+
+        // Check-only keys never reach a backend
+        let check_only =
+            VirtualKeyStore::new(vec![key("k2", "csk-two", vec![KeyScope::Check])], false);
+        assert!(check_only.check_upstream_keys(|_| false).is_ok());
+    }
+
+    #[test]
+    fn test_issue_key() {
+        let store = VirtualKeyStore::new(vec![], true);
+        let (plaintext, issued) = store
+            .issue(Some("ci".to_string()), "acme", vec![KeyScope::Check], None)
+            .unwrap();
+
+        assert_ne!(issued.key_hash, plaintext);
+        let found = store
+            .check(Some(&plaintext), KeyScope::Check, Utc::now())
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.id, "ci");
+        assert!(store
+            .issue(Some("ci".to_string()), "acme", vec![], None)
+            .is_err());
+    }
+
+    #[test]
+    fn test_extract_client_key() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(extract_client_key(&headers), None);
+
+        headers.insert("x-api-key", "csk-header".parse().unwrap());
+        assert_eq!(extract_client_key(&headers), Some("csk-header"));
+
+        headers.insert("authorization", "Bearer csk-bearer".parse().unwrap());
+        assert_eq!(extract_client_key(&headers), Some("csk-bearer"));
+    }
+}
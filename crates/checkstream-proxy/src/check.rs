@@ -0,0 +1,595 @@
+//! Standalone check API for non-proxy integrations
+//!
+//! Runs a tenant's pipeline and policies on caller-supplied text without
+//! forwarding anything to a backend:
+//! - `POST /v1/check` checks a complete text for one phase
+//! - `GET /v1/check/stream` upgrades to a WebSocket that checks chunks incrementally
+
+use axum::{
+    extract::{
+        ws::{close_code, CloseFrame, Message, WebSocket, WebSocketUpgrade},
+        Extension, Query, State,
+    },
+    http::HeaderMap,
+    response::Response,
+    routing::{get, post},
+    Json, Router,
+};
+use checkstream_classifiers::{FailureKind, StreamingPipeline};
+use serde::Deserialize;
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::{debug, error, info};
+
+use crate::config::KeyScope;
+use crate::proxy::{self, generate_request_id, AppState, MidstreamResult, Phase, TextEvaluation};
+use crate::routes::{apply_modifications, authenticate_client, AppError, MAX_BODY_SIZE};
+use crate::tenant::TenantRuntime;
+use crate::tls::ClientCertIdentity;
+
+/// Build the check router (merged into the main router)
+pub(crate) fn router() -> Router<AppState> {
+    Router::new()
+        .route("/v1/check", post(check))
+        .route("/v1/check/stream", get(check_stream))
+}
+
+/// Check request body
+#[derive(Debug, Deserialize)]
+struct CheckRequest {
+    /// Text to check
+    text: String,
+    /// Phase whose pipeline should run
+    #[serde(default)]
+    phase: Phase,
+    /// Tenant to check against (resolved from headers if omitted)
+    tenant: Option<String>,
+    /// Structured context for context triggers
+    #[serde(default)]
+    context: HashMap<String, String>,
+}
+
+/// Check a complete text against a tenant's pipeline and policies
+async fn check(
+    State(state): State<AppState>,
+    client_cert: Option<Extension<ClientCertIdentity>>,
+    headers: HeaderMap,
+    Json(req): Json<CheckRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let tenant = resolve_tenant(
+        &state,
+        req.tenant.as_deref(),
+        &headers,
+        "/v1/check",
+        client_cert.as_ref().map(|Extension(identity)| identity),
+    )?;
+    let request_id = generate_request_id();
+    metrics::counter!("checkstream_requests_total", "tenant" => tenant.id.clone()).increment(1);
+
+    let evaluation = proxy::check_text_with_tenant(
+        &state,
+        &tenant,
+        req.phase,
+        &req.text,
+        &req.context,
+        &request_id,
+    )
+    .await?;
+
+    let mut body = evaluation_json(&tenant.id, req.phase, &req.text, &evaluation);
+    body["request_id"] = json!(request_id);
+    Ok(Json(body))
+}
+
+/// Render a phase evaluation as the JSON shape shared by the check and admin APIs
+pub(crate) fn evaluation_json(
+    tenant_id: &str,
+    phase: Phase,
+    text: &str,
+    evaluation: &TextEvaluation,
+) -> serde_json::Value {
+    let classifiers: Vec<serde_json::Value> = evaluation
+        .pipeline_result
+        .results
+        .iter()
+        .map(|r| {
+            json!({
+                "stage": r.stage_name,
+                "classifier": r.classifier_name,
+                "label": r.result.label,
+                "score": r.result.score,
+                "latency_us": r.result.latency_us,
+            })
+        })
+        .collect();
+
+    let triggered: Vec<serde_json::Value> = evaluation
+        .policy_results
+        .iter()
+        .map(|r| {
+            json!({
+                "policy": r.policy_name,
+                "rule": r.rule_name,
+                "score": r.score,
+                "matched_content": r.metadata.matched_content,
+                "actions": r.actions,
+            })
+        })
+        .collect();
+
+    let outcome = &evaluation.action_outcome;
+    let modified_text = if outcome.modifications.is_empty() {
+        None
+    } else {
+        Some(apply_modifications(text, &outcome.modifications))
+    };
+
+    json!({
+        "tenant": tenant_id,
+        "phase": phase,
+        "blocked": evaluation.blocked,
+        "final_decision": evaluation.pipeline_result.final_decision.as_ref().map(|d| json!({
+            "label": d.label,
+            "score": d.score,
+        })),
+        "classifiers": classifiers,
+        "scores": evaluation.classifier_scores,
+        "missing": evaluation.pipeline_result.failures.iter().map(|f| json!({
+            "stage": f.stage_name,
+            "classifier": f.classifier_name,
+            "reason": match f.kind {
+                FailureKind::TimedOut { .. } => "timeout",
+                FailureKind::Error(_) => "error",
+            },
+            "error": match &f.kind {
+                FailureKind::Error(e) => Some(e),
+                FailureKind::TimedOut { .. } => None,
+            },
+            "substituted": f.substituted,
+        })).collect::<Vec<_>>(),
+        "triggered_rules": triggered,
+        "outcome": {
+            "should_stop": outcome.should_stop,
+            "stop_status": outcome.stop_status,
+            "stop_message": outcome.stop_message,
+            "modifications": outcome.modifications.len(),
+            "audit_records": outcome.audit_records.len(),
+        },
+        "modified_text": modified_text,
+        "stopped_after": evaluation.pipeline_result.stopped_after,
+        "total_latency_us": evaluation.pipeline_result.total_latency_us,
+    })
+}
+
+/// Streaming check query parameters
+#[derive(Debug, Deserialize)]
+struct StreamCheckParams {
+    /// Tenant to check against (resolved from headers if omitted)
+    tenant: Option<String>,
+}
+
+/// Client messages on the streaming check socket
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum StreamCheckMessage {
+    /// Set structured context for the rest of the stream
+    Start {
+        #[serde(default)]
+        context: HashMap<String, String>,
+    },
+    /// Next chunk of generated text
+    Chunk { text: String },
+    /// End of stream: run egress checks on the accumulated text
+    End,
+}
+
+/// Upgrade to a WebSocket for incremental (midstream + egress) checks
+async fn check_stream(
+    State(state): State<AppState>,
+    client_cert: Option<Extension<ClientCertIdentity>>,
+    headers: HeaderMap,
+    Query(params): Query<StreamCheckParams>,
+    ws: WebSocketUpgrade,
+) -> Result<Response, AppError> {
+    let tenant = resolve_tenant(
+        &state,
+        params.tenant.as_deref(),
+        &headers,
+        "/v1/check/stream",
+        client_cert.as_ref().map(|Extension(identity)| identity),
+    )?;
+    metrics::counter!("checkstream_requests_total", "tenant" => tenant.id.clone()).increment(1);
+
+    Ok(ws.on_upgrade(move |socket| run_check_stream(socket, state, tenant)))
+}
+
+/// Per-stream state of an incremental check, shared by the WebSocket and gRPC transports
+///
+/// Chunks run through the tenant's midstream pipeline as they arrive and are
+/// kept for the egress check at the end of the stream, up to
+/// [`MAX_BODY_SIZE`] bytes, the same limit as a `POST /v1/check` body.
+pub(crate) struct StreamCheckSession {
+    pub tenant: Arc<TenantRuntime>,
+    /// Structured context for context triggers
+    pub context: HashMap<String, String>,
+    request_id: String,
+    streaming: StreamingPipeline,
+    full_text: String,
+    index: u64,
+}
+
+/// Midstream verdict for one chunk of a stream
+pub(crate) struct ChunkCheck {
+    /// Position of the chunk in the stream
+    pub index: u64,
+    /// Chunk text, or `[REDACTED]` if it must not be released
+    pub releasable_text: String,
+    pub result: MidstreamResult,
+}
+
+impl StreamCheckSession {
+    pub fn new(
+        tenant: Arc<TenantRuntime>,
+        context: HashMap<String, String>,
+        request_id: String,
+    ) -> Self {
+        let streaming = StreamingPipeline::new(
+            tenant.pipelines.midstream.clone(),
+            tenant.streaming_config(),
+        );
+        Self {
+            tenant,
+            context,
+            request_id,
+            streaming,
+            full_text: String::new(),
+            index: 0,
+        }
+    }
+
+    /// Text received so far
+    pub fn full_text(&self) -> &str {
+        &self.full_text
+    }
+
+    /// Number of chunks checked so far
+    pub fn chunks(&self) -> u64 {
+        self.index
+    }
+
+    /// Run the midstream checks on the next chunk
+    ///
+    /// Fails with [`AppError::InvalidRequest`] once the stream would exceed
+    /// [`MAX_BODY_SIZE`]; the caller should then end the stream.
+    pub async fn check_chunk(
+        &mut self,
+        state: &AppState,
+        text: String,
+    ) -> Result<ChunkCheck, AppError> {
+        if self.full_text.len() + text.len() > MAX_BODY_SIZE {
+            return Err(AppError::InvalidRequest(format!(
+                "Stream exceeds the {} byte limit",
+                MAX_BODY_SIZE
+            )));
+        }
+        self.full_text.push_str(&text);
+
+        let result = proxy::execute_midstream_chunk_with_context(
+            state,
+            &self.tenant,
+            &mut self.streaming,
+            text.clone(),
+            &self.context,
+            &self.request_id,
+        )
+        .await
+        .map_err(|e| {
+            error!(
+                "Midstream check failed: {} (request_id: {})",
+                e, self.request_id
+            );
+            AppError::InternalError("Chunk check failed".to_string())
+        })?;
+
+        let index = self.index;
+        self.index += 1;
+        Ok(ChunkCheck {
+            index,
+            releasable_text: if result.redacted {
+                "[REDACTED]".to_string()
+            } else {
+                text
+            },
+            result,
+        })
+    }
+
+    /// Run the egress checks on the whole stream
+    pub async fn finish(&self, state: &AppState) -> Result<TextEvaluation, AppError> {
+        proxy::check_text_with_tenant(
+            state,
+            &self.tenant,
+            Phase::Egress,
+            &self.full_text,
+            &self.context,
+            &self.request_id,
+        )
+        .await
+        .map_err(|e| {
+            error!(
+                "Egress check failed: {} (request_id: {})",
+                e, self.request_id
+            );
+            AppError::InternalError("Final check failed".to_string())
+        })
+    }
+}
+
+async fn run_check_stream(mut socket: WebSocket, state: AppState, tenant: Arc<TenantRuntime>) {
+    let request_id = generate_request_id();
+    info!(
+        "Streaming check opened for tenant: {} (request_id: {})",
+        tenant.id, request_id
+    );
+
+    let mut session = StreamCheckSession::new(tenant, HashMap::new(), request_id.clone());
+    let mut close = None;
+
+    while let Some(Ok(message)) = socket.recv().await {
+        let payload = match message {
+            Message::Text(payload) => payload,
+            Message::Close(_) => break,
+            _ => continue,
+        };
+
+        let (reply, done) = match serde_json::from_str::<StreamCheckMessage>(&payload) {
+            Ok(StreamCheckMessage::Start { context }) => {
+                session.context = context;
+                (
+                    json!({ "type": "started", "request_id": request_id }),
+                    false,
+                )
+            }
+            Ok(StreamCheckMessage::Chunk { text }) => {
+                match session.check_chunk(&state, text).await {
+                    Ok(check) => {
+                        let outcome = &check.result.action_outcome;
+                        let reply = json!({
+                            "type": "verdict",
+                            "index": check.index,
+                            "redacted": check.result.redacted,
+                            "should_stop": outcome.should_stop,
+                            "stop_message": outcome.stop_message,
+                            "text": check.releasable_text,
+                            "scores": check.result.classifier_scores,
+                        });
+                        (reply, outcome.should_stop)
+                    }
+                    Err(AppError::InvalidRequest(message)) => {
+                        close = Some(CloseFrame {
+                            code: close_code::SIZE,
+                            reason: "Stream too large".into(),
+                        });
+                        (json!({ "type": "error", "message": message }), true)
+                    }
+                    Err(_) => (
+                        json!({
+                            "type": "error",
+                            "index": session.chunks(),
+                            "message": "Chunk check failed",
+                        }),
+                        false,
+                    ),
+                }
+            }
+            Ok(StreamCheckMessage::End) => {
+                let reply = match session.finish(&state).await {
+                    Ok(evaluation) => {
+                        let mut body = evaluation_json(
+                            &session.tenant.id,
+                            Phase::Egress,
+                            session.full_text(),
+                            &evaluation,
+                        );
+                        body["type"] = json!("final");
+                        body["request_id"] = json!(request_id);
+                        body
+                    }
+                    Err(_) => json!({ "type": "error", "message": "Final check failed" }),
+                };
+                (reply, true)
+            }
+            Err(e) => (
+                json!({ "type": "error", "message": format!("Invalid message: {}", e) }),
+                false,
+            ),
+        };
+
+        if socket.send(Message::Text(reply.to_string())).await.is_err() {
+            break;
+        }
+        if done {
+            let _ = socket.send(Message::Close(close.take())).await;
+            break;
+        }
+    }
+
+    debug!(
+        "Streaming check closed after {} chunks (request_id: {})",
+        session.chunks(),
+        request_id
+    );
+}
+
+/// Resolve the tenant from the client's virtual key, an explicit ID or the request
+pub(crate) fn resolve_tenant(
+    state: &AppState,
+    tenant_id: Option<&str>,
+    headers: &HeaderMap,
+    path: &str,
+    client_cert: Option<&ClientCertIdentity>,
+) -> Result<Arc<TenantRuntime>, AppError> {
+    // A virtual key pins the tenant
+    if let Some((_, tenant)) = authenticate_client(state, headers, KeyScope::Check)? {
+        if tenant_id.is_some_and(|id| id != tenant.id) {
+            return Err(AppError::Forbidden(
+                "API key does not belong to this tenant".to_string(),
+            ));
+        }
+        return Ok(tenant);
+    }
+
+    match tenant_id {
+        Some(id) => state
+            .tenant_resolver
+            .find(id)
+            .ok_or_else(|| AppError::InvalidRequest(format!("Unknown tenant '{}'", id))),
+        None => Ok(state.tenant_resolver.resolve(headers, path, client_cert)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{json_request, TestApp};
+    use axum::http::StatusCode;
+    use futures::{SinkExt, StreamExt};
+    use tokio::net::TcpStream;
+    use tokio_tungstenite::tungstenite::{self, protocol::frame::coding::CloseCode};
+    use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+
+    type Client = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+    #[tokio::test]
+    async fn test_check_response_shape() {
+        let app = TestApp::new().await;
+
+        let (status, body) = app
+            .call(
+                router(),
+                json_request(
+                    "POST",
+                    "/v1/check",
+                    &[],
+                    Some(json!({ "text": "my secret", "phase": "egress", "tenant": "acme" })),
+                ),
+            )
+            .await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["tenant"], "acme");
+        assert_eq!(body["phase"], "egress");
+        assert!(body["request_id"].is_string());
+        assert_eq!(body["classifiers"][0]["stage"], "pii");
+        assert_eq!(body["triggered_rules"][0]["rule"], "block-secret");
+        assert_eq!(body["outcome"]["should_stop"], true);
+        assert!(body["missing"].as_array().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_virtual_key_scope_and_tenant() {
+        let app = TestApp::new().await;
+        let keys = &app.state.virtual_keys;
+        let (chat_key, _) = keys
+            .issue(None, "acme", vec![KeyScope::Chat], None)
+            .unwrap();
+        let (check_key, _) = keys
+            .issue(None, "acme", vec![KeyScope::Check], None)
+            .unwrap();
+        let check = |key: &str, tenant: Option<&str>| {
+            let mut body = json!({ "text": "hello" });
+            if let Some(tenant) = tenant {
+                body["tenant"] = json!(tenant);
+            }
+            json_request(
+                "POST",
+                "/v1/check",
+                &[("authorization", &format!("Bearer {key}"))],
+                Some(body),
+            )
+        };
+
+        // A chat-only key cannot use the check API
+        let (status, _) = app.call(router(), check(&chat_key, None)).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+
+        // A check key pins its tenant
+        let (status, body) = app.call(router(), check(&check_key, None)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["tenant"], "acme");
+
+        let (status, _) = app.call(router(), check(&check_key, Some("globex"))).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+    }
+
+    async fn connect(app: &TestApp) -> Client {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let app_router = router().with_state(app.state.clone());
+        tokio::spawn(async move { axum::serve(listener, app_router).await });
+
+        let url = format!("ws://{addr}/v1/check/stream?tenant=acme");
+        tokio_tungstenite::connect_async(url).await.unwrap().0
+    }
+
+    async fn send(socket: &mut Client, message: serde_json::Value) -> serde_json::Value {
+        socket
+            .send(tungstenite::Message::Text(message.to_string()))
+            .await
+            .unwrap();
+        match socket.next().await.unwrap().unwrap() {
+            tungstenite::Message::Text(reply) => serde_json::from_str(&reply).unwrap(),
+            other => panic!("Expected a text reply, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stream_round_trip() {
+        let app = TestApp::new().await;
+        let mut socket = connect(&app).await;
+
+        let reply = send(&mut socket, json!({ "type": "start", "context": {} })).await;
+        assert_eq!(reply["type"], "started");
+
+        let reply = send(&mut socket, json!({ "type": "chunk", "text": "hello " })).await;
+        assert_eq!(reply["type"], "verdict");
+        assert_eq!(reply["index"], 0);
+        assert_eq!(reply["text"], "hello ");
+        assert_eq!(reply["should_stop"], false);
+
+        let reply = send(&mut socket, json!({ "type": "chunk", "text": "world" })).await;
+        assert_eq!(reply["index"], 1);
+
+        let reply = send(&mut socket, json!({ "type": "end" })).await;
+        assert_eq!(reply["type"], "final");
+        assert_eq!(reply["tenant"], "acme");
+        assert_eq!(reply["phase"], "egress");
+        assert!(reply["triggered_rules"].as_array().unwrap().is_empty());
+
+        assert!(matches!(
+            socket.next().await,
+            Some(Ok(tungstenite::Message::Close(_)))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_stream_closes_over_limit() {
+        let app = TestApp::new().await;
+        let mut socket = connect(&app).await;
+
+        let reply = send(&mut socket, json!({ "type": "chunk", "text": "hello" })).await;
+        assert_eq!(reply["type"], "verdict");
+
+        let text = "a".repeat(MAX_BODY_SIZE);
+        let reply = send(&mut socket, json!({ "type": "chunk", "text": text })).await;
+        assert_eq!(reply["type"], "error");
+
+        match socket.next().await {
+            Some(Ok(tungstenite::Message::Close(Some(frame)))) => {
+                assert_eq!(frame.code, CloseCode::Size)
+            }
+            other => panic!("Expected a close frame, got {other:?}"),
+        }
+    }
+}
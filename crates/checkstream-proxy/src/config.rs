@@ -2,6 +2,8 @@
 //!
 //! Supports both single-tenant (backward compatible) and multi-tenant configurations.
 
+use checkstream_classifiers::{ContextWindow, EvaluationSchedule, PiiEntity};
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::Path;
@@ -37,6 +39,30 @@ pub struct ProxyConfig {
     /// Telemetry configuration
     #[serde(default)]
     pub telemetry: TelemetryConfig,
+
+    /// TLS termination on the listener (plain HTTP if not set)
+    #[serde(default)]
+    pub tls: Option<TlsConfig>,
+
+    /// Client certificate and CA for backend connections
+    #[serde(default)]
+    pub backend_tls: Option<BackendTlsConfig>,
+
+    /// Client authentication settings
+    #[serde(default)]
+    pub auth: AuthConfig,
+
+    /// Virtual API keys for the default tenant
+    #[serde(default)]
+    pub virtual_keys: Vec<VirtualKeyConfig>,
+
+    /// Upstream API key sent to the backend for virtual-key clients
+    #[serde(default)]
+    pub upstream_api_key: Option<String>,
+
+    /// Environment variable holding the upstream API key
+    #[serde(default)]
+    pub upstream_api_key_env: Option<String>,
 }
 
 /// Pipeline execution settings
@@ -75,12 +101,74 @@ pub struct PipelineSettings {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StreamingSettings {
     /// Number of chunks to include as context (0 = entire buffer)
+    ///
+    /// Ignored when `window` is set.
     #[serde(default = "default_context_chunks")]
     pub context_chunks: usize,
 
+    /// Context window by chunks, characters, tokens or sentences
+    #[serde(default)]
+    pub window: Option<WindowSettings>,
+
     /// Maximum buffer size
     #[serde(default = "default_stream_buffer_size")]
     pub max_buffer_size: usize,
+
+    /// When model classifiers re-run during a stream (pattern and PII
+    /// classifiers scan each chunk incrementally regardless)
+    #[serde(default)]
+    pub schedule: EvaluationSchedule,
+
+    /// Schedules for individual classifiers, by classifier name
+    #[serde(default)]
+    pub classifier_schedules: HashMap<String, EvaluationSchedule>,
+}
+
+/// Streaming context window (`pipelines.streaming.window`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WindowSettings {
+    /// Unit the window is measured in
+    pub unit: WindowUnit,
+
+    /// Number of units before the end of the stream
+    pub size: usize,
+
+    /// Extra units before the window, so matches spanning its edge are seen
+    #[serde(default)]
+    pub overlap: usize,
+}
+
+/// Unit of a streaming context window
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WindowUnit {
+    /// Streamed chunks
+    Chunks,
+    /// Characters
+    Chars,
+    /// Whitespace-separated words
+    Words,
+    /// Sentences, split after `.`, `!`, `?` or newlines
+    Sentences,
+}
+
+impl StreamingSettings {
+    /// Context window and overlap to stream with
+    pub fn context_window(&self) -> (ContextWindow, usize) {
+        let Some(window) = &self.window else {
+            return match self.context_chunks {
+                0 => (ContextWindow::All, 0),
+                n => (ContextWindow::Chunks(n), 0),
+            };
+        };
+        let context = match window.unit {
+            WindowUnit::Chunks => ContextWindow::Chunks(window.size),
+            WindowUnit::Chars => ContextWindow::Chars(window.size),
+            WindowUnit::Words => ContextWindow::Tokens(window.size),
+            WindowUnit::Sentences => ContextWindow::Sentences(window.size),
+        };
+        (context, window.overlap)
+    }
 }
 
 impl Default for PipelineSettings {
@@ -101,7 +189,10 @@ impl Default for StreamingSettings {
     fn default() -> Self {
         Self {
             context_chunks: default_context_chunks(),
+            window: None,
             max_buffer_size: default_stream_buffer_size(),
+            schedule: EvaluationSchedule::default(),
+            classifier_schedules: HashMap::new(),
         }
     }
 }
@@ -138,6 +229,8 @@ impl ProxyConfig {
             config.policy_path = policy.clone();
         }
 
+        config.tls = TlsConfig::with_cli_overrides(config.tls, cli)?;
+
         Ok(config)
     }
 }
@@ -152,10 +245,156 @@ impl Default for ProxyConfig {
             max_buffer_capacity: default_buffer_capacity(),
             pipelines: PipelineSettings::default(),
             telemetry: TelemetryConfig::default(),
+            tls: None,
+            backend_tls: None,
+            auth: AuthConfig::default(),
+            virtual_keys: Vec::new(),
+            upstream_api_key: None,
+            upstream_api_key_env: None,
         }
     }
 }
 
+/// Client authentication settings
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct AuthConfig {
+    /// Reject requests without a valid virtual key instead of falling back
+    /// to header/path/default tenant resolution
+    ///
+    /// When virtual keys are configured, presented keys that match none of
+    /// them are rejected either way; this also rejects requests with no key.
+    #[serde(default)]
+    pub require_virtual_key: bool,
+}
+
+/// A CheckStream-issued API key (only the SHA-256 hash is stored)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VirtualKeyConfig {
+    /// Key identifier (used for listing and revocation)
+    pub id: String,
+
+    /// Hex-encoded SHA-256 hash of the key
+    pub key_hash: String,
+
+    /// Endpoints the key may call
+    #[serde(default = "default_key_scopes")]
+    pub scopes: Vec<KeyScope>,
+
+    /// Expiry time (never expires if not set)
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+
+    /// Whether the key has been revoked
+    #[serde(default)]
+    pub revoked: bool,
+}
+
+/// Endpoint groups a virtual key can be scoped to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyScope {
+    /// Chat completions proxy
+    Chat,
+    /// Standalone check API (HTTP, WebSocket and gRPC)
+    Check,
+}
+
+impl KeyScope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            KeyScope::Chat => "chat",
+            KeyScope::Check => "check",
+        }
+    }
+}
+
+fn default_key_scopes() -> Vec<KeyScope> {
+    vec![KeyScope::Chat, KeyScope::Check]
+}
+
+/// Listener TLS configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TlsConfig {
+    /// PEM certificate chain presented to clients
+    pub cert_path: String,
+
+    /// PEM private key for the certificate
+    pub key_path: String,
+
+    /// PEM CA bundle used to verify client certificates (enables mTLS)
+    #[serde(default)]
+    pub client_ca_path: Option<String>,
+
+    /// Whether clients must present a certificate when `client_ca_path` is set
+    #[serde(default)]
+    pub client_auth: ClientAuthMode,
+
+    /// How often to check the certificate files for changes, in seconds (0 = never)
+    #[serde(default = "default_tls_reload_interval")]
+    pub reload_interval_secs: u64,
+}
+
+impl TlsConfig {
+    /// Apply `--tls-cert`/`--tls-key` overrides, creating the config if needed
+    ///
+    /// Without a `tls` section both flags are required; passing only one is an
+    /// error rather than a silent fallback to plain HTTP.
+    fn with_cli_overrides(tls: Option<Self>, cli: &crate::Cli) -> anyhow::Result<Option<Self>> {
+        match (tls, &cli.tls_cert, &cli.tls_key) {
+            (Some(mut tls), cert, key) => {
+                if let Some(cert) = cert {
+                    tls.cert_path = cert.clone();
+                }
+                if let Some(key) = key {
+                    tls.key_path = key.clone();
+                }
+                Ok(Some(tls))
+            }
+            (None, Some(cert), Some(key)) => Ok(Some(Self {
+                cert_path: cert.clone(),
+                key_path: key.clone(),
+                client_ca_path: None,
+                client_auth: ClientAuthMode::default(),
+                reload_interval_secs: default_tls_reload_interval(),
+            })),
+            (None, Some(_), None) => {
+                anyhow::bail!("--tls-cert requires --tls-key when the config has no tls section")
+            }
+            (None, None, Some(_)) => {
+                anyhow::bail!("--tls-key requires --tls-cert when the config has no tls section")
+            }
+            (None, None, None) => Ok(None),
+        }
+    }
+}
+
+/// Client certificate requirement for mTLS
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ClientAuthMode {
+    /// Verify a client certificate if presented, but allow anonymous clients
+    #[default]
+    Optional,
+    /// Reject connections without a valid client certificate
+    Required,
+}
+
+/// TLS settings for connections to the backend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackendTlsConfig {
+    /// PEM client certificate chain presented to the backend
+    #[serde(default)]
+    pub client_cert_path: Option<String>,
+
+    /// PEM private key for the client certificate
+    #[serde(default)]
+    pub client_key_path: Option<String>,
+
+    /// Additional PEM CA bundle trusted for the backend
+    #[serde(default)]
+    pub ca_cert_path: Option<String>,
+}
+
 /// Telemetry configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TelemetryConfig {
@@ -255,6 +494,24 @@ pub struct TenantConfig {
     /// Maximum buffer capacity (optional, inherits from default)
     #[serde(default)]
     pub max_buffer_capacity: Option<usize>,
+    /// Client certificate names (subject CN or SAN) that map to this tenant
+    #[serde(default)]
+    pub client_cert_names: Vec<String>,
+    /// Backend TLS settings (optional, inherits from default)
+    #[serde(default)]
+    pub backend_tls: Option<BackendTlsConfig>,
+    /// Virtual API keys issued to this tenant
+    #[serde(default)]
+    pub virtual_keys: Vec<VirtualKeyConfig>,
+    /// Upstream API key sent to the backend for virtual-key clients
+    #[serde(default)]
+    pub upstream_api_key: Option<String>,
+    /// Environment variable holding the upstream API key
+    #[serde(default)]
+    pub upstream_api_key_env: Option<String>,
+    /// PII entity types to detect (optional, inherits from classifiers config)
+    #[serde(default)]
+    pub pii_entities: Option<Vec<PiiEntity>>,
 }
 
 fn default_policy_path() -> String {
@@ -305,6 +562,8 @@ impl MultiTenantConfig {
             config.default.policy_path = policy.clone();
         }
 
+        config.default.tls = TlsConfig::with_cli_overrides(config.default.tls, cli)?;
+
         Ok(config)
     }
 
@@ -374,6 +633,62 @@ fn default_stream_buffer_size() -> usize {
     100
 }
 
+fn default_tls_reload_interval() -> u64 {
+    30
+}
+
 fn default_true() -> bool {
     true
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    fn cli(args: &[&str]) -> crate::Cli {
+        crate::Cli::try_parse_from(["checkstream-proxy"].iter().chain(args)).unwrap()
+    }
+
+    fn file_tls() -> TlsConfig {
+        TlsConfig {
+            cert_path: "file.crt".to_string(),
+            key_path: "file.key".to_string(),
+            client_ca_path: None,
+            client_auth: ClientAuthMode::default(),
+            reload_interval_secs: default_tls_reload_interval(),
+        }
+    }
+
+    #[test]
+    fn test_tls_cli_overrides() {
+        let tls = TlsConfig::with_cli_overrides(None, &cli(&[])).unwrap();
+        assert!(tls.is_none());
+
+        let both = cli(&["--tls-cert", "cli.crt", "--tls-key", "cli.key"]);
+        let tls = TlsConfig::with_cli_overrides(None, &both).unwrap().unwrap();
+        assert_eq!(
+            (tls.cert_path.as_str(), tls.key_path.as_str()),
+            ("cli.crt", "cli.key")
+        );
+
+        let cert_only = cli(&["--tls-cert", "cli.crt"]);
+        let tls = TlsConfig::with_cli_overrides(Some(file_tls()), &cert_only)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            (tls.cert_path.as_str(), tls.key_path.as_str()),
+            ("cli.crt", "file.key")
+        );
+    }
+
+    #[test]
+    fn test_tls_cli_half_pair_is_rejected() {
+        let err =
+            TlsConfig::with_cli_overrides(None, &cli(&["--tls-cert", "cli.crt"])).unwrap_err();
+        assert!(err.to_string().contains("--tls-key"));
+
+        let err = TlsConfig::with_cli_overrides(None, &cli(&["--tls-key", "cli.key"])).unwrap_err();
+        assert!(err.to_string().contains("--tls-cert"));
+    }
+}
@@ -0,0 +1,445 @@
+//! `checkstream-proxy evaluate`: threshold and calibration tooling
+//!
+//! Runs a classifier or pipeline from a classifiers config over a labelled
+//! JSONL dataset and reports precision, recall and F1, ROC and
+//! precision-recall curves, and the threshold that keeps false positives
+//! under a target rate. With `--fit`, it also fits a calibration to the
+//! classifier's raw scores and prints the `calibration:` entry to add to the
+//! config; the metrics are then reported on examples held out from the fit.
+
+use anyhow::{bail, Context, Result};
+use checkstream_classifiers::evaluation::{load_dataset, Evaluation, ThresholdMetrics};
+use checkstream_classifiers::{Calibration, CalibrationMethod, ClassificationResult};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use crate::tenant::{load_classifier_config, load_classifier_registry};
+
+/// Arguments for the `evaluate` subcommand
+#[derive(clap::Args, Debug)]
+pub(crate) struct EvaluateArgs {
+    /// Labelled JSONL dataset: one {"text": ..., "label": true|false} per line
+    #[arg(long)]
+    dataset: PathBuf,
+
+    /// Classifiers configuration file
+    #[arg(long, default_value = "classifiers.yaml")]
+    classifiers: String,
+
+    /// Classifier to evaluate
+    #[arg(
+        long,
+        required_unless_present = "pipeline",
+        conflicts_with = "pipeline"
+    )]
+    classifier: Option<String>,
+
+    /// Pipeline to evaluate, scored by its final decision
+    #[arg(long)]
+    pipeline: Option<String>,
+
+    /// Score one label of a multi-label classifier instead of its top score
+    #[arg(long, requires = "classifier")]
+    label: Option<String>,
+
+    /// Threshold to report precision, recall and F1 at
+    #[arg(long, default_value_t = 0.5)]
+    threshold: f32,
+
+    /// Highest acceptable false-positive rate for the recommended threshold
+    #[arg(long, default_value_t = 0.01)]
+    target_fpr: f32,
+
+    /// Fit a calibration (platt, isotonic or temperature) to raw scores
+    #[arg(long, requires = "classifier", conflicts_with = "label")]
+    fit: Option<String>,
+
+    /// Share of each label held out of `--fit` to report metrics on
+    /// (0 reports in-sample metrics)
+    #[arg(long, default_value_t = 0.3)]
+    holdout: f32,
+
+    /// Print the report, including every curve point, as JSON
+    #[arg(long)]
+    json: bool,
+}
+
+/// `(score, is positive)` for each example
+type Samples = Vec<(f32, bool)>;
+
+/// JSON report printed with `--json`
+#[derive(Debug, Serialize)]
+struct Report {
+    examples: usize,
+    fit_examples: Option<usize>,
+    in_sample: bool,
+    positives: usize,
+    roc_auc: f32,
+    average_precision: f32,
+    at_threshold: ThresholdMetrics,
+    target_fpr: f32,
+    recommended: Option<ThresholdMetrics>,
+    best_f1: Option<ThresholdMetrics>,
+    calibration: Option<HashMap<String, Calibration>>,
+    curve: Vec<ThresholdMetrics>,
+}
+
+/// Run the `evaluate` subcommand
+pub(crate) async fn run(args: EvaluateArgs) -> Result<()> {
+    let report = evaluate(&args).await?;
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+
+    if let Some(fitted) = report.fit_examples {
+        if report.in_sample {
+            println!(
+                "Calibration fitted on all {} examples; the metrics below are in-sample and optimistic",
+                fitted
+            );
+        } else {
+            println!(
+                "Calibration fitted on {} examples; the metrics below are on the {} held out",
+                fitted, report.examples
+            );
+        }
+    }
+    println!(
+        "Examples: {} ({} positive, {} negative)",
+        report.examples,
+        report.positives,
+        report.examples - report.positives
+    );
+    println!(
+        "ROC AUC: {:.4}  Average precision: {:.4}",
+        report.roc_auc, report.average_precision
+    );
+    println!();
+    println!(
+        "{:<24} {:>9} {:>9} {:>9} {:>9} {:>9}",
+        "", "threshold", "precision", "recall", "f1", "fpr"
+    );
+    print_metrics(&format!("at {}", args.threshold), &report.at_threshold);
+    match &report.recommended {
+        Some(metrics) => print_metrics(&format!("fpr <= {}", args.target_fpr), metrics),
+        None => println!(
+            "{:<24} no threshold keeps fpr <= {}",
+            format!("fpr <= {}", args.target_fpr),
+            args.target_fpr
+        ),
+    }
+    if let Some(metrics) = &report.best_f1 {
+        print_metrics("best f1", metrics);
+    }
+
+    if let Some(entry) = report.calibration {
+        println!();
+        println!("Scores above are calibrated. Add to {}:", args.classifiers);
+        println!();
+        print!(
+            "{}",
+            serde_yaml::to_string(&HashMap::from([("calibration", entry)]))?
+        );
+    }
+
+    Ok(())
+}
+
+/// Score the dataset and build the report
+async fn evaluate(args: &EvaluateArgs) -> Result<Report> {
+    let method = args
+        .fit
+        .as_deref()
+        .map(CalibrationMethod::parse)
+        .transpose()?;
+    if method.is_some() && !(0.0..1.0).contains(&args.holdout) {
+        bail!("--holdout must be at least 0 and below 1");
+    }
+
+    let mut config = load_classifier_config(&args.classifiers)?;
+    if let (Some(name), Some(_)) = (&args.classifier, method) {
+        // Fit on raw scores, not on top of an existing calibration
+        config.calibration.remove(name);
+    }
+    let registry = load_classifier_registry(config).await?;
+
+    let examples = load_dataset(&args.dataset)?;
+    if examples.is_empty() {
+        bail!("Dataset {} has no examples", args.dataset.display());
+    }
+
+    let mut samples = Vec::with_capacity(examples.len());
+    if let Some(name) = &args.classifier {
+        let classifier = registry
+            .get(name)
+            .with_context(|| format!("Classifier '{}' not found", name))?;
+        if registry.is_noop(name) {
+            bail!("Classifier '{}' is a no-op that scores everything 0", name);
+        }
+        for example in &examples {
+            let result = classifier.classify(&example.text).await?;
+            samples.push((score(&result, args.label.as_deref())?, example.label));
+        }
+    } else if let Some(name) = &args.pipeline {
+        let pipeline = registry.build_pipeline(name)?;
+        for example in &examples {
+            let result = pipeline.execute(&example.text).await?;
+            let score = result.final_decision.map_or(0.0, |decision| decision.score);
+            samples.push((score, example.label));
+        }
+    }
+
+    let mut fit_examples = None;
+    let calibration = match method {
+        Some(method) => {
+            let (fit, held_out) = split_holdout(samples, args.holdout);
+            let calibration = Calibration::fit(method, &fit)?;
+            fit_examples = Some(fit.len());
+            samples = if held_out.is_empty() { fit } else { held_out };
+            if args.holdout > 0.0
+                && samples
+                    .iter()
+                    .all(|&(_, positive)| positive == samples[0].1)
+            {
+                bail!(
+                    "The held-out examples need both labels; add examples or pass --holdout 0 for in-sample metrics"
+                );
+            }
+            for sample in &mut samples {
+                sample.0 = calibration.apply(sample.0);
+            }
+            Some(calibration)
+        }
+        None => None,
+    };
+
+    let evaluation = Evaluation::new(samples);
+    Ok(Report {
+        examples: evaluation.len(),
+        fit_examples,
+        in_sample: fit_examples.is_some() && args.holdout == 0.0,
+        positives: evaluation.positives(),
+        roc_auc: evaluation.roc_auc(),
+        average_precision: evaluation.average_precision(),
+        at_threshold: evaluation.metrics_at(args.threshold),
+        target_fpr: args.target_fpr,
+        recommended: evaluation.threshold_for_fpr(args.target_fpr),
+        best_f1: evaluation.best_f1(),
+        calibration: calibration_entry(args, calibration.as_ref()),
+        curve: evaluation.curve(),
+    })
+}
+
+/// Split samples into `(fit, held out)`, holding out `fraction` of each label
+///
+/// Held-out samples are spread evenly through the dataset, starting with the
+/// first of each label, so the split is the same on every run.
+fn split_holdout(samples: Samples, fraction: f32) -> (Samples, Samples) {
+    // Whole per-mille so float error never shifts the split
+    let per_mille = (fraction * 1000.0).round() as usize;
+    let held = |count: usize| (count * per_mille).div_ceil(1000);
+
+    let mut seen = [0usize; 2];
+    let mut fit = Vec::with_capacity(samples.len());
+    let mut held_out = Vec::new();
+    for sample in samples {
+        let count = &mut seen[usize::from(sample.1)];
+        *count += 1;
+        if held(*count) > held(*count - 1) {
+            held_out.push(sample);
+        } else {
+            fit.push(sample);
+        }
+    }
+    (fit, held_out)
+}
+
+/// Score evaluated for one result: the top score, or one label's score
+fn score(result: &ClassificationResult, label: Option<&str>) -> Result<f32> {
+    let Some(label) = label else {
+        return Ok(result.score);
+    };
+    result
+        .metadata
+        .all_scores
+        .iter()
+        .flatten()
+        .find(|(name, _)| name == label)
+        .map(|(_, score)| *score)
+        .with_context(|| format!("Classifier result has no score for label '{}'", label))
+}
+
+/// `calibration:` section entry for the fitted classifier
+fn calibration_entry(
+    args: &EvaluateArgs,
+    calibration: Option<&Calibration>,
+) -> Option<HashMap<String, Calibration>> {
+    let name = args.classifier.clone()?;
+    Some(HashMap::from([(name, calibration?.clone())]))
+}
+
+fn print_metrics(name: &str, metrics: &ThresholdMetrics) {
+    println!(
+        "{:<24} {:>9.4} {:>9.4} {:>9.4} {:>9.4} {:>9.4}",
+        name,
+        metrics.threshold,
+        metrics.precision,
+        metrics.recall,
+        metrics.f1,
+        metrics.false_positive_rate
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use checkstream_classifiers::LabelScore;
+    use clap::Parser;
+    use std::io::Write;
+
+    #[derive(Parser)]
+    struct Cli {
+        #[command(flatten)]
+        args: EvaluateArgs,
+    }
+
+    fn args(extra: &[&str]) -> EvaluateArgs {
+        let argv = ["evaluate", "--dataset", "data.jsonl"].iter().chain(extra);
+        Cli::try_parse_from(argv).unwrap().args
+    }
+
+    /// Classifiers config with a keyword classifier and a dataset it half gets right
+    fn fixtures() -> (tempfile::NamedTempFile, tempfile::NamedTempFile) {
+        let mut classifiers = tempfile::Builder::new().suffix(".yaml").tempfile().unwrap();
+        write!(
+            classifiers,
+            "patterns:\n  competitors:\n    categories:\n      competitor:\n        severity: 0.6\n        keywords: [globex, initech]\n"
+        )
+        .unwrap();
+
+        let mut dataset = tempfile::Builder::new()
+            .suffix(".jsonl")
+            .tempfile()
+            .unwrap();
+        for (text, label) in [
+            ("Globex is cheaper", true),
+            ("Initech has a better rate", true),
+            ("Try Globex instead", true),
+            ("Another bank offers more", true),
+            ("Globex Street branch is closed", false),
+            ("What is my balance?", false),
+            ("Open a savings account", false),
+            ("Reset my password", false),
+        ] {
+            writeln!(
+                dataset,
+                "{}",
+                serde_json::json!({"text": text, "label": label})
+            )
+            .unwrap();
+        }
+        (classifiers, dataset)
+    }
+
+    fn fixture_args(
+        classifiers: &tempfile::NamedTempFile,
+        dataset: &tempfile::NamedTempFile,
+        extra: &[&str],
+    ) -> EvaluateArgs {
+        let mut args = args(&[&["--classifier", "competitors"], extra].concat());
+        args.classifiers = classifiers.path().display().to_string();
+        args.dataset = dataset.path().to_path_buf();
+        args
+    }
+
+    #[test]
+    fn test_score() {
+        let result = ClassificationResult::new("toxic", 0.8);
+        assert_eq!(score(&result, None).unwrap(), 0.8);
+
+        let result = ClassificationResult::multi_label(vec![
+            LabelScore::new("toxic", 0.8, 0.5),
+            LabelScore::new("insult", 0.3, 0.5),
+        ]);
+        assert_eq!(score(&result, Some("insult")).unwrap(), 0.3);
+        let err = score(&result, Some("threat")).unwrap_err();
+        assert!(err.to_string().contains("'threat'"));
+    }
+
+    #[test]
+    fn test_calibration_entry() {
+        let calibration = Calibration::Temperature { temperature: 2.0 };
+        let entry = calibration_entry(
+            &args(&["--classifier", "toxicity", "--fit", "temperature"]),
+            Some(&calibration),
+        )
+        .unwrap();
+        assert_eq!(entry["toxicity"], calibration);
+
+        assert!(calibration_entry(&args(&["--classifier", "toxicity"]), None).is_none());
+        assert!(calibration_entry(&args(&["--pipeline", "default"]), Some(&calibration)).is_none());
+    }
+
+    #[test]
+    fn test_split_holdout() {
+        let samples: Vec<(f32, bool)> = (0..20).map(|i| (i as f32, i % 4 == 0)).collect();
+        let (fit, held_out) = split_holdout(samples.clone(), 0.3);
+        assert_eq!((fit.len(), held_out.len()), (13, 7));
+        assert_eq!(held_out.iter().filter(|(_, positive)| *positive).count(), 2);
+
+        let (fit, held_out) = split_holdout(samples, 0.0);
+        assert_eq!((fit.len(), held_out.len()), (20, 0));
+    }
+
+    #[tokio::test]
+    async fn test_evaluate_classifier() {
+        let (classifiers, dataset) = fixtures();
+
+        let report = evaluate(&fixture_args(&classifiers, &dataset, &[]))
+            .await
+            .unwrap();
+        assert_eq!((report.examples, report.positives), (8, 4));
+        assert_eq!(report.fit_examples, None);
+        assert_eq!(report.at_threshold.true_positives, 3);
+        assert_eq!(report.at_threshold.false_positives, 1);
+        assert!(report.calibration.is_none());
+
+        // Fitted calibrations are scored on the held-out half
+        let args = fixture_args(
+            &classifiers,
+            &dataset,
+            &["--fit", "platt", "--holdout", "0.5"],
+        );
+        let report = evaluate(&args).await.unwrap();
+        assert_eq!((report.fit_examples, report.examples), (Some(4), 4));
+        assert!(!report.in_sample);
+        assert!(report.calibration.unwrap().contains_key("competitors"));
+
+        let args = fixture_args(
+            &classifiers,
+            &dataset,
+            &["--fit", "platt", "--holdout", "0"],
+        );
+        let report = evaluate(&args).await.unwrap();
+        assert_eq!((report.fit_examples, report.examples), (Some(8), 8));
+        assert!(report.in_sample);
+
+        let args = fixture_args(
+            &classifiers,
+            &dataset,
+            &["--fit", "platt", "--holdout", "1"],
+        );
+        assert!(evaluate(&args).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_evaluate_rejects_unknown_classifier() {
+        let (classifiers, dataset) = fixtures();
+        let mut args = fixture_args(&classifiers, &dataset, &[]);
+        args.classifier = Some("missing".to_string());
+        let err = evaluate(&args).await.unwrap_err();
+        assert!(err.to_string().contains("'missing' not found"));
+    }
+}
@@ -0,0 +1,568 @@
+//! gRPC service interface
+//!
+//! Exposes the standalone check API and the admin operations over gRPC
+//! (`proto/checkstream.proto`), sharing `AppState`, `TenantResolver` and the
+//! phase functions in `proxy.rs` with the HTTP routes. With TLS configured the
+//! listener reuses the HTTP proxy's certificates, and verified client
+//! certificates map to tenants the same way they do over HTTP.
+//!
+//! Messages and service stubs are generated from the proto file by `build.rs`.
+
+use axum::http::HeaderMap;
+use std::collections::HashMap;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_stream::StreamExt;
+use tonic::{Request, Response, Status, Streaming};
+use tracing::{debug, error, info};
+
+use crate::admin::{find_tenant, reload_tenant_policies};
+use crate::check::{resolve_tenant, StreamCheckSession};
+use crate::proxy::{self, generate_request_id, AppState, TextEvaluation};
+use crate::routes::{apply_modifications, require_admin, AppError};
+use crate::tenant::TenantRuntime;
+use crate::tls::{self, ClientCertIdentity, ReloadableTlsConfig};
+
+mod proto {
+    tonic::include_proto!("checkstream.v1");
+}
+
+pub use proto::check_stream_server::{CheckStream, CheckStreamServer};
+pub use proto::*;
+
+/// Serve the gRPC interface until `shutdown` resolves
+///
+/// With `tls` set, connections are terminated by the same rustls listener as
+/// the HTTP proxy, so certificate reloads and client identities apply here too.
+pub async fn serve(
+    state: AppState,
+    addr: SocketAddr,
+    tls: Option<ReloadableTlsConfig>,
+    shutdown: impl Future<Output = ()>,
+) -> anyhow::Result<()> {
+    let service = CheckStreamServer::new(CheckStreamService::new(state));
+
+    match tls {
+        Some(tls) => {
+            let listener = TcpListener::bind(addr).await?;
+            let router = tonic::service::Routes::new(service).into_axum_router();
+            tls::serve(listener, router, tls, shutdown).await
+        }
+        None => Ok(tonic::transport::Server::builder()
+            .add_service(service)
+            .serve_with_shutdown(addr, shutdown)
+            .await?),
+    }
+}
+
+/// gRPC implementation of the `checkstream.v1.CheckStream` service
+#[derive(Clone)]
+pub struct CheckStreamService {
+    state: AppState,
+}
+
+impl CheckStreamService {
+    /// Create a new service backed by the shared application state
+    pub fn new(state: AppState) -> Self {
+        Self { state }
+    }
+}
+
+#[tonic::async_trait]
+impl CheckStream for CheckStreamService {
+    async fn check(
+        &self,
+        request: Request<CheckRequest>,
+    ) -> Result<Response<CheckResponse>, Status> {
+        let headers = request.metadata().clone().into_headers();
+        let client_cert = client_cert(&request);
+        let req = request.into_inner();
+        let phase = phase_from_proto(req.phase)
+            .ok_or_else(|| Status::invalid_argument(format!("Unknown phase {}", req.phase)))?;
+
+        let tenant = resolve_tenant(
+            &self.state,
+            non_empty(&req.tenant),
+            &headers,
+            "",
+            client_cert.as_ref(),
+        )
+        .map_err(to_status)?;
+        let request_id = generate_request_id();
+        metrics::counter!("checkstream_requests_total", "tenant" => tenant.id.clone()).increment(1);
+
+        let evaluation = proxy::check_text_with_tenant(
+            &self.state,
+            &tenant,
+            phase,
+            &req.text,
+            &req.context,
+            &request_id,
+        )
+        .await
+        .map_err(|e| to_status(e.into()))?;
+
+        Ok(Response::new(check_response(
+            &request_id,
+            &tenant.id,
+            phase,
+            &req.text,
+            &evaluation,
+        )))
+    }
+
+    type CheckStreamStream = ReceiverStream<Result<StreamCheckResponse, Status>>;
+
+    async fn check_stream(
+        &self,
+        request: Request<Streaming<StreamCheckRequest>>,
+    ) -> Result<Response<Self::CheckStreamStream>, Status> {
+        let headers = request.metadata().clone().into_headers();
+        let client_cert = client_cert(&request);
+        // Authenticate up front; also the tenant when `start` names none
+        let default_tenant = resolve_tenant(&self.state, None, &headers, "", client_cert.as_ref())
+            .map_err(to_status)?;
+        let inbound = request.into_inner();
+        let (tx, rx) = mpsc::channel(16);
+
+        tokio::spawn(run_check_stream(
+            self.state.clone(),
+            headers,
+            client_cert,
+            default_tenant,
+            inbound,
+            tx,
+        ));
+
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
+
+    async fn list_tenants(
+        &self,
+        request: Request<ListTenantsRequest>,
+    ) -> Result<Response<ListTenantsResponse>, Status> {
+        require_admin(&request.metadata().clone().into_headers()).map_err(to_status)?;
+
+        let tenants = self
+            .state
+            .tenant_resolver
+            .all_tenants()
+            .iter()
+            .map(|tenant| tenant_info(tenant))
+            .collect();
+
+        Ok(Response::new(ListTenantsResponse { tenants }))
+    }
+
+    async fn list_classifiers(
+        &self,
+        request: Request<ListClassifiersRequest>,
+    ) -> Result<Response<ListClassifiersResponse>, Status> {
+        require_admin(&request.metadata().clone().into_headers()).map_err(to_status)?;
+
+        let registry = &self.state.registry;
+        let classifiers = registry
+            .classifier_names()
+            .into_iter()
+            .filter_map(|name| {
+                let classifier = registry.get(&name)?;
+                Some(ClassifierInfo {
+                    implementation: classifier.name().to_string(),
+                    tier: format!("{:?}", classifier.tier()),
+                    latency_budget_us: classifier.tier().latency_budget_us(),
+                    backend: registry.backend(&name).unwrap_or_default().to_string(),
+                    noop: registry.is_noop(&name),
+                    name,
+                })
+            })
+            .collect();
+
+        Ok(Response::new(ListClassifiersResponse { classifiers }))
+    }
+
+    async fn list_pipelines(
+        &self,
+        request: Request<ListPipelinesRequest>,
+    ) -> Result<Response<ListPipelinesResponse>, Status> {
+        require_admin(&request.metadata().clone().into_headers()).map_err(to_status)?;
+
+        let config = self.state.registry.config();
+        let mut names = config.pipeline_names();
+        names.sort();
+
+        let mut pipelines = Vec::with_capacity(names.len());
+        for name in names {
+            let spec = &config.pipelines[&name];
+            pipelines.push(PipelineInfo {
+                stages_json: serde_json::to_string(&spec.stages)
+                    .map_err(|e| to_status(e.into()))?,
+                description: spec.description.clone(),
+                name,
+            });
+        }
+
+        Ok(Response::new(ListPipelinesResponse { pipelines }))
+    }
+
+    async fn set_rule_enabled(
+        &self,
+        request: Request<SetRuleEnabledRequest>,
+    ) -> Result<Response<SetRuleEnabledResponse>, Status> {
+        require_admin(&request.metadata().clone().into_headers()).map_err(to_status)?;
+        let req = request.into_inner();
+
+        let tenant = find_tenant(&self.state, &req.tenant).map_err(to_status)?;
+        let updated = tenant.policy_engine.write().unwrap().set_rule_enabled(
+            non_empty(&req.policy),
+            &req.rule,
+            req.enabled,
+        );
+
+        if updated == 0 {
+            return Err(Status::not_found(format!(
+                "Rule '{}' not found for tenant '{}'",
+                req.rule, req.tenant
+            )));
+        }
+
+        info!(
+            "Admin (gRPC) set rule '{}' enabled={} for tenant {} ({} rules updated)",
+            req.rule, req.enabled, tenant.id, updated
+        );
+
+        Ok(Response::new(SetRuleEnabledResponse {
+            updated: updated as u32,
+        }))
+    }
+
+    async fn reload_policies(
+        &self,
+        request: Request<ReloadPoliciesRequest>,
+    ) -> Result<Response<ReloadPoliciesResponse>, Status> {
+        require_admin(&request.metadata().clone().into_headers()).map_err(to_status)?;
+        let req = request.into_inner();
+
+        let tenants = match non_empty(&req.tenant) {
+            Some(id) => vec![find_tenant(&self.state, id).map_err(to_status)?],
+            None => self.state.tenant_resolver.all_tenants(),
+        };
+
+        let policies = reload_tenant_policies(&self.state, &tenants)
+            .map_err(to_status)?
+            .into_iter()
+            .map(|(tenant, count)| (tenant, count as u32))
+            .collect();
+
+        Ok(Response::new(ReloadPoliciesResponse { policies }))
+    }
+
+    async fn evaluate(
+        &self,
+        request: Request<EvaluateRequest>,
+    ) -> Result<Response<CheckResponse>, Status> {
+        require_admin(&request.metadata().clone().into_headers()).map_err(to_status)?;
+        let req = request.into_inner();
+        let phase = phase_from_proto(req.phase)
+            .ok_or_else(|| Status::invalid_argument(format!("Unknown phase {}", req.phase)))?;
+
+        let tenant = match non_empty(&req.tenant) {
+            Some(id) => find_tenant(&self.state, id).map_err(to_status)?,
+            None => self.state.tenant_resolver.default_tenant().clone(),
+        };
+
+        let evaluation = proxy::evaluate_text_with_tenant(&tenant, phase, &req.text, &req.context)
+            .await
+            .map_err(|e| to_status(e.into()))?;
+
+        Ok(Response::new(check_response(
+            &generate_request_id(),
+            &tenant.id,
+            phase,
+            &req.text,
+            &evaluation,
+        )))
+    }
+}
+
+type StreamSender = mpsc::Sender<Result<StreamCheckResponse, Status>>;
+
+async fn run_check_stream(
+    state: AppState,
+    headers: HeaderMap,
+    client_cert: Option<ClientCertIdentity>,
+    default_tenant: Arc<TenantRuntime>,
+    mut inbound: Streaming<StreamCheckRequest>,
+    tx: StreamSender,
+) {
+    let request_id = generate_request_id();
+    // Created on `start`, or on the first chunk for the default tenant
+    let mut session: Option<StreamCheckSession> = None;
+    let new_session =
+        |tenant, context| StreamCheckSession::new(tenant, context, request_id.clone());
+
+    while let Some(message) = inbound.next().await {
+        let event = match message {
+            Ok(StreamCheckRequest { event: Some(event) }) => event,
+            Ok(StreamCheckRequest { event: None }) => {
+                let _ = tx
+                    .send(Err(Status::invalid_argument("Stream message has no event")))
+                    .await;
+                return;
+            }
+            Err(status) => {
+                debug!(
+                    "gRPC check stream ended: {} (request_id: {})",
+                    status, request_id
+                );
+                return;
+            }
+        };
+
+        match event {
+            stream_check_request::Event::Start(start) => {
+                if session.is_some() {
+                    let _ = tx
+                        .send(Err(Status::failed_precondition(
+                            "Stream has already started",
+                        )))
+                        .await;
+                    return;
+                }
+                match resolve_tenant(
+                    &state,
+                    non_empty(&start.tenant),
+                    &headers,
+                    "",
+                    client_cert.as_ref(),
+                ) {
+                    Ok(tenant) => session = Some(new_session(tenant, start.context)),
+                    Err(e) => {
+                        let _ = tx.send(Err(to_status(e))).await;
+                        return;
+                    }
+                }
+            }
+
+            stream_check_request::Event::Chunk(chunk) => {
+                let session = session
+                    .get_or_insert_with(|| new_session(default_tenant.clone(), HashMap::new()));
+                let check = match session.check_chunk(&state, chunk).await {
+                    Ok(check) => check,
+                    Err(e) => {
+                        let _ = tx.send(Err(to_status(e))).await;
+                        return;
+                    }
+                };
+
+                let outcome = &check.result.action_outcome;
+                let verdict = ChunkVerdict {
+                    index: check.index,
+                    redacted: check.result.redacted,
+                    should_stop: outcome.should_stop,
+                    stop_message: outcome.stop_message.clone().unwrap_or_default(),
+                    releasable_text: check.releasable_text,
+                    scores: check.result.classifier_scores,
+                };
+
+                let stop = verdict.should_stop;
+                let response = StreamCheckResponse {
+                    event: Some(stream_check_response::Event::Verdict(verdict)),
+                };
+                if tx.send(Ok(response)).await.is_err() || stop {
+                    return;
+                }
+            }
+
+            stream_check_request::Event::End(_) => {
+                let session = session
+                    .get_or_insert_with(|| new_session(default_tenant.clone(), HashMap::new()));
+                let evaluation = match session.finish(&state).await {
+                    Ok(evaluation) => evaluation,
+                    Err(e) => {
+                        let _ = tx.send(Err(to_status(e))).await;
+                        return;
+                    }
+                };
+
+                let response = StreamCheckResponse {
+                    event: Some(stream_check_response::Event::Final(check_response(
+                        &request_id,
+                        &session.tenant.id,
+                        proxy::Phase::Egress,
+                        session.full_text(),
+                        &evaluation,
+                    ))),
+                };
+                let _ = tx.send(Ok(response)).await;
+                return;
+            }
+        }
+    }
+}
+
+fn check_response(
+    request_id: &str,
+    tenant_id: &str,
+    phase: proxy::Phase,
+    text: &str,
+    evaluation: &TextEvaluation,
+) -> CheckResponse {
+    let classifiers = evaluation
+        .pipeline_result
+        .results
+        .iter()
+        .map(|r| ClassifierResult {
+            stage: r.stage_name.clone(),
+            classifier: r.classifier_name.clone(),
+            label: r.result.label.clone(),
+            score: r.result.score,
+            latency_us: r.result.latency_us,
+        })
+        .collect();
+
+    let triggered_rules = evaluation
+        .policy_results
+        .iter()
+        .map(|r| TriggeredRule {
+            policy: r.policy_name.clone(),
+            rule: r.rule_name.clone(),
+            score: r.score,
+            matched_content: r.metadata.matched_content.clone(),
+        })
+        .collect();
+
+    let outcome = &evaluation.action_outcome;
+    let modified_text = if outcome.modifications.is_empty() {
+        None
+    } else {
+        Some(apply_modifications(text, &outcome.modifications))
+    };
+
+    CheckResponse {
+        request_id: request_id.to_string(),
+        tenant: tenant_id.to_string(),
+        phase: phase_to_proto(phase) as i32,
+        blocked: evaluation.blocked,
+        classifiers,
+        scores: evaluation.classifier_scores.clone(),
+        triggered_rules,
+        outcome: Some(ActionOutcome {
+            should_stop: outcome.should_stop,
+            stop_status: outcome.stop_status.map(u32::from).unwrap_or_default(),
+            stop_message: outcome.stop_message.clone().unwrap_or_default(),
+            modifications: outcome.modifications.len() as u32,
+            audit_records: outcome.audit_records.len() as u32,
+        }),
+        modified_text,
+        total_latency_us: evaluation.pipeline_result.total_latency_us,
+    }
+}
+
+fn tenant_info(tenant: &TenantRuntime) -> TenantInfo {
+    let engine = tenant.policy_engine.read().unwrap();
+    let policies = engine
+        .policies()
+        .iter()
+        .map(|policy| PolicyInfo {
+            name: policy.name.clone(),
+            version: policy.version.clone(),
+            rules: policy
+                .rules
+                .iter()
+                .map(|rule| RuleInfo {
+                    name: rule.name.clone(),
+                    enabled: rule.enabled,
+                })
+                .collect(),
+        })
+        .collect();
+
+    let settings = &tenant.pipeline_settings;
+    TenantInfo {
+        id: tenant.id.clone(),
+        name: tenant.name.clone(),
+        ingress_pipeline: settings.ingress_pipeline.clone(),
+        midstream_pipeline: settings.midstream_pipeline.clone(),
+        egress_pipeline: settings.egress_pipeline.clone(),
+        policies,
+    }
+}
+
+fn phase_from_proto(value: i32) -> Option<proxy::Phase> {
+    match Phase::try_from(value).ok()? {
+        Phase::Ingress => Some(proxy::Phase::Ingress),
+        Phase::Midstream => Some(proxy::Phase::Midstream),
+        Phase::Egress => Some(proxy::Phase::Egress),
+    }
+}
+
+fn phase_to_proto(phase: proxy::Phase) -> Phase {
+    match phase {
+        proxy::Phase::Ingress => Phase::Ingress,
+        proxy::Phase::Midstream => Phase::Midstream,
+        proxy::Phase::Egress => Phase::Egress,
+    }
+}
+
+/// Identity of the verified client certificate on an mTLS connection
+fn client_cert<T>(request: &Request<T>) -> Option<ClientCertIdentity> {
+    request.extensions().get::<ClientCertIdentity>().cloned()
+}
+
+/// Proto3 strings default to empty; treat empty as unset
+fn non_empty(value: &str) -> Option<&str> {
+    (!value.is_empty()).then_some(value)
+}
+
+fn to_status(err: AppError) -> Status {
+    match err {
+        AppError::InvalidRequest(msg) => Status::invalid_argument(msg),
+        AppError::Unauthorized(msg) => Status::unauthenticated(msg),
+        AppError::Forbidden(msg) => Status::permission_denied(msg),
+        AppError::NotFound(msg) => Status::not_found(msg),
+        AppError::BackendError(status) => Status::unavailable(format!("Backend error: {}", status)),
+        AppError::InternalError(msg) => {
+            error!("Internal error: {}", msg);
+            Status::internal("Internal server error")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prost::Message;
+
+    #[test]
+    fn test_phase_conversion() {
+        assert_eq!(phase_from_proto(0), Some(proxy::Phase::Ingress));
+        assert_eq!(phase_from_proto(2), Some(proxy::Phase::Egress));
+        assert_eq!(phase_from_proto(7), None);
+        assert_eq!(phase_to_proto(proxy::Phase::Midstream) as i32, 1);
+    }
+
+    #[test]
+    fn test_client_cert_from_extensions() {
+        let identity = ClientCertIdentity {
+            names: vec!["tenant-a.clients.example.com".to_string()],
+        };
+        let mut request = Request::new(ListTenantsRequest {});
+        assert_eq!(client_cert(&request), None);
+
+        request.extensions_mut().insert(identity.clone());
+        assert_eq!(client_cert(&request), Some(identity));
+    }
+
+    #[test]
+    fn test_stream_request_roundtrip() {
+        let request = StreamCheckRequest {
+            event: Some(stream_check_request::Event::Chunk("Hello".to_string())),
+        };
+        let decoded = StreamCheckRequest::decode(request.encode_to_vec().as_slice()).unwrap();
+        assert_eq!(decoded, request);
+    }
+}
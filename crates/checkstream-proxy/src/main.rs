@@ -11,13 +11,22 @@ use metrics_exporter_prometheus::PrometheusHandle;
 use std::net::SocketAddr;
 use std::sync::atomic::{AtomicBool, Ordering};
 use tokio::signal;
-use tracing::{info, warn};
+use tracing::{error, info, warn};
 
+mod admin;
+mod auth;
+mod check;
 mod config;
+mod evaluate;
+mod grpc;
 mod proxy;
+mod pseudonym;
 mod routes;
 mod security;
 mod tenant;
+#[cfg(test)]
+mod test_support;
+mod tls;
 
 use config::MultiTenantConfig;
 pub use tenant::{TenantResolver, TenantRuntime};
@@ -54,17 +63,44 @@ struct Cli {
     #[arg(short = 'P', long, default_value = "8080")]
     port: u16,
 
+    /// TLS certificate chain (PEM); enables TLS together with --tls-key
+    #[arg(long)]
+    tls_cert: Option<String>,
+
+    /// TLS private key (PEM)
+    #[arg(long)]
+    tls_key: Option<String>,
+
+    /// gRPC listen port (gRPC interface disabled if omitted)
+    #[arg(long)]
+    grpc_port: Option<u16>,
+
     /// Enable verbose logging
     #[arg(short, long)]
     verbose: bool,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(clap::Subcommand, Debug)]
+enum Command {
+    /// Score a labelled dataset and recommend thresholds or calibrations
+    Evaluate(evaluate::EvaluateArgs),
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
     let cli = Cli::parse();
 
+    if let Some(Command::Evaluate(args)) = cli.command {
+        // Reports go to stdout, so keep logs on stderr
+        init_tracing(cli.verbose, true);
+        return evaluate::run(args).await;
+    }
+
     // Initialize tracing
-    init_tracing(cli.verbose);
+    init_tracing(cli.verbose, false);
 
     info!("Starting CheckStream Proxy");
     info!("Built with Rust for maximum performance");
@@ -80,6 +116,14 @@ async fn main() -> Result<()> {
     // Initialize metrics
     let metrics_handle = init_metrics()?;
 
+    // Load TLS certificates up front so misconfiguration fails fast
+    let tls_config = config
+        .default
+        .tls
+        .clone()
+        .map(tls::ReloadableTlsConfig::new)
+        .transpose()?;
+
     // Initialize application state (load classifiers and build pipelines)
     info!("Initializing application state...");
     let state = proxy::AppState::new_multi_tenant(config, metrics_handle).await?;
@@ -89,22 +133,53 @@ async fn main() -> Result<()> {
     let addr: SocketAddr = format!("{}:{}", cli.listen, cli.port).parse()?;
     info!("Starting proxy server on {}", addr);
 
+    // Shutdown broadcast shared by the HTTP and gRPC servers
+    let (shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+
+    // Start the gRPC interface alongside the HTTP proxy
+    if let Some(grpc_port) = cli.grpc_port {
+        let grpc_addr: SocketAddr = format!("{}:{}", cli.listen, grpc_port).parse()?;
+        let grpc_state = state.clone();
+        let grpc_tls = tls_config.clone();
+        let mut grpc_shutdown = shutdown_rx.clone();
+        info!("gRPC listening on {}", grpc_addr);
+        tokio::spawn(async move {
+            let shutdown = async move {
+                let _ = grpc_shutdown.wait_for(|stop| *stop).await;
+            };
+            if let Err(e) = grpc::serve(grpc_state, grpc_addr, grpc_tls, shutdown).await {
+                error!("gRPC server error: {}", e);
+            }
+        });
+    }
+    drop(shutdown_rx);
+
     // Build and run the server with graceful shutdown
     let app = routes::create_router(state);
 
     let listener = tokio::net::TcpListener::bind(addr).await?;
-    info!("Proxy listening on http://{}", addr);
 
     // Graceful shutdown handler
-    let shutdown = async {
+    let shutdown = async move {
         shutdown_signal().await;
         SHUTDOWN.store(true, Ordering::SeqCst);
+        let _ = shutdown_tx.send(true);
         warn!("Shutdown signal received, stopping server...");
     };
 
-    axum::serve(listener, app)
-        .with_graceful_shutdown(shutdown)
-        .await?;
+    match tls_config {
+        Some(tls_config) => {
+            info!("Proxy listening on https://{}", addr);
+            tls_config.spawn_watcher();
+            tls::serve(listener, app, tls_config, shutdown).await?;
+        }
+        None => {
+            info!("Proxy listening on http://{}", addr);
+            axum::serve(listener, app)
+                .with_graceful_shutdown(shutdown)
+                .await?;
+        }
+    }
 
     info!("Server shutdown complete");
     Ok(())
@@ -136,7 +211,8 @@ async fn shutdown_signal() {
 }
 
 /// Initialize tracing/logging
-fn init_tracing(verbose: bool) {
+fn init_tracing(verbose: bool, to_stderr: bool) {
+    use tracing_subscriber::fmt::writer::BoxMakeWriter;
     use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
 
     let filter = if verbose {
@@ -147,7 +223,11 @@ fn init_tracing(verbose: bool) {
 
     tracing_subscriber::registry()
         .with(filter)
-        .with(tracing_subscriber::fmt::layer())
+        .with(tracing_subscriber::fmt::layer().with_writer(if to_stderr {
+            BoxMakeWriter::new(std::io::stderr)
+        } else {
+            BoxMakeWriter::new(std::io::stdout)
+        }))
         .init();
 }
 
@@ -175,6 +255,22 @@ fn init_metrics() -> Result<PrometheusHandle> {
         "Pipeline execution latency in microseconds by phase"
     );
     metrics::describe_counter!("checkstream_errors_total", "Total number of errors by type");
+    metrics::describe_counter!(
+        "checkstream_classifier_failures_total",
+        "Classifiers that timed out or errored by phase, classifier and reason"
+    );
+    metrics::describe_gauge!(
+        "checkstream_pipeline_noop_stages",
+        "No-op classifiers in each tenant's active pipelines by phase"
+    );
+    metrics::describe_gauge!(
+        "checkstream_classifier_batch_queue_depth",
+        "Requests waiting to join a micro-batch by classifier"
+    );
+    metrics::describe_histogram!(
+        "checkstream_classifier_batch_size",
+        "Requests per batched model forward pass by classifier"
+    );
 
     info!("Metrics exporter initialized");
     Ok(handle)
@@ -0,0 +1,526 @@
+//! Reversible PII pseudonymization
+//!
+//! On ingress, detected PII is swapped for stable placeholders such as
+//! `<EMAIL_1>` before the prompt is forwarded to the backend. The mapping
+//! lives in a per-request [`PseudonymVault`] and the originals are put back
+//! into the response on egress. For streamed responses, [`StreamRestorer`]
+//! holds back text that could be the start of a placeholder so that one split
+//! across chunks is still restored.
+
+use checkstream_classifiers::Span;
+use checkstream_core::{ParsedChunk, StreamAdapter};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Per-request mapping between original PII values and their placeholders
+#[derive(Debug, Default)]
+pub struct PseudonymVault {
+    /// Original value -> placeholder
+    placeholders: HashMap<String, String>,
+
+    /// Placeholder -> original value
+    originals: HashMap<String, String>,
+
+    /// Next placeholder number per entity label
+    counters: HashMap<String, usize>,
+}
+
+impl PseudonymVault {
+    /// Create an empty vault
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of distinct values held
+    pub fn len(&self) -> usize {
+        self.originals.len()
+    }
+
+    /// Whether no value has been pseudonymized
+    pub fn is_empty(&self) -> bool {
+        self.originals.is_empty()
+    }
+
+    /// Replace PII spans in `text` with placeholders
+    ///
+    /// `entities` limits which span labels are replaced; an empty list
+    /// replaces every span. The same value always maps to the same
+    /// placeholder within a vault.
+    pub fn pseudonymize(&mut self, text: &str, spans: &[Span], entities: &[String]) -> String {
+        let mut selected: Vec<&Span> = spans
+            .iter()
+            .filter(|span| entities.is_empty() || entities.contains(&span.label))
+            .filter(|span| span.start < span.end && text.get(span.start..span.end).is_some())
+            .collect();
+        selected.sort_by_key(|span| span.start);
+
+        let mut output = String::with_capacity(text.len());
+        let mut cursor = 0;
+        for span in selected {
+            if span.start < cursor {
+                continue;
+            }
+            output.push_str(&text[cursor..span.start]);
+            output.push_str(&self.placeholder_for(&span.label, &text[span.start..span.end]));
+            cursor = span.end;
+        }
+        output.push_str(&text[cursor..]);
+        output
+    }
+
+    /// Put the original values back in place of any placeholders in `text`
+    pub fn restore(&self, text: &str) -> String {
+        let mut output = text.to_string();
+        for (placeholder, original) in &self.originals {
+            if output.contains(placeholder.as_str()) {
+                output = output.replace(placeholder.as_str(), original);
+            }
+        }
+        output
+    }
+
+    /// Length of the longest suffix of `text` that could still grow into a placeholder
+    fn pending_prefix_len(&self, text: &str) -> usize {
+        let Some(start) = text.rfind('<') else {
+            return 0;
+        };
+        let tail = &text[start..];
+        if self
+            .originals
+            .keys()
+            .any(|placeholder| placeholder.len() > tail.len() && placeholder.starts_with(tail))
+        {
+            tail.len()
+        } else {
+            0
+        }
+    }
+
+    fn placeholder_for(&mut self, label: &str, original: &str) -> String {
+        if let Some(existing) = self.placeholders.get(original) {
+            return existing.clone();
+        }
+
+        let counter = self.counters.entry(label.to_string()).or_insert(0);
+        *counter += 1;
+        let placeholder = format!("<{}_{}>", label.to_uppercase(), counter);
+
+        self.placeholders
+            .insert(original.to_string(), placeholder.clone());
+        self.originals
+            .insert(placeholder.clone(), original.to_string());
+        placeholder
+    }
+}
+
+/// Restores placeholders in streamed content, including across chunk boundaries
+#[derive(Debug)]
+pub struct StreamRestorer {
+    vault: Arc<PseudonymVault>,
+    pending: String,
+    last_event: Option<String>,
+    template: Option<ContentTemplate>,
+}
+
+impl StreamRestorer {
+    /// Create a restorer backed by a request's vault
+    pub fn new(vault: Arc<PseudonymVault>) -> Self {
+        Self {
+            vault,
+            pending: String::new(),
+            last_event: None,
+            template: None,
+        }
+    }
+
+    /// Feed the next piece of content and return the text that is safe to emit
+    ///
+    /// A trailing partial placeholder is held back until the next call.
+    pub fn push(&mut self, content: &str) -> String {
+        self.pending.push_str(content);
+        let hold = self.vault.pending_prefix_len(&self.pending);
+        let ready: String = self.pending.drain(..self.pending.len() - hold).collect();
+        self.vault.restore(&ready)
+    }
+
+    /// Return whatever is still held back
+    pub fn finish(&mut self) -> String {
+        let rest = std::mem::take(&mut self.pending);
+        self.vault.restore(&rest)
+    }
+
+    /// Rewrite a raw backend chunk so its content carries restored values
+    ///
+    /// Each line is parsed with the tenant's adapter and content lines are
+    /// re-serialized with the restored text in place of the original value.
+    /// A line whose content cannot be found in it is replaced by a synthesized
+    /// content event; failing that it is passed on as is and nothing is held
+    /// back from it. Text still held back when the stream finishes is emitted
+    /// as a synthesized content event just before the done event.
+    pub fn rewrite_chunk(&mut self, raw: &str, adapter: &dyn StreamAdapter) -> String {
+        let mut output = String::with_capacity(raw.len());
+
+        for line in raw.split_inclusive('\n') {
+            let trimmed = line.trim();
+            if trimmed.starts_with("event:") {
+                self.last_event = Some(trimmed.to_string());
+            }
+
+            let mut rewritten: Option<String> = None;
+            for chunk in adapter.parse(line) {
+                match chunk {
+                    ParsedChunk::Content { text, .. } => {
+                        let restored = self.push(&text);
+                        if restored != text {
+                            rewritten = self
+                                .rewrite_line(line, &text, &restored)
+                                .or_else(|| self.content_event(restored));
+                            if rewritten.is_none() {
+                                // The raw line carries all of the text already
+                                self.pending.clear();
+                            }
+                        }
+                    }
+                    ParsedChunk::Done { .. } => {
+                        if let Some(event) = self.flush() {
+                            output.push_str(&event);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+
+            output.push_str(rewritten.as_deref().unwrap_or(line));
+        }
+
+        output
+    }
+
+    /// Emit whatever is still held back as a synthesized content event
+    ///
+    /// Used when the stream ends, with or without a done event.
+    pub fn flush(&mut self) -> Option<String> {
+        let rest = self.finish();
+        if rest.is_empty() {
+            return None;
+        }
+        self.content_event(rest)
+    }
+
+    /// A content event shaped like the last rewritten one, carrying `text`
+    fn content_event(&self, text: String) -> Option<String> {
+        let template = self.template.as_ref()?;
+
+        let mut value = template.value.clone();
+        *value.pointer_mut(&template.pointer)? = Value::String(text);
+        let body = serde_json::to_string(&value).ok()?;
+
+        let mut event = String::new();
+        if let Some(event_line) = &template.event {
+            event.push_str(event_line);
+            event.push('\n');
+        }
+        event.push_str(&template.prefix);
+        event.push_str(&body);
+        event.push_str(if template.prefix.is_empty() {
+            "\n"
+        } else {
+            "\n\n"
+        });
+        Some(event)
+    }
+
+    /// Re-serialize a content line with `original` replaced by `restored`
+    fn rewrite_line(&mut self, line: &str, original: &str, restored: &str) -> Option<String> {
+        let unindented = line.trim_start();
+        let after_prefix = unindented
+            .strip_prefix("data:")
+            .map(str::trim_start)
+            .unwrap_or(unindented);
+        let body_start = line.len() - after_prefix.len();
+        let body = after_prefix.trim_end();
+        let body_end = body_start + body.len();
+
+        let Ok(mut value) = serde_json::from_str::<Value>(body) else {
+            // Plain-text body: the content appears in it verbatim
+            return body.contains(original).then(|| {
+                format!(
+                    "{}{}{}",
+                    &line[..body_start],
+                    body.replacen(original, restored, 1),
+                    &line[body_end..]
+                )
+            });
+        };
+        let pointer = find_string(&value, original)?;
+        *value.pointer_mut(&pointer)? = Value::String(restored.to_string());
+        let encoded = serde_json::to_string(&value).ok()?;
+
+        self.template = Some(ContentTemplate {
+            event: self.last_event.clone(),
+            prefix: line[..body_start].trim_start().to_string(),
+            value,
+            pointer,
+        });
+
+        Some(format!(
+            "{}{}{}",
+            &line[..body_start],
+            encoded,
+            &line[body_end..]
+        ))
+    }
+}
+
+/// Shape of the last content event, used to synthesize one for flushed text
+#[derive(Debug)]
+struct ContentTemplate {
+    /// SSE `event:` line that preceded the data line, if any
+    event: Option<String>,
+
+    /// Text before the JSON body, e.g. `data: `
+    prefix: String,
+
+    /// Parsed JSON body
+    value: Value,
+
+    /// JSON pointer to the content string within `value`
+    pointer: String,
+}
+
+/// JSON pointer to the first string in `value` equal to `text`
+fn find_string(value: &Value, text: &str) -> Option<String> {
+    match value {
+        Value::String(s) if s == text => Some(String::new()),
+        Value::Array(items) => items
+            .iter()
+            .enumerate()
+            .find_map(|(i, item)| find_string(item, text).map(|rest| format!("/{}{}", i, rest))),
+        Value::Object(map) => map.iter().find_map(|(key, item)| {
+            find_string(item, text)
+                .map(|rest| format!("/{}{}", key.replace('~', "~0").replace('/', "~1"), rest))
+        }),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use checkstream_core::OpenAiAdapter;
+
+    /// Reads `data:` lines as JSON with the content in `text` or split across
+    /// `parts`, or as plain text when they are not JSON
+    #[derive(Debug)]
+    struct PartsAdapter;
+
+    impl StreamAdapter for PartsAdapter {
+        fn name(&self) -> &str {
+            "parts"
+        }
+
+        fn parse(&self, data: &str) -> Vec<ParsedChunk> {
+            data.lines()
+                .filter_map(|line| line.trim().strip_prefix("data:"))
+                .map(|body| match serde_json::from_str::<Value>(body.trim()) {
+                    Ok(value) => match (value["text"].as_str(), value["parts"].as_array()) {
+                        (Some(text), _) => ParsedChunk::content(text),
+                        (_, Some(parts)) => ParsedChunk::content(
+                            parts.iter().filter_map(Value::as_str).collect::<String>(),
+                        ),
+                        _ => ParsedChunk::Empty,
+                    },
+                    Err(_) => ParsedChunk::content(body.trim()),
+                })
+                .collect()
+        }
+
+        fn is_done_marker(&self, _data: &str) -> bool {
+            false
+        }
+    }
+
+    /// Content of every event in `output`
+    fn content(output: &str) -> String {
+        PartsAdapter
+            .parse(output)
+            .iter()
+            .filter_map(|chunk| chunk.text())
+            .collect()
+    }
+
+    fn vault_with_email() -> (PseudonymVault, String) {
+        let text = "Email john@example.com or john@example.com";
+        let spans = vec![
+            Span::new(6, 22, "email", 0.95),
+            Span::new(26, 42, "email", 0.95),
+        ];
+        let mut vault = PseudonymVault::new();
+        let masked = vault.pseudonymize(text, &spans, &[]);
+        (vault, masked)
+    }
+
+    #[test]
+    fn test_pseudonymize_and_restore() {
+        let (vault, masked) = vault_with_email();
+
+        assert_eq!(masked, "Email <EMAIL_1> or <EMAIL_1>");
+        assert_eq!(vault.len(), 1);
+        assert_eq!(
+            vault.restore("Sent to <EMAIL_1>."),
+            "Sent to john@example.com."
+        );
+    }
+
+    #[test]
+    fn test_entity_filter() {
+        let text = "john@example.com 555-123-4567";
+        let spans = vec![
+            Span::new(0, 16, "email", 0.95),
+            Span::new(17, 29, "phone", 0.6),
+        ];
+        let mut vault = PseudonymVault::new();
+
+        let masked = vault.pseudonymize(text, &spans, &["phone".to_string()]);
+        assert_eq!(masked, "john@example.com <PHONE_1>");
+    }
+
+    #[test]
+    fn test_stream_restore_across_chunks() {
+        let (vault, _) = vault_with_email();
+        let mut restorer = StreamRestorer::new(Arc::new(vault));
+
+        let mut output = String::new();
+        for piece in ["Hi <EM", "AIL", "_1> there", " <b>ok</b>"] {
+            output.push_str(&restorer.push(piece));
+        }
+        output.push_str(&restorer.finish());
+
+        assert_eq!(output, "Hi john@example.com there <b>ok</b>");
+    }
+
+    #[test]
+    fn test_rewrite_raw_chunks() {
+        let (vault, _) = vault_with_email();
+        let mut restorer = StreamRestorer::new(Arc::new(vault));
+        let adapter = OpenAiAdapter::new();
+
+        let first = "data: {\"choices\":[{\"delta\":{\"content\":\"Mail <EMA\"}}]}\n\n";
+        let second = "data: {\"choices\":[{\"delta\":{\"content\":\"IL_1>\"}}]}\n\n";
+
+        let out1 = restorer.rewrite_chunk(first, &adapter);
+        let out2 = restorer.rewrite_chunk(second, &adapter);
+
+        assert_eq!(
+            out1,
+            "data: {\"choices\":[{\"delta\":{\"content\":\"Mail \"}}]}\n\n"
+        );
+        assert_eq!(
+            out2,
+            "data: {\"choices\":[{\"delta\":{\"content\":\"john@example.com\"}}]}\n\n"
+        );
+    }
+
+    #[test]
+    fn test_held_back_text_flushed_before_done() {
+        let (vault, _) = vault_with_email();
+        let mut restorer = StreamRestorer::new(Arc::new(vault));
+        let adapter = OpenAiAdapter::new();
+
+        let content = "data: {\"choices\":[{\"delta\":{\"content\":\"Mail <EMAIL_\"}}]}\n\n";
+        let finish = "data: {\"choices\":[{\"delta\":{},\"finish_reason\":\"stop\"}]}\n\n";
+
+        let mut output = restorer.rewrite_chunk(content, &adapter);
+        output.push_str(&restorer.rewrite_chunk(finish, &adapter));
+        output.push_str(&restorer.rewrite_chunk("data: [DONE]\n\n", &adapter));
+
+        let text: String = adapter
+            .parse(&output)
+            .iter()
+            .filter_map(|chunk| chunk.text())
+            .collect();
+        assert_eq!(text, "Mail <EMAIL_");
+        assert!(output.find("<EMAIL_").unwrap() < output.find("finish_reason").unwrap());
+    }
+
+    #[test]
+    fn test_held_back_text_flushed_without_done() {
+        let (vault, _) = vault_with_email();
+        let mut restorer = StreamRestorer::new(Arc::new(vault));
+        let adapter = OpenAiAdapter::new();
+
+        let content = "data: {\"choices\":[{\"delta\":{\"content\":\"see <EM\"}}]}\n\n";
+        let mut output = restorer.rewrite_chunk(content, &adapter);
+        output.push_str(&restorer.flush().unwrap());
+
+        let text: String = adapter
+            .parse(&output)
+            .iter()
+            .filter_map(|chunk| chunk.text())
+            .collect();
+        assert_eq!(text, "see <EM");
+        assert!(restorer.flush().is_none());
+    }
+
+    #[test]
+    fn test_rewrite_escaped_content() {
+        let (vault, _) = vault_with_email();
+        let mut restorer = StreamRestorer::new(Arc::new(vault));
+        let adapter = OpenAiAdapter::new();
+
+        let raw = "data: {\"choices\":[{\"delta\":{\"content\":\"Caf\\u00e9 <EMAIL_1>\"}}]}\n\n";
+        let output = restorer.rewrite_chunk(raw, &adapter);
+
+        let parsed = adapter.parse(&output);
+        assert_eq!(parsed[0].text(), Some("Café john@example.com"));
+        assert!(!output.contains("<EMAIL_1>"));
+    }
+
+    #[test]
+    fn test_rewrite_plain_text_lines() {
+        let (vault, _) = vault_with_email();
+        let mut restorer = StreamRestorer::new(Arc::new(vault));
+
+        let mut output = restorer.rewrite_chunk("data: Mail <EMA\n\n", &PartsAdapter);
+        output.push_str(&restorer.rewrite_chunk("data: IL_1> today\n\n", &PartsAdapter));
+
+        assert_eq!(output, "data: Mail \n\ndata: john@example.com today\n\n");
+        assert!(restorer.flush().is_none());
+    }
+
+    #[test]
+    fn test_rewrite_line_without_matching_string() {
+        let (vault, _) = vault_with_email();
+        let mut restorer = StreamRestorer::new(Arc::new(vault));
+
+        // Content split across fields is sent as an event like the last one
+        let mut output =
+            restorer.rewrite_chunk("data: {\"text\":\"Hi <EMAIL_1>\"}\n", &PartsAdapter);
+        output.push_str(&restorer.rewrite_chunk(
+            "data: {\"parts\":[\"Mail \",\"<EMAIL_1>, <EMA\"]}\n",
+            &PartsAdapter,
+        ));
+        output.push_str(&restorer.rewrite_chunk("data: {\"text\":\"IL_1>\"}\n", &PartsAdapter));
+
+        assert_eq!(
+            content(&output),
+            "Hi john@example.comMail john@example.com, john@example.com"
+        );
+        assert!(restorer.flush().is_none());
+    }
+
+    #[test]
+    fn test_unrewritable_line_holds_nothing_back() {
+        let (vault, _) = vault_with_email();
+        let mut restorer = StreamRestorer::new(Arc::new(vault));
+
+        // Without an earlier content event to copy, the line passes as is
+        let raw = "data: {\"parts\":[\"Mail \",\"<EMA\"]}\n";
+        let output = restorer.rewrite_chunk(raw, &PartsAdapter);
+
+        assert_eq!(output, raw);
+        assert!(restorer.flush().is_none());
+        assert_eq!(restorer.finish(), "");
+    }
+}
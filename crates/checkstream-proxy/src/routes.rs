@@ -1,7 +1,7 @@
 //! HTTP routes and handlers
 
 use axum::{
-    extract::{DefaultBodyLimit, Query, State},
+    extract::{DefaultBodyLimit, Extension, Query, State},
     http::{HeaderMap, HeaderName, HeaderValue, StatusCode},
     response::{IntoResponse, Response},
     routing::{get, post},
@@ -11,7 +11,7 @@ use subtle::ConstantTimeEq;
 use tower_http::set_header::SetResponseHeaderLayer;
 use checkstream_policy::action::InjectPosition;
 use checkstream_policy::executor::{ActionOutcome, ModificationKind, TextModification};
-use checkstream_telemetry::{AuditQuery as TelemetryAuditQuery, AuditSeverity};
+use checkstream_telemetry::{AuditQuery as TelemetryAuditQuery, AuditSeverity, RequestContext};
 use futures_util::stream::StreamExt;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
@@ -20,14 +20,19 @@ use std::sync::{Arc, OnceLock};
 use tokio::sync::Mutex;
 use tracing::{debug, error, info, warn};
 
+use crate::auth::{AuthError, VirtualKey};
+use crate::config::KeyScope;
+use crate::{admin, check};
 use crate::proxy::{self, generate_request_id, AppState};
+use crate::pseudonym::{PseudonymVault, StreamRestorer};
 use crate::tenant::TenantRuntime;
+use crate::tls::ClientCertIdentity;
 use axum::extract::Path;
-use checkstream_classifiers::{StreamingConfig, StreamingPipeline};
+use checkstream_classifiers::StreamingPipeline;
 use checkstream_core::ParsedChunk;
 
 /// Maximum request body size (10 MB)
-const MAX_BODY_SIZE: usize = 10 * 1024 * 1024;
+pub(crate) const MAX_BODY_SIZE: usize = 10 * 1024 * 1024;
 
 pub fn create_router(state: AppState) -> Router {
     Router::new()
@@ -46,8 +51,12 @@ pub fn create_router(state: AppState) -> Router {
         // Audit endpoints
         .route("/audit", get(audit_query))
         .route("/audit/stats", get(audit_stats))
+        // Standalone check API (no backend call)
+        .merge(check::router())
         // Tenant info endpoint
         .route("/tenants", get(list_tenants))
+        // Admin inspection and management endpoints
+        .merge(admin::router())
         .fallback(fallback)
         // Security: Request body size limit to prevent memory exhaustion
         .layer(DefaultBodyLimit::max(MAX_BODY_SIZE))
@@ -107,8 +116,27 @@ async fn readiness_check(
     // Check if policy engine has policies (optional - may be valid with no policies)
     let policy_count = state.policy_engine.read().unwrap().policies().len();
 
+    // Flag active pipelines that silently score 0 through a no-op classifier
+    let noop_stages: Vec<serde_json::Value> = state
+        .tenant_resolver
+        .all_tenants()
+        .iter()
+        .flat_map(|tenant| {
+            tenant.noop_stages.iter().map(|stage| {
+                json!({
+                    "tenant": tenant.id,
+                    "phase": stage.phase,
+                    "pipeline": stage.pipeline,
+                    "classifier": stage.classifier,
+                })
+            })
+        })
+        .collect();
+
     Ok(Json(json!({
         "status": "ready",
+        "degraded": !noop_stages.is_empty(),
+        "noop_stages": noop_stages,
         "components": {
             "classifiers": classifier_count,
             "policies": policy_count,
@@ -143,7 +171,7 @@ fn constant_time_eq(a: &str, b: &str) -> bool {
     a.as_bytes().ct_eq(b.as_bytes()).into()
 }
 
-fn require_admin(headers: &HeaderMap) -> Result<(), AppError> {
+pub(crate) fn require_admin(headers: &HeaderMap) -> Result<(), AppError> {
     let expected = admin_api_key()
         .ok_or_else(|| AppError::Forbidden("Admin API key is not configured".to_string()))?;
 
@@ -312,17 +340,29 @@ struct Usage {
     total_tokens: u32,
 }
 
-/// Main chat completions handler (uses tenant from header or API key, falls back to default)
+/// Main chat completions handler (uses tenant from virtual key, client certificate, header or API key, falls back to default)
 async fn chat_completions(
     State(state): State<AppState>,
+    client_cert: Option<Extension<ClientCertIdentity>>,
     headers: HeaderMap,
     Json(req): Json<ChatCompletionRequest>,
 ) -> Result<Response, AppError> {
-    // Resolve tenant from headers/API key
-    let tenant = state
-        .tenant_resolver
-        .resolve(&headers, "/v1/chat/completions");
-    chat_completions_internal(state, tenant, headers, req).await
+    // Virtual keys select their own tenant; otherwise resolve from
+    // client certificate/headers/API key
+    let (tenant, authenticated) = match authenticate_client(&state, &headers, KeyScope::Chat)? {
+        Some((_, tenant)) => (tenant, true),
+        None => {
+            let tenant = state.tenant_resolver.resolve(
+                &headers,
+                "/v1/chat/completions",
+                client_cert.as_ref().map(|Extension(identity)| identity),
+            );
+            (tenant, false)
+        }
+    };
+
+    let backend_auth = backend_authorization(&tenant, &headers, authenticated)?;
+    chat_completions_internal(state, tenant, backend_auth, req).await
 }
 
 /// Chat completions handler with explicit tenant from path
@@ -337,7 +377,61 @@ async fn chat_completions_with_tenant(
         .get(&tenant_id)
         .ok_or_else(|| AppError::InvalidRequest(format!("Unknown tenant '{}'", tenant_id)))?;
 
-    chat_completions_internal(state, tenant, headers, req).await
+    let authenticated = authenticate_client(&state, &headers, KeyScope::Chat)?;
+    if let Some((key, _)) = &authenticated {
+        if key.tenant_id != tenant.id {
+            return Err(AppError::Forbidden(
+                "API key does not belong to this tenant".to_string(),
+            ));
+        }
+    }
+
+    let backend_auth = backend_authorization(&tenant, &headers, authenticated.is_some())?;
+    chat_completions_internal(state, tenant, backend_auth, req).await
+}
+
+/// Authenticate the client's virtual key and look up the tenant it belongs to
+///
+/// Returns `Ok(None)` when no virtual key matched and keys are not required.
+pub(crate) fn authenticate_client(
+    state: &AppState,
+    headers: &HeaderMap,
+    scope: KeyScope,
+) -> Result<Option<(VirtualKey, Arc<TenantRuntime>)>, AppError> {
+    let Some(key) = state.virtual_keys.authenticate(headers, scope)? else {
+        return Ok(None);
+    };
+
+    let tenant = state.tenant_resolver.find(&key.tenant_id).ok_or_else(|| {
+        warn!("Virtual key '{}' refers to unknown tenant", key.id);
+        AppError::Unauthorized("Invalid API key".to_string())
+    })?;
+
+    Ok(Some((key, tenant)))
+}
+
+/// Authorization header to send to the tenant's backend
+///
+/// Virtual keys are never forwarded: authenticated clients get the tenant's
+/// upstream key instead, and are refused if it has none. Other clients keep
+/// the legacy passthrough.
+fn backend_authorization(
+    tenant: &TenantRuntime,
+    headers: &HeaderMap,
+    authenticated: bool,
+) -> Result<Option<String>, AppError> {
+    if authenticated {
+        let key = tenant.upstream_api_key.as_ref().ok_or_else(|| {
+            error!("Tenant {} has no upstream API key", tenant.id);
+            AppError::InternalError("Tenant has no upstream credentials".to_string())
+        })?;
+        return Ok(Some(format!("Bearer {}", key)));
+    }
+
+    Ok(headers
+        .get("authorization")
+        .and_then(|h| h.to_str().ok())
+        .map(str::to_string))
 }
 
 /// List configured tenants
@@ -363,8 +457,8 @@ async fn list_tenants(
 async fn chat_completions_internal(
     state: AppState,
     tenant: Arc<TenantRuntime>,
-    headers: HeaderMap,
-    req: ChatCompletionRequest,
+    backend_auth: Option<String>,
+    mut req: ChatCompletionRequest,
 ) -> Result<Response, AppError> {
     // Generate unique request ID for audit trail
     let request_id = generate_request_id();
@@ -390,22 +484,75 @@ async fn chat_completions_internal(
         return Ok(blocked_response(&req, &ingress_result.action_outcome).into_response());
     }
 
+    // Swap PII for placeholders so the backend never sees the originals
+    let vault = match &ingress_result.action_outcome.pseudonymize {
+        Some(entities) => {
+            pseudonymize_messages(&tenant, &mut req.messages, entities, &request_id).await?
+        }
+        None => None,
+    };
+
     // Forward request to backend LLM
     if req.stream {
         // Streaming response path with Phase 2: Midstream checks
-        handle_streaming_request(state, tenant, req, headers, request_id).await
+        handle_streaming_request(state, tenant, req, backend_auth, vault, request_id).await
     } else {
         // Non-streaming response path
-        handle_non_streaming_request(state, tenant, req, headers, request_id).await
+        handle_non_streaming_request(state, tenant, req, backend_auth, vault, request_id).await
     }
 }
 
+/// Replace detected PII in every message with placeholders held in a request vault
+///
+/// Returns `None` when nothing was replaced.
+async fn pseudonymize_messages(
+    tenant: &TenantRuntime,
+    messages: &mut [Message],
+    entities: &[String],
+    request_id: &str,
+) -> Result<Option<Arc<PseudonymVault>>, AppError> {
+    let Some(detector) = &tenant.pii_detector else {
+        warn!(
+            "Pseudonymization requested but tenant {} has no PII detector (request_id: {})",
+            tenant.id, request_id
+        );
+        return Ok(None);
+    };
+
+    let mut vault = PseudonymVault::new();
+    for message in messages.iter_mut() {
+        let result = detector
+            .classify(&message.content)
+            .await
+            .map_err(|e| AppError::InternalError(format!("PII detection failed: {}", e)))?;
+        if !result.metadata.spans.is_empty() {
+            message.content =
+                vault.pseudonymize(&message.content, &result.metadata.spans, entities);
+        }
+    }
+
+    if vault.is_empty() {
+        return Ok(None);
+    }
+
+    metrics::counter!("checkstream_pii_pseudonymized_total", "tenant" => tenant.id.clone())
+        .increment(vault.len() as u64);
+    debug!(
+        "Pseudonymized {} PII values (request_id: {})",
+        vault.len(),
+        request_id
+    );
+
+    Ok(Some(Arc::new(vault)))
+}
+
 /// Handle non-streaming chat completion (complete response at once)
 async fn handle_non_streaming_request(
     state: AppState,
     tenant: Arc<TenantRuntime>,
     req: ChatCompletionRequest,
-    headers: HeaderMap,
+    backend_auth: Option<String>,
+    vault: Option<Arc<PseudonymVault>>,
     request_id: String,
 ) -> Result<Response, AppError> {
     info!(
@@ -415,19 +562,15 @@ async fn handle_non_streaming_request(
 
     // Forward to tenant-specific backend
     let backend_url = format!("{}/chat/completions", tenant.backend_url);
-    let auth_header = headers
-        .get("authorization")
-        .and_then(|h| h.to_str().ok())
-        .unwrap_or("");
-
-    let backend_response = state
-        .http_client
+    let mut backend_request = tenant
+        .http_client(&state.http_client)
         .post(&backend_url)
-        .header("Authorization", auth_header)
-        .header("Content-Type", "application/json")
-        .json(&req)
-        .send()
-        .await?;
+        .header("Content-Type", "application/json");
+    if let Some(auth) = &backend_auth {
+        backend_request = backend_request.header("Authorization", auth);
+    }
+
+    let backend_response = backend_request.json(&req).send().await?;
 
     if !backend_response.status().is_success() {
         error!(
@@ -463,6 +606,11 @@ async fn handle_non_streaming_request(
         response.choices[0].finish_reason = "content_filter".to_string();
     }
 
+    // Put the client's original values back in place of the placeholders
+    if let Some(vault) = &vault {
+        response.choices[0].message.content = vault.restore(&response.choices[0].message.content);
+    }
+
     info!(
         "Non-streaming request complete (request_id: {})",
         request_id
@@ -476,7 +624,8 @@ async fn handle_streaming_request(
     state: AppState,
     tenant: Arc<TenantRuntime>,
     mut req: ChatCompletionRequest,
-    headers: HeaderMap,
+    backend_auth: Option<String>,
+    vault: Option<Arc<PseudonymVault>>,
     request_id: String,
 ) -> Result<Response, AppError> {
     info!(
@@ -489,19 +638,15 @@ async fn handle_streaming_request(
 
     // Forward to tenant-specific backend
     let backend_url = format!("{}/chat/completions", tenant.backend_url);
-    let auth_header = headers
-        .get("authorization")
-        .and_then(|h| h.to_str().ok())
-        .unwrap_or("");
-
-    let backend_response = state
-        .http_client
+    let mut backend_request = tenant
+        .http_client(&state.http_client)
         .post(&backend_url)
-        .header("Authorization", auth_header)
-        .header("Content-Type", "application/json")
-        .json(&req)
-        .send()
-        .await?;
+        .header("Content-Type", "application/json");
+    if let Some(auth) = &backend_auth {
+        backend_request = backend_request.header("Authorization", auth);
+    }
+
+    let backend_response = backend_request.json(&req).send().await?;
 
     if !backend_response.status().is_success() {
         error!(
@@ -513,11 +658,7 @@ async fn handle_streaming_request(
     }
 
     // Create streaming pipeline for Phase 2: Midstream checks using tenant-specific settings
-    let streaming_config = StreamingConfig {
-        context_chunks: tenant.pipeline_settings.streaming.context_chunks,
-        max_buffer_size: tenant.pipeline_settings.streaming.max_buffer_size,
-        chunk_delimiter: " ".to_string(),
-    };
+    let streaming_config = tenant.streaming_config();
 
     let midstream_pipeline = tenant.pipelines.midstream.clone();
     let streaming = Arc::new(Mutex::new(StreamingPipeline::new(
@@ -525,6 +666,9 @@ async fn handle_streaming_request(
         streaming_config,
     )));
 
+    // Egress classifies each chunk incrementally instead of rescanning the response
+    let egress_streaming = Arc::new(Mutex::new(proxy::EgressStream::new(&tenant)));
+
     let full_text = Arc::new(Mutex::new(String::new()));
 
     // Clone for midstream processing
@@ -533,17 +677,59 @@ async fn handle_streaming_request(
     let stream_adapter = tenant.stream_adapter.clone();
     let tenant_for_checks = Arc::clone(&tenant);
     let stream_blocked = Arc::new(AtomicBool::new(false));
+    let stream_finished = Arc::new(AtomicBool::new(false));
+    let restorer = vault.map(|vault| Arc::new(Mutex::new(StreamRestorer::new(vault))));
+
+    // Streams that end without a done event still get the final evaluation
+    // and any text the restorer held back
+    let end_of_stream = {
+        let state = state.clone();
+        let tenant = Arc::clone(&tenant);
+        let egress_streaming = Arc::clone(&egress_streaming);
+        let full_text = Arc::clone(&full_text);
+        let blocked = Arc::clone(&stream_blocked);
+        let finished = Arc::clone(&stream_finished);
+        let request_id = request_id.clone();
+        let restorer = restorer.clone();
+        futures_util::stream::once(async move {
+            if blocked.load(Ordering::Relaxed) {
+                return None;
+            }
+            let mut tail = match &restorer {
+                Some(restorer) => restorer.lock().await.flush().unwrap_or_default(),
+                None => String::new(),
+            };
+            if !finished.swap(true, Ordering::SeqCst) {
+                if let Some(marker) = finish_egress_stream(
+                    &state,
+                    &tenant,
+                    &egress_streaming,
+                    &full_text,
+                    &request_id,
+                )
+                .await
+                {
+                    tail.push_str(&marker);
+                }
+            }
+            (!tail.is_empty()).then_some(Ok::<String, std::io::Error>(tail))
+        })
+        .filter_map(|marker| async move { marker })
+    };
 
     // Convert backend stream to SSE stream with midstream checks
     let stream = backend_response.bytes_stream()
         .filter_map(move |chunk_result| {
             let streaming = streaming.clone();
+            let egress_streaming = egress_streaming.clone();
             let full_text = full_text.clone();
             let state = state_for_midstream.clone();
             let req_id = request_id_for_midstream.clone();
             let adapter = stream_adapter.clone();
             let tenant = Arc::clone(&tenant_for_checks);
             let blocked = Arc::clone(&stream_blocked);
+            let finished = Arc::clone(&stream_finished);
+            let restorer = restorer.clone();
 
             async move {
                 if blocked.load(Ordering::Relaxed) {
@@ -561,11 +747,7 @@ async fn handle_streaming_request(
                         for parsed in &parsed_chunks {
                             if let ParsedChunk::Content { text: content, .. } = parsed {
                                 // Store for Phase 3
-                                let full_snapshot = {
-                                    let mut full = full_text.lock().await;
-                                    full.push_str(content);
-                                    full.clone()
-                                };
+                                full_text.lock().await.push_str(content);
 
                                 // **Phase 2: Midstream** - Check this chunk
                                 {
@@ -600,15 +782,19 @@ async fn handle_streaming_request(
                                     }
                                 }
 
-                                // Run full-text egress checks incrementally so violations can stop the stream.
-                                match proxy::execute_egress_with_tenant(
-                                    &state,
-                                    &tenant,
-                                    &full_snapshot,
-                                    &req_id,
-                                )
-                                .await
-                                {
+                                // Run egress checks incrementally so violations can stop the stream.
+                                let egress = {
+                                    let mut egress_streaming = egress_streaming.lock().await;
+                                    proxy::execute_egress_chunk_with_tenant(
+                                        &state,
+                                        &tenant,
+                                        &mut egress_streaming,
+                                        content.clone(),
+                                        &req_id,
+                                    )
+                                    .await
+                                };
+                                match egress {
                                     Ok(result) => {
                                         if result.action_outcome.should_stop {
                                             blocked.store(true, Ordering::Relaxed);
@@ -626,6 +812,29 @@ async fn handle_streaming_request(
                                     }
                                 }
                             }
+
+                            if let ParsedChunk::Done { .. } = parsed {
+                                if !finished.swap(true, Ordering::SeqCst) {
+                                    if let Some(marker) = finish_egress_stream(
+                                        &state,
+                                        &tenant,
+                                        &egress_streaming,
+                                        &full_text,
+                                        &req_id,
+                                    )
+                                    .await
+                                    {
+                                        blocked.store(true, Ordering::Relaxed);
+                                        return Some(Ok::<String, std::io::Error>(marker));
+                                    }
+                                }
+                            }
+                        }
+
+                        // Restore pseudonymized values before the chunk reaches the client
+                        if let Some(restorer) = &restorer {
+                            let text = restorer.lock().await.rewrite_chunk(&text, adapter.as_ref());
+                            return Some(Ok::<String, std::io::Error>(text));
                         }
 
                         // Return original text if no redaction needed
@@ -637,7 +846,8 @@ async fn handle_streaming_request(
                     }
                 }
             }
-        });
+        })
+        .chain(end_of_stream);
 
     // Return SSE response with tenant-specific content type
     let mut response = Response::new(axum::body::Body::from_stream(stream));
@@ -657,6 +867,57 @@ async fn handle_streaming_request(
     Ok(response)
 }
 
+/// Run the final egress evaluation on a completed stream
+///
+/// Classifiers not yet due on their schedule get a final run and scanners
+/// settle matches at the end of the text. Returns the marker to send if
+/// policies block the response; by then the client has received it, so the
+/// block is also recorded as an audit event.
+async fn finish_egress_stream(
+    state: &AppState,
+    tenant: &TenantRuntime,
+    egress_streaming: &Mutex<proxy::EgressStream>,
+    full_text: &Mutex<String>,
+    request_id: &str,
+) -> Option<String> {
+    let full_text = full_text.lock().await;
+    let egress = {
+        let mut egress_streaming = egress_streaming.lock().await;
+        proxy::finish_egress_stream_with_tenant(
+            state,
+            tenant,
+            &mut egress_streaming,
+            &full_text,
+            request_id,
+        )
+        .await
+    };
+
+    match egress {
+        Ok(result) if result.action_outcome.should_stop => {
+            warn!(
+                "Streaming egress blocked completed response (request_id: {})",
+                request_id
+            );
+            state.audit_service.record_event(
+                "egress_blocked_after_delivery",
+                AuditSeverity::High,
+                &RequestContext::new(request_id, "egress"),
+                Some(json!({
+                    "tenant": tenant.id,
+                    "delivered_bytes": full_text.len(),
+                })),
+            );
+            Some("[REDACTED]".to_string())
+        }
+        Ok(_) => None,
+        Err(e) => {
+            error!("Egress check failed: {} (request_id: {})", e, request_id);
+            None
+        }
+    }
+}
+
 /// Extract user prompt from messages
 fn extract_prompt(messages: &[Message]) -> Result<String, AppError> {
     messages
@@ -693,7 +954,7 @@ fn blocked_response(req: &ChatCompletionRequest, action_outcome: &ActionOutcome)
     policy_denied_response(status, &message)
 }
 
-fn apply_modifications(text: &str, modifications: &[TextModification]) -> String {
+pub(crate) fn apply_modifications(text: &str, modifications: &[TextModification]) -> String {
     let mut output = text.to_string();
     for modification in modifications {
         match modification.kind {
@@ -728,13 +989,24 @@ async fn fallback() -> impl IntoResponse {
 
 /// Error handling
 #[derive(Debug)]
-enum AppError {
+pub(crate) enum AppError {
     InvalidRequest(String),
     BackendError(StatusCode),
+    Unauthorized(String),
     Forbidden(String),
+    NotFound(String),
     InternalError(String),
 }
 
+impl From<AuthError> for AppError {
+    fn from(err: AuthError) -> Self {
+        match err {
+            AuthError::Scope(_) => AppError::Forbidden(err.to_string()),
+            _ => AppError::Unauthorized(err.to_string()),
+        }
+    }
+}
+
 impl From<anyhow::Error> for AppError {
     fn from(err: anyhow::Error) -> Self {
         AppError::InternalError(err.to_string())
@@ -758,7 +1030,9 @@ impl IntoResponse for AppError {
         let (status, message) = match self {
             AppError::InvalidRequest(msg) => (StatusCode::BAD_REQUEST, msg),
             AppError::BackendError(status) => (status, "Backend error".to_string()),
+            AppError::Unauthorized(msg) => (StatusCode::UNAUTHORIZED, msg),
             AppError::Forbidden(msg) => (StatusCode::FORBIDDEN, msg),
+            AppError::NotFound(msg) => (StatusCode::NOT_FOUND, msg),
             AppError::InternalError(msg) => {
                 error!("Internal error: {}", msg);
                 (
@@ -36,7 +36,7 @@ const BLOCKED_HOSTNAMES: &[&str] = &[
 ];
 
 /// Configuration for URL validation
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct UrlValidationConfig {
     /// Allow HTTP scheme (not recommended for production)
     pub allow_http: bool,
@@ -48,17 +48,6 @@ pub struct UrlValidationConfig {
     pub allowed_domains: Option<Vec<String>>,
 }
 
-impl Default for UrlValidationConfig {
-    fn default() -> Self {
-        Self {
-            allow_http: false,
-            allow_localhost: false,
-            allow_private_ips: false,
-            allowed_domains: None,
-        }
-    }
-}
-
 impl UrlValidationConfig {
     /// Development configuration that allows localhost
     pub fn development() -> Self {
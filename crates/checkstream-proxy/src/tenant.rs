@@ -4,11 +4,15 @@
 
 use anyhow::Result;
 use axum::http::HeaderMap;
-use checkstream_classifiers::ClassifierRegistry;
+use checkstream_classifiers::{
+    load_config, Classifier, ClassifierConfig, ClassifierRegistry, ContextWindow,
+    ModelLoaderPlugin, StreamingConfig,
+};
 use checkstream_core::{
     anthropic_adapter, AdapterConfig, ConfigurableAdapter, OpenAiAdapter, StreamAdapter,
 };
 use checkstream_policy::{ActionExecutor, PolicyEngine};
+use serde::Serialize;
 use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
 use tracing::{debug, info, warn};
@@ -17,6 +21,7 @@ use crate::security::{validate_backend_url, UrlValidationConfig};
 
 use crate::config::{MultiTenantConfig, PipelineSettings, ProxyConfig, StreamFormat, TenantConfig};
 use crate::proxy::Pipelines;
+use crate::tls::{self, ClientCertIdentity};
 
 /// Pre-built runtime state per tenant
 ///
@@ -33,6 +38,15 @@ pub struct TenantRuntime {
     /// Backend URL for this tenant
     pub backend_url: String,
 
+    /// Tenant-specific backend client (set when the tenant has its own `backend_tls`)
+    pub backend_client: Option<reqwest::Client>,
+
+    /// Upstream API key sent to the backend for virtual-key clients
+    pub upstream_api_key: Option<String>,
+
+    /// Policy file or directory the policy engine was loaded from
+    pub policy_path: String,
+
     /// Pre-built pipelines for the three phases
     pub pipelines: Arc<Pipelines>,
 
@@ -42,6 +56,9 @@ pub struct TenantRuntime {
     /// Action executor
     pub action_executor: Arc<ActionExecutor>,
 
+    /// PII detector used for ingress pseudonymization
+    pub pii_detector: Option<Arc<dyn Classifier>>,
+
     /// Stream adapter for parsing backend responses
     pub stream_adapter: Arc<dyn StreamAdapter>,
 
@@ -53,6 +70,22 @@ pub struct TenantRuntime {
 
     /// Pipeline settings
     pub pipeline_settings: PipelineSettings,
+
+    /// Stages of the active pipelines that run a no-op classifier
+    pub noop_stages: Vec<NoopStage>,
+}
+
+/// A classifier in one of a tenant's active pipelines that always scores 0
+#[derive(Debug, Clone, Serialize)]
+pub struct NoopStage {
+    /// Phase the pipeline runs in
+    pub phase: &'static str,
+
+    /// Pipeline name
+    pub pipeline: String,
+
+    /// No-op classifier name
+    pub classifier: String,
 }
 
 impl TenantRuntime {
@@ -82,7 +115,11 @@ impl TenantRuntime {
                 .classifiers_config
                 .as_ref()
                 .unwrap_or(&default_config.classifiers_config);
-            Arc::new(ClassifierRegistry::from_file(classifiers_path).await?)
+            let mut classifier_config = load_classifier_config(classifiers_path)?;
+            if let Some(entities) = &tenant_config.pii_entities {
+                classifier_config.pii.entities = Some(entities.clone());
+            }
+            Arc::new(load_classifier_registry(classifier_config).await?)
         };
 
         // Determine pipeline settings (tenant override or default)
@@ -93,6 +130,7 @@ impl TenantRuntime {
 
         // Build pipelines
         let pipelines = Self::build_pipelines(&pipeline_settings, registry.as_ref())?;
+        let noop_stages = find_noop_stages(&tenant_config.id, &pipeline_settings, &registry);
 
         // Load policy engine
         let policy_engine = Self::load_policy_engine(&tenant_config.policy_path)?;
@@ -105,13 +143,30 @@ impl TenantRuntime {
         // Create stream adapter based on format
         let stream_adapter = create_stream_adapter(&tenant_config.stream_format);
 
+        // Tenants without backend TLS settings share the default client
+        let backend_client = tenant_config
+            .backend_tls
+            .as_ref()
+            .map(|backend_tls| tls::backend_client(Some(backend_tls)))
+            .transpose()?;
+
+        let upstream_api_key = resolve_upstream_api_key(
+            tenant_config.upstream_api_key.as_deref(),
+            tenant_config.upstream_api_key_env.as_deref(),
+        )
+        .map_err(|e| anyhow::anyhow!("Tenant '{}': {}", tenant_config.id, e))?;
+
         Ok(Self {
             id: tenant_config.id.clone(),
             name: tenant_config.name.clone(),
             backend_url: tenant_config.backend_url.clone(),
+            backend_client,
+            upstream_api_key,
+            policy_path: tenant_config.policy_path.clone(),
             pipelines: Arc::new(pipelines),
             policy_engine: Arc::new(RwLock::new(policy_engine)),
             action_executor: Arc::new(ActionExecutor::new()),
+            pii_detector: registry.get("pii").cloned(),
             stream_adapter,
             token_holdback: tenant_config
                 .token_holdback
@@ -120,6 +175,7 @@ impl TenantRuntime {
                 .max_buffer_capacity
                 .unwrap_or(default_config.max_buffer_capacity),
             pipeline_settings,
+            noop_stages,
         })
     }
 
@@ -137,10 +193,12 @@ impl TenantRuntime {
             .map_err(|e| anyhow::anyhow!("Invalid backend URL: {}", e))?;
 
         // Load classifier registry
-        let registry = ClassifierRegistry::from_file(&config.classifiers_config).await?;
+        let registry =
+            load_classifier_registry(load_classifier_config(&config.classifiers_config)?).await?;
 
         // Build pipelines
         let pipelines = Self::build_pipelines(&config.pipelines, &registry)?;
+        let noop_stages = find_noop_stages("_default", &config.pipelines, &registry);
 
         // Load policy engine
         let policy_engine = Self::load_policy_engine(&config.policy_path)?;
@@ -149,13 +207,21 @@ impl TenantRuntime {
             id: "_default".to_string(),
             name: "Default Tenant".to_string(),
             backend_url: config.backend_url.clone(),
+            backend_client: None,
+            upstream_api_key: resolve_upstream_api_key(
+                config.upstream_api_key.as_deref(),
+                config.upstream_api_key_env.as_deref(),
+            )?,
+            policy_path: config.policy_path.clone(),
             pipelines: Arc::new(pipelines),
             policy_engine: Arc::new(RwLock::new(policy_engine)),
             action_executor: Arc::new(ActionExecutor::new()),
+            pii_detector: registry.get("pii").cloned(),
             stream_adapter: Arc::new(OpenAiAdapter::new()),
             token_holdback: config.token_holdback,
             max_buffer_capacity: config.max_buffer_capacity,
             pipeline_settings: config.pipelines.clone(),
+            noop_stages,
         })
     }
 
@@ -175,8 +241,39 @@ impl TenantRuntime {
         })
     }
 
+    /// HTTP client for backend requests, falling back to the shared client
+    pub fn http_client<'a>(&'a self, shared: &'a reqwest::Client) -> &'a reqwest::Client {
+        self.backend_client.as_ref().unwrap_or(shared)
+    }
+
+    /// Streaming buffer configuration for midstream checks
+    pub fn streaming_config(&self) -> StreamingConfig {
+        let streaming = &self.pipeline_settings.streaming;
+        let (window, overlap) = streaming.context_window();
+        StreamingConfig {
+            window,
+            overlap,
+            tokenizer: None,
+            max_buffer_size: streaming.max_buffer_size,
+            schedule: streaming.schedule,
+            classifier_schedules: streaming.classifier_schedules.clone(),
+        }
+    }
+
+    /// Streaming configuration for egress checks on a response as it streams
+    ///
+    /// Egress sees the whole response so far.
+    pub fn egress_streaming_config(&self) -> StreamingConfig {
+        StreamingConfig {
+            window: ContextWindow::All,
+            overlap: 0,
+            max_buffer_size: usize::MAX,
+            ..self.streaming_config()
+        }
+    }
+
     /// Load policy engine from path
-    fn load_policy_engine(policy_path: &str) -> Result<PolicyEngine> {
+    pub(crate) fn load_policy_engine(policy_path: &str) -> Result<PolicyEngine> {
         let mut engine = PolicyEngine::new();
 
         let path = std::path::Path::new(policy_path);
@@ -208,6 +305,132 @@ impl TenantRuntime {
     }
 }
 
+/// Load a classifiers config, defaulting to strict classifier resolution
+///
+/// Unresolved classifier names fail startup unless the config sets
+/// `strict: false` or `CHECKSTREAM_DEV_MODE` is set.
+pub(crate) fn load_classifier_config(path: &str) -> Result<ClassifierConfig> {
+    let mut config = load_config(path)?;
+    config
+        .strict
+        .get_or_insert_with(|| std::env::var("CHECKSTREAM_DEV_MODE").is_err());
+    Ok(config)
+}
+
+/// Reload policies from each tenant's `policy_path`, replacing their engines
+///
+/// Every engine is loaded before any is swapped in, so a tenant that fails to
+/// load leaves all of them on their current policies. Runtime rule toggles
+/// are discarded. Returns `(tenant id, policy count)` pairs in the order given.
+pub fn reload_policies(tenants: &[Arc<TenantRuntime>]) -> Result<Vec<(String, usize)>> {
+    let engines = tenants
+        .iter()
+        .map(|tenant| {
+            TenantRuntime::load_policy_engine(&tenant.policy_path)
+                .map_err(|e| anyhow::anyhow!("Tenant '{}': {}", tenant.id, e))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(tenants
+        .iter()
+        .zip(engines)
+        .map(|(tenant, engine)| {
+            let count = engine.policies().len();
+            *tenant.policy_engine.write().unwrap() = engine;
+            info!("Tenant {} reloaded {} policies", tenant.id, count);
+            (tenant.id.clone(), count)
+        })
+        .collect())
+}
+
+/// Build a classifier registry, resolving models through compiled-in loader plugins
+pub(crate) async fn load_classifier_registry(config: ClassifierConfig) -> Result<ClassifierRegistry> {
+    let loaders = model_loaders(&config)?;
+    Ok(ClassifierRegistry::from_config_with_loaders(config, loaders).await?)
+}
+
+/// Loader plugins for the config's model registry, in resolution order
+///
+/// ONNX exports are preferred over Candle checkpoints when both plugins are
+/// compiled in; neither is available without the `onnx-plugin` / `ml-plugin`
+/// features.
+fn model_loaders(config: &ClassifierConfig) -> Result<Vec<Arc<dyn ModelLoaderPlugin>>> {
+    let Some(path) = &config.model_registry else {
+        return Ok(Vec::new());
+    };
+
+    #[allow(unused_mut)]
+    let mut loaders: Vec<Arc<dyn ModelLoaderPlugin>> = Vec::new();
+
+    #[cfg(feature = "onnx-plugin")]
+    loaders.push(Arc::new(
+        checkstream_classifiers_onnx_plugin::OnnxModelLoader::from_file(path)?,
+    ));
+
+    #[cfg(feature = "ml-plugin")]
+    loaders.push(Arc::new(
+        checkstream_classifiers_ml_plugin::ExternalMlModelLoader::from_file(path)?,
+    ));
+
+    if loaders.is_empty() {
+        warn!(
+            "Model registry {} ignored: built without the ml-plugin or onnx-plugin feature",
+            path.display()
+        );
+    } else {
+        info!("Loaded model registry for ML inference: {}", path.display());
+    }
+    Ok(loaders)
+}
+
+/// Find no-op classifiers in the active pipelines and publish them as a metric
+fn find_noop_stages(
+    tenant_id: &str,
+    settings: &PipelineSettings,
+    registry: &ClassifierRegistry,
+) -> Vec<NoopStage> {
+    let mut stages = Vec::new();
+    for (phase, pipeline) in [
+        ("ingress", &settings.ingress_pipeline),
+        ("midstream", &settings.midstream_pipeline),
+        ("egress", &settings.egress_pipeline),
+    ] {
+        let noop = registry.noop_stages(pipeline);
+        metrics::gauge!(
+            "checkstream_pipeline_noop_stages",
+            "tenant" => tenant_id.to_string(),
+            "phase" => phase
+        )
+        .set(noop.len() as f64);
+
+        for classifier in noop {
+            warn!(
+                "Tenant {} {} pipeline '{}' runs no-op classifier '{}'",
+                tenant_id, phase, pipeline, classifier
+            );
+            stages.push(NoopStage {
+                phase,
+                pipeline: pipeline.clone(),
+                classifier,
+            });
+        }
+    }
+    stages
+}
+
+/// Resolve the upstream API key from config, preferring the environment variable
+fn resolve_upstream_api_key(
+    literal: Option<&str>,
+    env_var: Option<&str>,
+) -> Result<Option<String>> {
+    match env_var {
+        Some(name) => std::env::var(name)
+            .map(Some)
+            .map_err(|_| anyhow::anyhow!("Upstream API key variable {} is not set", name)),
+        None => Ok(literal.map(str::to_string)),
+    }
+}
+
 /// Create a stream adapter based on the stream format configuration
 fn create_stream_adapter(format: &StreamFormat) -> Arc<dyn StreamAdapter> {
     match format {
@@ -227,10 +450,11 @@ fn create_stream_adapter(format: &StreamFormat) -> Arc<dyn StreamAdapter> {
 /// Resolves tenant from incoming requests
 ///
 /// Resolution order:
-/// 1. X-Tenant-ID header
-/// 2. Path prefix (e.g., /tenant-id/v1/chat/completions)
-/// 3. API key mapping
-/// 4. Default tenant
+/// 1. Verified client certificate (mTLS)
+/// 2. X-Tenant-ID header
+/// 3. Path prefix (e.g., /tenant-id/v1/chat/completions)
+/// 4. API key mapping
+/// 5. Default tenant
 pub struct TenantResolver {
     /// Named tenants
     tenants: HashMap<String, Arc<TenantRuntime>>,
@@ -238,6 +462,9 @@ pub struct TenantResolver {
     /// API key to tenant ID mapping
     api_key_index: HashMap<String, String>,
 
+    /// Client certificate name to tenant ID mapping
+    client_cert_index: HashMap<String, String>,
+
     /// Default tenant (used when no tenant is resolved)
     default_tenant: Arc<TenantRuntime>,
 }
@@ -252,6 +479,7 @@ impl TenantResolver {
         Self {
             tenants,
             api_key_index,
+            client_cert_index: HashMap::new(),
             default_tenant,
         }
     }
@@ -264,6 +492,7 @@ impl TenantResolver {
         // Build named tenants
         let mut tenants = HashMap::new();
         let mut api_key_index = HashMap::new();
+        let mut client_cert_index = HashMap::new();
 
         for (id, tenant_config) in &config.tenants {
             let runtime = TenantRuntime::new(tenant_config, &config.default, None).await?;
@@ -273,6 +502,11 @@ impl TenantResolver {
                 api_key_index.insert(key.clone(), id.clone());
             }
 
+            // Build client certificate index
+            for name in &tenant_config.client_cert_names {
+                client_cert_index.insert(name.clone(), id.clone());
+            }
+
             tenants.insert(id.clone(), Arc::new(runtime));
         }
 
@@ -281,6 +515,7 @@ impl TenantResolver {
         Ok(Self {
             tenants,
             api_key_index,
+            client_cert_index,
             default_tenant,
         })
     }
@@ -288,12 +523,31 @@ impl TenantResolver {
     /// Resolve tenant from request
     ///
     /// Priority:
-    /// 1. X-Tenant-ID header
-    /// 2. Path prefix
-    /// 3. API key mapping
-    /// 4. Default tenant
-    pub fn resolve(&self, headers: &HeaderMap, path: &str) -> Arc<TenantRuntime> {
-        // 1. Check X-Tenant-ID header
+    /// 1. Verified client certificate (mTLS)
+    /// 2. X-Tenant-ID header
+    /// 3. Path prefix
+    /// 4. API key mapping
+    /// 5. Default tenant
+    pub fn resolve(
+        &self,
+        headers: &HeaderMap,
+        path: &str,
+        client_cert: Option<&ClientCertIdentity>,
+    ) -> Arc<TenantRuntime> {
+        // 1. Check client certificate names (CN first, then SANs)
+        if let Some(identity) = client_cert {
+            let tenant = identity
+                .names
+                .iter()
+                .filter_map(|name| self.client_cert_index.get(name))
+                .find_map(|tenant_id| self.tenants.get(tenant_id));
+            if let Some(tenant) = tenant {
+                debug!("Resolved tenant from client certificate");
+                return tenant.clone();
+            }
+        }
+
+        // 2. Check X-Tenant-ID header
         if let Some(tenant_id) = headers.get("x-tenant-id") {
             if let Ok(id) = tenant_id.to_str() {
                 if let Some(tenant) = self.tenants.get(id) {
@@ -305,7 +559,7 @@ impl TenantResolver {
             }
         }
 
-        // 2. Check path prefix: /tenant-id/v1/...
+        // 3. Check path prefix: /tenant-id/v1/...
         if let Some(tenant_id) = extract_path_tenant(path) {
             if let Some(tenant) = self.tenants.get(&tenant_id) {
                 debug!("Resolved tenant from path: {}", tenant_id);
@@ -314,7 +568,7 @@ impl TenantResolver {
             // Don't warn here - might just be a normal path
         }
 
-        // 3. Check API key mapping
+        // 4. Check API key mapping
         if let Some(auth) = headers.get("authorization") {
             if let Ok(auth_str) = auth.to_str() {
                 if let Some(api_key) = extract_api_key(auth_str) {
@@ -328,7 +582,7 @@ impl TenantResolver {
             }
         }
 
-        // 4. Return default tenant
+        // 5. Return default tenant
         debug!("Using default tenant");
         self.default_tenant.clone()
     }
@@ -343,6 +597,26 @@ impl TenantResolver {
         &self.default_tenant
     }
 
+    /// Get tenant by ID, including the default tenant under its own ID
+    pub fn find(&self, tenant_id: &str) -> Option<Arc<TenantRuntime>> {
+        if tenant_id == self.default_tenant.id {
+            Some(self.default_tenant.clone())
+        } else {
+            self.get(tenant_id)
+        }
+    }
+
+    /// Get all tenant runtimes, default tenant first
+    pub fn all_tenants(&self) -> Vec<Arc<TenantRuntime>> {
+        let mut named: Vec<_> = self.tenants.values().cloned().collect();
+        named.sort_by(|a, b| a.id.cmp(&b.id));
+
+        let mut all = Vec::with_capacity(named.len() + 1);
+        all.push(self.default_tenant.clone());
+        all.extend(named);
+        all
+    }
+
     /// List all tenant IDs
     pub fn list_tenants(&self) -> Vec<&str> {
         self.tenants.keys().map(|s| s.as_str()).collect()
@@ -411,4 +685,56 @@ mod tests {
         assert_eq!(extract_api_key("key-test123"), Some("key-test123"));
         assert_eq!(extract_api_key("invalid"), None);
     }
+
+    const POLICY: &str = "name: test\ndescription: test\nrules:\n  - name: block\n    description: block\n    trigger:\n      type: pattern\n      pattern: secret\n    actions:\n      - type: stop\n";
+
+    const EMPTY_POLICY: &str = "name: empty\ndescription: empty\nrules: []\n";
+
+    fn rule_count(tenant: &TenantRuntime) -> usize {
+        let engine = tenant.policy_engine.read().unwrap();
+        engine.policies()[0].rules.len()
+    }
+
+    async fn tenant_with_policy(policy: &str) -> (Arc<TenantRuntime>, tempfile::NamedTempFile) {
+        let mut policy_file = tempfile::Builder::new().suffix(".yaml").tempfile().unwrap();
+        std::io::Write::write_all(&mut policy_file, policy.as_bytes()).unwrap();
+        let mut classifiers = tempfile::Builder::new().suffix(".yaml").tempfile().unwrap();
+        std::io::Write::write_all(
+            &mut classifiers,
+            b"pipelines:\n  scan:\n    stages:\n      - type: single\n        name: pii\n        classifier: pii\n",
+        )
+        .unwrap();
+
+        let config = ProxyConfig {
+            policy_path: policy_file.path().display().to_string(),
+            classifiers_config: classifiers.path().display().to_string(),
+            pipelines: PipelineSettings {
+                ingress_pipeline: "scan".to_string(),
+                midstream_pipeline: "scan".to_string(),
+                egress_pipeline: "scan".to_string(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let tenant = TenantRuntime::from_proxy_config(&config).await.unwrap();
+        (Arc::new(tenant), policy_file)
+    }
+
+    #[tokio::test]
+    async fn test_reload_policies_is_all_or_nothing() {
+        let (first, first_policy) = tenant_with_policy(POLICY).await;
+        let (second, second_policy) = tenant_with_policy(POLICY).await;
+        let tenants = [first.clone(), second.clone()];
+
+        std::fs::write(first_policy.path(), EMPTY_POLICY).unwrap();
+        std::fs::write(second_policy.path(), "rules: [not a rule").unwrap();
+        let err = reload_policies(&tenants).unwrap_err();
+        assert!(err.to_string().contains("'_default'"));
+        assert!(tenants.iter().all(|tenant| rule_count(tenant) == 1));
+
+        std::fs::write(second_policy.path(), POLICY).unwrap();
+        let reloaded = reload_policies(&tenants).unwrap();
+        assert_eq!(reloaded.len(), 2);
+        assert_eq!(rule_count(&first), 0);
+    }
 }
@@ -0,0 +1,123 @@
+//! Shared fixtures for handler tests
+//!
+//! [`TestApp`] builds a full [`AppState`] over temporary config files: a
+//! default tenant plus `acme` and `globex`, each with its own policy file.
+//! Every tenant runs a `scan` pipeline (PII) in all phases, and its policy
+//! stops any text containing "secret".
+
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use axum::Router;
+use checkstream_telemetry::PersistenceConfig;
+use metrics_exporter_prometheus::PrometheusBuilder;
+use std::path::PathBuf;
+use tempfile::TempDir;
+use tower::ServiceExt;
+
+use crate::config::MultiTenantConfig;
+use crate::proxy::AppState;
+
+/// Admin API key the fixtures authenticate with
+pub(crate) const ADMIN_KEY: &str = "test-admin-key";
+
+/// Policy stopping any text that contains "secret"
+pub(crate) const POLICY: &str = "name: test\ndescription: test\nrules:\n  - name: block-secret\n    description: block\n    trigger:\n      type: pattern\n      pattern: secret\n    actions:\n      - type: stop\n";
+
+const CLASSIFIERS: &str = "pipelines:\n  scan:\n    stages:\n      - type: single\n        name: pii\n        classifier: pii\n";
+
+/// App state over temporary config files, which live as long as it does
+pub(crate) struct TestApp {
+    pub state: AppState,
+    dir: TempDir,
+}
+
+impl TestApp {
+    /// Build the default, `acme` and `globex` tenants
+    pub async fn new() -> Self {
+        // The admin key is read once per process, so every test sets the same one
+        std::env::set_var("CHECKSTREAM_ADMIN_API_KEY", ADMIN_KEY);
+
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("classifiers.yaml"), CLASSIFIERS).unwrap();
+        for tenant in ["default", "acme", "globex"] {
+            std::fs::write(dir.path().join(format!("{tenant}.yaml")), POLICY).unwrap();
+        }
+
+        let config = format!(
+            "backend_url: https://api.openai.com/v1\n\
+             policy_path: {dir}/default.yaml\n\
+             classifiers_config: {dir}/classifiers.yaml\n\
+             pipelines:\n  ingress_pipeline: scan\n  midstream_pipeline: scan\n  egress_pipeline: scan\n\
+             tenants:\n\
+             \x20 acme:\n    id: acme\n    backend_url: https://api.openai.com/v1\n    policy_path: {dir}/acme.yaml\n    upstream_api_key: sk-acme-upstream\n\
+             \x20 globex:\n    id: globex\n    backend_url: https://api.openai.com/v1\n    policy_path: {dir}/globex.yaml\n",
+            dir = dir.path().display()
+        );
+        let config: MultiTenantConfig = serde_yaml::from_str(&config).unwrap();
+        let audit_config = PersistenceConfig {
+            audit_dir: dir.path().join("audit"),
+            ..Default::default()
+        };
+        let metrics_handle = PrometheusBuilder::new().build_recorder().handle();
+        let state = AppState::with_audit_config(config, metrics_handle, audit_config)
+            .await
+            .unwrap();
+
+        Self { state, dir }
+    }
+
+    /// Policy file of a tenant (`default` for the default tenant)
+    pub fn policy_path(&self, tenant: &str) -> PathBuf {
+        self.dir.path().join(format!("{tenant}.yaml"))
+    }
+
+    /// Send `request` through `router`, returning the status and JSON body
+    pub async fn call(
+        &self,
+        router: Router<AppState>,
+        request: Request<Body>,
+    ) -> (StatusCode, serde_json::Value) {
+        let response = router
+            .with_state(self.state.clone())
+            .oneshot(request)
+            .await
+            .unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (
+            status,
+            serde_json::from_slice(&body).unwrap_or(serde_json::Value::Null),
+        )
+    }
+}
+
+/// A request with a JSON body (if any) and the given headers
+pub(crate) fn json_request(
+    method: &str,
+    uri: &str,
+    headers: &[(&str, &str)],
+    body: Option<serde_json::Value>,
+) -> Request<Body> {
+    let mut request = Request::builder().method(method).uri(uri);
+    for (name, value) in headers {
+        request = request.header(*name, *value);
+    }
+    match body {
+        Some(body) => request
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap(),
+        None => request.body(Body::empty()).unwrap(),
+    }
+}
+
+/// A request authenticated with [`ADMIN_KEY`]
+pub(crate) fn admin_request(
+    method: &str,
+    uri: &str,
+    body: Option<serde_json::Value>,
+) -> Request<Body> {
+    json_request(method, uri, &[("x-checkstream-admin-key", ADMIN_KEY)], body)
+}
@@ -0,0 +1,425 @@
+//! TLS termination and backend client certificates
+//!
+//! - The listener terminates TLS natively with rustls. Certificate, key and
+//!   client CA files are polled for changes and swapped in without a restart.
+//! - With a client CA configured, verified client certificates are exposed to
+//!   handlers as a [`ClientCertIdentity`] request extension so `TenantResolver`
+//!   can map them to tenants.
+//! - Backend clients can present their own certificate (`backend_tls`).
+
+use anyhow::{Context, Result};
+use axum::{extract::Request, Router};
+use hyper_util::rt::{TokioExecutor, TokioIo};
+use hyper_util::server::conn::auto::Builder;
+use hyper_util::server::graceful::GracefulShutdown;
+use hyper_util::service::TowerToHyperService;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use rustls::server::WebPkiClientVerifier;
+use rustls::{RootCertStore, ServerConfig};
+use std::future::Future;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime};
+use tokio::net::TcpListener;
+use tokio_rustls::TlsAcceptor;
+use tower::ServiceExt;
+use tracing::{debug, info, warn};
+use x509_parser::extensions::GeneralName;
+
+use crate::config::{BackendTlsConfig, ClientAuthMode, TlsConfig};
+
+/// Backend request timeout
+const BACKEND_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Time a client has to complete the TLS handshake after connecting
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Names from a verified client certificate
+///
+/// Inserted as a request extension on mTLS connections. `names` holds the
+/// subject common name first, followed by DNS, email and URI SANs.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ClientCertIdentity {
+    pub names: Vec<String>,
+}
+
+impl ClientCertIdentity {
+    /// Extract the identity from a DER-encoded end-entity certificate
+    pub fn from_der(der: &[u8]) -> Option<Self> {
+        let (_, cert) = x509_parser::parse_x509_certificate(der).ok()?;
+
+        let mut names: Vec<String> = cert
+            .subject()
+            .iter_common_name()
+            .filter_map(|cn| cn.as_str().ok())
+            .map(str::to_string)
+            .collect();
+
+        if let Ok(Some(san)) = cert.subject_alternative_name() {
+            for name in &san.value.general_names {
+                match name {
+                    GeneralName::DNSName(n) | GeneralName::RFC822Name(n) | GeneralName::URI(n) => {
+                        names.push(n.to_string())
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        (!names.is_empty()).then_some(Self { names })
+    }
+}
+
+/// Build a rustls server configuration from PEM files
+pub fn load_server_config(config: &TlsConfig) -> Result<Arc<ServerConfig>> {
+    let certs = load_certs(&config.cert_path)?;
+    let key = load_private_key(&config.key_path)?;
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+
+    let builder = ServerConfig::builder_with_provider(provider.clone())
+        .with_safe_default_protocol_versions()?;
+
+    let builder = match &config.client_ca_path {
+        Some(ca_path) => {
+            let mut roots = RootCertStore::empty();
+            for cert in load_certs(ca_path)? {
+                roots.add(cert)?;
+            }
+            let verifier = WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider);
+            let verifier = match config.client_auth {
+                ClientAuthMode::Optional => verifier.allow_unauthenticated().build()?,
+                ClientAuthMode::Required => verifier.build()?,
+            };
+            builder.with_client_cert_verifier(verifier)
+        }
+        None => builder.with_no_client_auth(),
+    };
+
+    let mut server_config = builder
+        .with_single_cert(certs, key)
+        .context("Invalid TLS certificate or key")?;
+    server_config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+
+    Ok(Arc::new(server_config))
+}
+
+fn load_certs(path: &str) -> Result<Vec<CertificateDer<'static>>> {
+    let pem = std::fs::read(path).with_context(|| format!("Failed to read {}", path))?;
+    let certs = rustls_pemfile::certs(&mut pem.as_slice())
+        .collect::<std::io::Result<Vec<_>>>()
+        .with_context(|| format!("Invalid PEM certificates in {}", path))?;
+    if certs.is_empty() {
+        anyhow::bail!("No certificates found in {}", path);
+    }
+    Ok(certs)
+}
+
+fn load_private_key(path: &str) -> Result<PrivateKeyDer<'static>> {
+    let pem = std::fs::read(path).with_context(|| format!("Failed to read {}", path))?;
+    rustls_pemfile::private_key(&mut pem.as_slice())
+        .with_context(|| format!("Invalid PEM private key in {}", path))?
+        .ok_or_else(|| anyhow::anyhow!("No private key found in {}", path))
+}
+
+/// Server configuration that is reloaded when its files change
+#[derive(Clone)]
+pub struct ReloadableTlsConfig {
+    config: TlsConfig,
+    current: Arc<RwLock<Arc<ServerConfig>>>,
+}
+
+impl ReloadableTlsConfig {
+    /// Load the initial configuration
+    pub fn new(config: TlsConfig) -> Result<Self> {
+        let server_config = load_server_config(&config)?;
+        Ok(Self {
+            config,
+            current: Arc::new(RwLock::new(server_config)),
+        })
+    }
+
+    /// Current server configuration (used for each new connection)
+    pub fn current(&self) -> Arc<ServerConfig> {
+        self.current.read().unwrap().clone()
+    }
+
+    /// Reload from disk, keeping the current configuration on failure
+    pub fn reload(&self) -> Result<()> {
+        let server_config = load_server_config(&self.config)?;
+        *self.current.write().unwrap() = server_config;
+        Ok(())
+    }
+
+    /// Poll the certificate files and reload when any of them change
+    pub fn spawn_watcher(&self) {
+        if self.config.reload_interval_secs == 0 {
+            return;
+        }
+
+        let this = self.clone();
+        let interval = Duration::from_secs(self.config.reload_interval_secs);
+        tokio::spawn(async move {
+            let mut last_modified = this.modified_times();
+            let mut ticker = tokio::time::interval(interval);
+            ticker.tick().await;
+
+            loop {
+                ticker.tick().await;
+                let modified = this.modified_times();
+                if modified == last_modified {
+                    continue;
+                }
+
+                match this.reload() {
+                    Ok(()) => {
+                        info!("Reloaded TLS certificates from {}", this.config.cert_path);
+                        metrics::counter!("checkstream_tls_reloads_total", "result" => "success")
+                            .increment(1);
+                        last_modified = modified;
+                    }
+                    Err(e) => {
+                        // Files may be mid-rotation; retry on the next tick
+                        warn!("TLS certificate reload failed, keeping previous: {:#}", e);
+                        metrics::counter!("checkstream_tls_reloads_total", "result" => "error")
+                            .increment(1);
+                    }
+                }
+            }
+        });
+    }
+
+    fn modified_times(&self) -> Vec<Option<SystemTime>> {
+        [
+            Some(&self.config.cert_path),
+            Some(&self.config.key_path),
+            self.config.client_ca_path.as_ref(),
+        ]
+        .into_iter()
+        .flatten()
+        .map(|path| std::fs::metadata(path).and_then(|m| m.modified()).ok())
+        .collect()
+    }
+}
+
+/// Serve the router over TLS until `shutdown` resolves
+pub async fn serve(
+    listener: TcpListener,
+    app: Router,
+    tls: ReloadableTlsConfig,
+    shutdown: impl Future<Output = ()>,
+) -> Result<()> {
+    let builder = Builder::new(TokioExecutor::new());
+    let graceful = GracefulShutdown::new();
+    tokio::pin!(shutdown);
+
+    loop {
+        let (stream, peer) = tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok(conn) => conn,
+                Err(e) => {
+                    warn!("Failed to accept connection: {}", e);
+                    continue;
+                }
+            },
+            _ = &mut shutdown => break,
+        };
+
+        let acceptor = TlsAcceptor::from(tls.current());
+        let app = app.clone();
+        let builder = builder.clone();
+        let watcher = graceful.watcher();
+
+        tokio::spawn(async move {
+            let handshake = tokio::time::timeout(HANDSHAKE_TIMEOUT, acceptor.accept(stream));
+            let stream = match handshake.await {
+                Ok(Ok(stream)) => stream,
+                Ok(Err(e)) => {
+                    debug!("TLS handshake with {} failed: {}", peer, e);
+                    metrics::counter!("checkstream_errors_total", "type" => "tls_handshake")
+                        .increment(1);
+                    return;
+                }
+                Err(_) => {
+                    debug!("TLS handshake with {} timed out", peer);
+                    metrics::counter!("checkstream_errors_total", "type" => "tls_handshake")
+                        .increment(1);
+                    return;
+                }
+            };
+
+            let identity = stream
+                .get_ref()
+                .1
+                .peer_certificates()
+                .and_then(|certs| certs.first())
+                .and_then(|cert| ClientCertIdentity::from_der(cert));
+
+            let service = app.map_request(move |mut req: Request<_>| {
+                if let Some(identity) = &identity {
+                    req.extensions_mut().insert(identity.clone());
+                }
+                req
+            });
+
+            let conn = builder.serve_connection_with_upgrades(
+                TokioIo::new(stream),
+                TowerToHyperService::new(service),
+            );
+            if let Err(e) = watcher.watch(conn.into_owned()).await {
+                debug!("Connection from {} closed with error: {}", peer, e);
+            }
+        });
+    }
+
+    drop(listener);
+    graceful.shutdown().await;
+    Ok(())
+}
+
+/// Build the HTTP client used for backend requests
+pub fn backend_client(backend_tls: Option<&BackendTlsConfig>) -> Result<reqwest::Client> {
+    let mut builder = reqwest::Client::builder().timeout(BACKEND_TIMEOUT);
+
+    if let Some(tls) = backend_tls {
+        match (&tls.client_cert_path, &tls.client_key_path) {
+            (Some(cert_path), Some(key_path)) => {
+                let mut pem = std::fs::read(cert_path)
+                    .with_context(|| format!("Failed to read {}", cert_path))?;
+                pem.push(b'\n');
+                pem.extend(
+                    std::fs::read(key_path)
+                        .with_context(|| format!("Failed to read {}", key_path))?,
+                );
+                builder = builder.identity(reqwest::Identity::from_pem(&pem)?);
+            }
+            (None, None) => {}
+            _ => anyhow::bail!("backend_tls requires both client_cert_path and client_key_path"),
+        }
+
+        if let Some(ca_path) = &tls.ca_cert_path {
+            let pem =
+                std::fs::read(ca_path).with_context(|| format!("Failed to read {}", ca_path))?;
+            for cert in reqwest::Certificate::from_pem_bundle(&pem)? {
+                builder = builder.add_root_certificate(cert);
+            }
+        }
+    }
+
+    Ok(builder.build()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rcgen::{CertificateParams, DnType, KeyPair, SanType};
+    use tempfile::TempDir;
+    use tokio::io::AsyncReadExt;
+
+    fn self_signed(cn: &str, sans: Vec<SanType>) -> (String, String, Vec<u8>) {
+        let key = KeyPair::generate().unwrap();
+        let mut params = CertificateParams::default();
+        params.distinguished_name.push(DnType::CommonName, cn);
+        params.subject_alt_names = sans;
+        let cert = params.self_signed(&key).unwrap();
+        (cert.pem(), key.serialize_pem(), cert.der().to_vec())
+    }
+
+    fn write_temp(dir: &TempDir, name: &str, contents: &str) -> String {
+        let path = dir.path().join(name);
+        std::fs::write(&path, contents).unwrap();
+        path.to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn test_client_identity_names() {
+        let (_, _, der) = self_signed(
+            "acme-client",
+            vec![
+                SanType::DnsName("client.acme.test".try_into().unwrap()),
+                SanType::Rfc822Name("ops@acme.test".try_into().unwrap()),
+            ],
+        );
+
+        let identity = ClientCertIdentity::from_der(&der).unwrap();
+        assert_eq!(
+            identity.names,
+            vec!["acme-client", "client.acme.test", "ops@acme.test"]
+        );
+        assert!(ClientCertIdentity::from_der(b"not a certificate").is_none());
+    }
+
+    #[test]
+    fn test_load_server_config_with_client_ca() {
+        let dir = tempfile::tempdir().unwrap();
+        let (cert_pem, key_pem, _) = self_signed("localhost", vec![]);
+        let (ca_pem, _, _) = self_signed("test-ca", vec![]);
+
+        let config = TlsConfig {
+            cert_path: write_temp(&dir, "cert.pem", &cert_pem),
+            key_path: write_temp(&dir, "key.pem", &key_pem),
+            client_ca_path: Some(write_temp(&dir, "ca.pem", &ca_pem)),
+            client_auth: ClientAuthMode::Required,
+            reload_interval_secs: 0,
+        };
+
+        let server_config = load_server_config(&config).unwrap();
+        assert_eq!(server_config.alpn_protocols[0], b"h2".to_vec());
+
+        let reloadable = ReloadableTlsConfig::new(config).unwrap();
+        assert!(reloadable.reload().is_ok());
+    }
+
+    #[test]
+    fn test_reload_keeps_previous_on_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let (cert_pem, key_pem, _) = self_signed("localhost", vec![]);
+        let config = TlsConfig {
+            cert_path: write_temp(&dir, "cert.pem", &cert_pem),
+            key_path: write_temp(&dir, "key.pem", &key_pem),
+            client_ca_path: None,
+            client_auth: ClientAuthMode::Optional,
+            reload_interval_secs: 0,
+        };
+
+        let reloadable = ReloadableTlsConfig::new(config.clone()).unwrap();
+        let before = reloadable.current();
+        std::fs::write(&config.key_path, "garbage").unwrap();
+
+        assert!(reloadable.reload().is_err());
+        assert!(Arc::ptr_eq(&before, &reloadable.current()));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_stalled_handshake_is_dropped() {
+        let dir = tempfile::tempdir().unwrap();
+        let (cert_pem, key_pem, _) = self_signed("localhost", vec![]);
+        let config = TlsConfig {
+            cert_path: write_temp(&dir, "cert.pem", &cert_pem),
+            key_path: write_temp(&dir, "key.pem", &key_pem),
+            client_ca_path: None,
+            client_auth: ClientAuthMode::Optional,
+            reload_interval_secs: 0,
+        };
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let tls = ReloadableTlsConfig::new(config).unwrap();
+        tokio::spawn(serve(listener, Router::new(), tls, std::future::pending()));
+
+        // Never send a ClientHello; the server must hang up once the timeout passes
+        let mut client = tokio::net::TcpStream::connect(addr).await.unwrap();
+        let mut buf = [0u8; 1];
+        let read = client.read(&mut buf).await;
+        assert!(!matches!(read, Ok(n) if n > 0));
+    }
+
+    #[test]
+    fn test_backend_client_requires_cert_and_key() {
+        let tls = BackendTlsConfig {
+            client_cert_path: Some("/nonexistent/cert.pem".to_string()),
+            client_key_path: None,
+            ca_cert_path: None,
+        };
+        assert!(backend_client(Some(&tls)).is_err());
+        assert!(backend_client(None).is_ok());
+    }
+}
@@ -91,20 +91,16 @@ pub struct MetricsSnapshot {
 impl MetricsSnapshot {
     /// Calculate average latency per request
     pub fn avg_latency_us(&self) -> u64 {
-        if self.total_requests == 0 {
-            0
-        } else {
-            self.total_latency_us / self.total_requests
-        }
+        self.total_latency_us
+            .checked_div(self.total_requests)
+            .unwrap_or(0)
     }
 
     /// Calculate average classifier latency per request
     pub fn avg_classifier_latency_us(&self) -> u64 {
-        if self.total_requests == 0 {
-            0
-        } else {
-            self.classifier_latency_us / self.total_requests
-        }
+        self.classifier_latency_us
+            .checked_div(self.total_requests)
+            .unwrap_or(0)
     }
 
     /// Calculate policy trigger rate
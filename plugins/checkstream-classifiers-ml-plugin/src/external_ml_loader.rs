@@ -1,5 +1,5 @@
 use async_trait::async_trait;
-use candle_core::{DType, Device, IndexOp, Tensor, D};
+use candle_core::{DType, Device, IndexOp, Tensor};
 use candle_nn::{Linear, Module, VarBuilder};
 use candle_transformers::models::bert::{BertModel, Config as BertConfig};
 use candle_transformers::models::debertav2::{
@@ -11,9 +11,12 @@ use candle_transformers::models::xlm_roberta::{
 };
 use checkstream_classifiers::classifier::{ClassificationMetadata, Classifier};
 use checkstream_classifiers::loader_plugin::ModelLoaderPlugin;
+use checkstream_classifiers::logits::LogitDecoder;
+use checkstream_classifiers::long_text::with_long_text;
 use checkstream_classifiers::model_config::{
-    ArchitectureConfig, ModelConfig, ModelRegistry, ModelSource,
+    ArchitectureConfig, LongTextStrategy, ModelConfig, ModelRegistry, ModelSource,
 };
+use checkstream_classifiers::similarity::{Embedder, ExemplarSet, SimilarityClassifier};
 use checkstream_classifiers::{ClassificationResult, ClassifierTier};
 use checkstream_core::Result;
 use serde::de::DeserializeOwned;
@@ -81,8 +84,12 @@ impl ExternalMlModelLoader {
                 self.load_xlm_roberta_classifier(config, *num_labels, labels)
                     .await
             }
-            ArchitectureConfig::SentenceTransformer { pooling } => {
-                self.load_sentence_transformer_classifier(config, pooling)
+            ArchitectureConfig::SentenceTransformer {
+                pooling,
+                exemplars,
+                top_k,
+            } => {
+                self.load_sentence_transformer_classifier(config, pooling, exemplars, *top_k)
                     .await
             }
             ArchitectureConfig::Custom { implementation } => {
@@ -223,9 +230,7 @@ impl ExternalMlModelLoader {
             model,
             classifier,
             device,
-            num_labels,
-            labels,
-            threshold: config.inference.threshold,
+            decoder: LogitDecoder::from_config(config, labels)?,
             max_length: config.inference.max_length,
         }))
     }
@@ -293,9 +298,7 @@ impl ExternalMlModelLoader {
             pre_classifier,
             classifier,
             device,
-            num_labels,
-            labels,
-            threshold: config.inference.threshold,
+            decoder: LogitDecoder::from_config(config, labels)?,
             max_length: config.inference.max_length,
         }))
     }
@@ -333,9 +336,7 @@ impl ExternalMlModelLoader {
             tokenizer,
             model,
             device,
-            num_labels,
-            labels,
-            threshold: config.inference.threshold,
+            decoder: LogitDecoder::from_config(config, labels)?,
             max_length: config.inference.max_length,
         }))
     }
@@ -366,9 +367,7 @@ impl ExternalMlModelLoader {
             tokenizer,
             model,
             device,
-            num_labels,
-            labels,
-            threshold: config.inference.threshold,
+            decoder: LogitDecoder::from_config(config, labels)?,
             max_length: config.inference.max_length,
         }))
     }
@@ -377,7 +376,33 @@ impl ExternalMlModelLoader {
         &self,
         config: &ModelConfig,
         pooling: &str,
+        exemplars: &[PathBuf],
+        top_k: usize,
     ) -> Result<Box<dyn Classifier>> {
+        let embedder = self.load_sentence_embedder(config, pooling).await?;
+
+        if !exemplars.is_empty() {
+            let exemplars = ExemplarSet::load_all(exemplars)?;
+            let classifier =
+                SimilarityClassifier::new(resolved_name(config), Arc::new(embedder), &exemplars)?
+                    .with_threshold(config.inference.threshold)
+                    .with_label_thresholds(config.inference.label_thresholds.clone())
+                    .with_top_k(top_k);
+            return Ok(Box::new(classifier));
+        }
+
+        Ok(Box::new(SentenceTransformerClassifier {
+            name: resolved_name(config),
+            embedder,
+            threshold: config.inference.threshold,
+        }))
+    }
+
+    async fn load_sentence_embedder(
+        &self,
+        config: &ModelConfig,
+        pooling: &str,
+    ) -> Result<SentenceEmbedder> {
         let model_path = self.resolve_model_path(config).await?;
         let tokenizer = load_tokenizer(&model_path)?;
         let bert_config: BertConfig = parse_json_config(&model_path.join("config.json"))?;
@@ -393,27 +418,62 @@ impl ExternalMlModelLoader {
             pooling.as_str()
         );
 
-        Ok(Box::new(SentenceTransformerClassifier {
-            name: resolved_name(config),
+        Ok(SentenceEmbedder {
             tokenizer,
             model,
             device,
             pooling,
-            threshold: config.inference.threshold,
             max_length: config.inference.max_length,
-        }))
+        })
     }
 }
 
 #[async_trait]
 impl ModelLoaderPlugin for ExternalMlModelLoader {
     async fn load_classifier(&self, name: &str) -> Result<Box<dyn Classifier>> {
-        self.load_classifier_internal(name).await
+        let classifier = self.load_classifier_internal(name).await?;
+        let Some(config) = self.registry.get_model(name) else {
+            return Ok(classifier);
+        };
+
+        let windowed = config.inference.long_text.strategy != LongTextStrategy::Head
+            && !matches!(
+                config.architecture,
+                ArchitectureConfig::SentenceTransformer { .. }
+            );
+        if !windowed {
+            return Ok(classifier);
+        }
+
+        let model_path = self.resolve_model_path(config).await?;
+        let tokenizer = load_tokenizer(&model_path)?;
+        with_long_text(Arc::from(classifier), tokenizer, config)
     }
 
     fn available_models(&self) -> Vec<String> {
         self.registry.models.keys().cloned().collect()
     }
+
+    fn backend(&self) -> &str {
+        "candle"
+    }
+
+    fn batch_size(&self, name: &str) -> usize {
+        self.registry
+            .get_model(name)
+            .map_or(1, |config| config.inference.batch_size)
+    }
+
+    async fn load_embedder(&self, name: &str) -> Result<Option<Arc<dyn Embedder>>> {
+        let Some(config) = self.registry.get_model(name) else {
+            return Ok(None);
+        };
+        let ArchitectureConfig::SentenceTransformer { pooling, .. } = &config.architecture else {
+            return Ok(None);
+        };
+        let embedder = self.load_sentence_embedder(config, pooling).await?;
+        Ok(Some(Arc::new(embedder)))
+    }
 }
 
 fn get_device(device_str: &str) -> Result<Device> {
@@ -675,11 +735,6 @@ fn load_classification_head(
     Ok(Linear::new(weight, Some(bias)))
 }
 
-fn softmax(logits: &Tensor) -> Result<Tensor> {
-    candle_nn::ops::softmax(logits, D::Minus1)
-        .map_err(|e| checkstream_core::Error::classifier(format!("Softmax failed: {}", e)))
-}
-
 fn normalized_labels(num_labels: usize, labels: &[String]) -> Vec<String> {
     if labels.is_empty() {
         return match num_labels {
@@ -698,8 +753,8 @@ fn normalized_labels(num_labels: usize, labels: &[String]) -> Vec<String> {
     resolved
 }
 
-fn to_probabilities(logits: &Tensor) -> Result<Vec<f32>> {
-    softmax(logits)?
+fn to_logits(logits: &Tensor) -> Result<Vec<f32>> {
+    logits
         .squeeze(0)
         .map_err(|e| checkstream_core::Error::classifier(format!("Squeeze failed: {}", e)))?
         .to_vec1()
@@ -708,56 +763,58 @@ fn to_probabilities(logits: &Tensor) -> Result<Vec<f32>> {
         })
 }
 
+/// Token ids, token type ids and attention mask for `texts`, right-padded to
+/// the longest truncated encoding, each shaped `[batch, sequence]`.
+fn padded_batch(
+    tokenizer: &Tokenizer,
+    texts: &[&str],
+    max_length: usize,
+    device: &Device,
+) -> Result<(Tensor, Tensor, Tensor)> {
+    let mut encodings = Vec::with_capacity(texts.len());
+    for text in texts {
+        let mut encoding = tokenizer.encode(*text, true).map_err(|e| {
+            checkstream_core::Error::classifier(format!("Tokenization failed: {}", e))
+        })?;
+        encoding.truncate(max_length, 0, TruncationDirection::Right);
+        encodings.push(encoding);
+    }
+
+    let sequence_len = encodings.iter().map(|e| e.len()).max().unwrap_or(0);
+    let pad_id = tokenizer.get_padding().map_or(0, |padding| padding.pad_id);
+
+    let mut ids = Vec::with_capacity(encodings.len() * sequence_len);
+    let mut type_ids = Vec::with_capacity(encodings.len() * sequence_len);
+    let mut mask = Vec::with_capacity(encodings.len() * sequence_len);
+    for encoding in &encodings {
+        let padding = sequence_len - encoding.len();
+        ids.extend_from_slice(encoding.get_ids());
+        ids.resize(ids.len() + padding, pad_id);
+        type_ids.extend_from_slice(encoding.get_type_ids());
+        type_ids.resize(type_ids.len() + padding, 0);
+        mask.extend_from_slice(encoding.get_attention_mask());
+        mask.resize(mask.len() + padding, 0);
+    }
+
+    let shape = (encodings.len(), sequence_len);
+    let tensor = |values: Vec<u32>| {
+        Tensor::from_vec(values, shape, device).map_err(|e| {
+            checkstream_core::Error::classifier(format!("Failed to create batch tensor: {}", e))
+        })
+    };
+    Ok((tensor(ids)?, tensor(type_ids)?, tensor(mask)?))
+}
+
 fn build_sequence_result(
     name: &str,
-    labels: &[String],
-    num_labels: usize,
-    threshold: f32,
-    probs_vec: Vec<f32>,
+    decoder: &LogitDecoder,
+    logits: &[f32],
     start: Instant,
 ) -> ClassificationResult {
-    let (max_idx, max_prob) = probs_vec
-        .iter()
-        .enumerate()
-        .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal))
-        .unwrap_or((0, &0.0));
-
-    let predicted_label = labels
-        .get(max_idx)
-        .cloned()
-        .unwrap_or_else(|| format!("label_{}", max_idx));
-
-    let score = if num_labels == 2 {
-        probs_vec.get(1).copied().unwrap_or(*max_prob)
-    } else {
-        *max_prob
-    };
-
-    let final_label = if num_labels == 2 && score <= threshold {
-        labels
-            .first()
-            .cloned()
-            .unwrap_or_else(|| "negative".to_string())
-    } else {
-        predicted_label
-    };
-
-    let all_scores = labels
-        .iter()
-        .enumerate()
-        .map(|(idx, label)| (label.clone(), probs_vec.get(idx).copied().unwrap_or(0.0)))
-        .collect();
-
-    ClassificationResult {
-        label: final_label,
-        score,
-        metadata: ClassificationMetadata {
-            model: Some(name.to_string()),
-            all_scores: Some(all_scores),
-            ..Default::default()
-        },
-        latency_us: start.elapsed().as_micros() as u64,
-    }
+    let mut result = decoder.decode(logits);
+    result.metadata.model = Some(name.to_string());
+    result.latency_us = start.elapsed().as_micros() as u64;
+    result
 }
 
 struct BertSequenceClassifier {
@@ -766,9 +823,7 @@ struct BertSequenceClassifier {
     model: BertModel,
     classifier: Linear,
     device: Device,
-    num_labels: usize,
-    labels: Vec<String>,
-    threshold: f32,
+    decoder: LogitDecoder,
     max_length: usize,
 }
 
@@ -825,18 +880,50 @@ impl Classifier for BertSequenceClassifier {
             checkstream_core::Error::classifier(format!("Classification head failed: {}", e))
         })?;
 
-        let probs_vec = to_probabilities(&logits)?;
+        let logits_vec = to_logits(&logits)?;
 
         Ok(build_sequence_result(
             &self.name,
-            &self.labels,
-            self.num_labels,
-            self.threshold,
-            probs_vec,
+            &self.decoder,
+            &logits_vec,
             start,
         ))
     }
 
+    async fn classify_batch(&self, texts: &[&str]) -> Result<Vec<ClassificationResult>> {
+        if texts.is_empty() {
+            return Ok(Vec::new());
+        }
+        let start = Instant::now();
+
+        let (input_ids, token_type_ids, attention_mask) =
+            padded_batch(&self.tokenizer, texts, self.max_length, &self.device)?;
+
+        let hidden_states = self
+            .model
+            .forward(&input_ids, &token_type_ids, Some(&attention_mask))
+            .map_err(|e| {
+                checkstream_core::Error::classifier(format!("Model forward pass failed: {}", e))
+            })?;
+
+        let cls_embeddings = hidden_states.i((.., 0, ..)).map_err(|e| {
+            checkstream_core::Error::classifier(format!("Failed to get CLS tokens: {}", e))
+        })?;
+
+        let logits = self.classifier.forward(&cls_embeddings).map_err(|e| {
+            checkstream_core::Error::classifier(format!("Classification head failed: {}", e))
+        })?;
+
+        let logits: Vec<Vec<f32>> = logits.to_vec2().map_err(|e| {
+            checkstream_core::Error::classifier(format!("Failed to convert to vec: {}", e))
+        })?;
+
+        Ok(logits
+            .iter()
+            .map(|logits_vec| build_sequence_result(&self.name, &self.decoder, logits_vec, start))
+            .collect())
+    }
+
     fn name(&self) -> &str {
         &self.name
     }
@@ -853,9 +940,7 @@ struct DistilBertSequenceClassifier {
     pre_classifier: Option<Linear>,
     classifier: Linear,
     device: Device,
-    num_labels: usize,
-    labels: Vec<String>,
-    threshold: f32,
+    decoder: LogitDecoder,
     max_length: usize,
 }
 
@@ -929,14 +1014,12 @@ impl Classifier for DistilBertSequenceClassifier {
             checkstream_core::Error::classifier(format!("Classification head failed: {}", e))
         })?;
 
-        let probs_vec = to_probabilities(&logits)?;
+        let logits_vec = to_logits(&logits)?;
 
         Ok(build_sequence_result(
             &self.name,
-            &self.labels,
-            self.num_labels,
-            self.threshold,
-            probs_vec,
+            &self.decoder,
+            &logits_vec,
             start,
         ))
     }
@@ -955,9 +1038,7 @@ struct DebertaSequenceClassifier {
     tokenizer: Tokenizer,
     model: DebertaV2SeqClassificationModel,
     device: Device,
-    num_labels: usize,
-    labels: Vec<String>,
-    threshold: f32,
+    decoder: LogitDecoder,
     max_length: usize,
 }
 
@@ -1017,14 +1098,12 @@ impl Classifier for DebertaSequenceClassifier {
                 checkstream_core::Error::classifier(format!("Model forward pass failed: {}", e))
             })?;
 
-        let probs_vec = to_probabilities(&logits)?;
+        let logits_vec = to_logits(&logits)?;
 
         Ok(build_sequence_result(
             &self.name,
-            &self.labels,
-            self.num_labels,
-            self.threshold,
-            probs_vec,
+            &self.decoder,
+            &logits_vec,
             start,
         ))
     }
@@ -1043,9 +1122,7 @@ struct XlmRobertaSequenceClassifier {
     tokenizer: Tokenizer,
     model: XLMRobertaForSequenceClassification,
     device: Device,
-    num_labels: usize,
-    labels: Vec<String>,
-    threshold: f32,
+    decoder: LogitDecoder,
     max_length: usize,
 }
 
@@ -1108,14 +1185,12 @@ impl Classifier for XlmRobertaSequenceClassifier {
                 checkstream_core::Error::classifier(format!("Model forward pass failed: {}", e))
             })?;
 
-        let probs_vec = to_probabilities(&logits)?;
+        let logits_vec = to_logits(&logits)?;
 
         Ok(build_sequence_result(
             &self.name,
-            &self.labels,
-            self.num_labels,
-            self.threshold,
-            probs_vec,
+            &self.decoder,
+            &logits_vec,
             start,
         ))
     }
@@ -1151,21 +1226,17 @@ impl PoolingStrategy {
     }
 }
 
-struct SentenceTransformerClassifier {
-    name: String,
+/// Sentence embedding from a BERT-family encoder
+struct SentenceEmbedder {
     tokenizer: Tokenizer,
     model: BertModel,
     device: Device,
     pooling: PoolingStrategy,
-    threshold: f32,
     max_length: usize,
 }
 
-#[async_trait]
-impl Classifier for SentenceTransformerClassifier {
-    async fn classify(&self, text: &str) -> Result<ClassificationResult> {
-        let start = Instant::now();
-
+impl Embedder for SentenceEmbedder {
+    fn embed(&self, text: &str) -> Result<Vec<f32>> {
         let mut encoding = self.tokenizer.encode(text, true).map_err(|e| {
             checkstream_core::Error::classifier(format!("Tokenization failed: {}", e))
         })?;
@@ -1258,6 +1329,23 @@ impl Classifier for SentenceTransformerClassifier {
             }
         };
 
+        Ok(pooled_embedding)
+    }
+}
+
+struct SentenceTransformerClassifier {
+    name: String,
+    embedder: SentenceEmbedder,
+    threshold: f32,
+}
+
+#[async_trait]
+impl Classifier for SentenceTransformerClassifier {
+    async fn classify(&self, text: &str) -> Result<ClassificationResult> {
+        let start = Instant::now();
+
+        let pooled_embedding = self.embedder.embed(text)?;
+
         let raw_score = pooled_embedding
             .first()
             .copied()
@@ -1282,7 +1370,10 @@ impl Classifier for SentenceTransformerClassifier {
                     ("positive".to_string(), score),
                 ]),
                 extra: vec![
-                    ("pooling".to_string(), self.pooling.as_str().to_string()),
+                    (
+                        "pooling".to_string(),
+                        self.embedder.pooling.as_str().to_string(),
+                    ),
                     (
                         "embedding_dim".to_string(),
                         pooled_embedding.len().to_string(),
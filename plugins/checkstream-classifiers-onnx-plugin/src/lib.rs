@@ -0,0 +1,10 @@
+//! ONNX inference plugin for `checkstream-classifiers`.
+//!
+//! This crate provides a CPU implementation of `ModelLoaderPlugin` for
+//! `.onnx` sequence-classification models (including int8-quantized exports)
+//! and can be injected into
+//! `checkstream_classifiers::dynamic_registry::DynamicRegistryBuilder`.
+
+pub mod onnx_loader;
+
+pub use onnx_loader::OnnxModelLoader;
@@ -0,0 +1,448 @@
+use async_trait::async_trait;
+use checkstream_classifiers::classifier::Classifier;
+use checkstream_classifiers::loader_plugin::ModelLoaderPlugin;
+use checkstream_classifiers::logits::LogitDecoder;
+use checkstream_classifiers::long_text::with_long_text;
+use checkstream_classifiers::model_config::{
+    ArchitectureConfig, LongTextStrategy, ModelConfig, ModelRegistry, ModelSource,
+    QuantizationConfig,
+};
+use checkstream_classifiers::{ClassificationResult, ClassifierTier};
+use checkstream_core::Result;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Instant;
+use tokenizers::{PaddingParams, PaddingStrategy, Tokenizer, TruncationParams};
+use tract_onnx::prelude::*;
+
+/// Model inputs the loader knows how to feed from a tokenizer encoding.
+const SUPPORTED_INPUTS: [&str; 3] = ["input_ids", "attention_mask", "token_type_ids"];
+
+/// Directories searched (relative to the model path) for `.onnx` files.
+const MODEL_SUBDIRS: [&str; 2] = ["", "onnx"];
+
+/// ONNX-backed model loader plugin running sequence classifiers on CPU.
+///
+/// Models are read from `local` sources in the model registry. A source path
+/// may point at an `.onnx` file directly or at a directory holding
+/// `model.onnx` (or a quantized variant) next to `tokenizer.json`.
+pub struct OnnxModelLoader {
+    registry: Arc<ModelRegistry>,
+}
+
+impl OnnxModelLoader {
+    /// Create plugin from a parsed model registry.
+    pub fn from_registry(registry: ModelRegistry) -> Self {
+        Self {
+            registry: Arc::new(registry),
+        }
+    }
+
+    /// Create plugin by loading a model-registry file.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let registry = ModelRegistry::from_file(path).map_err(|e| {
+            checkstream_core::Error::classifier(format!("Failed to load model registry: {}", e))
+        })?;
+        Ok(Self::from_registry(registry))
+    }
+
+    /// Access the underlying model registry.
+    pub fn registry(&self) -> &Arc<ModelRegistry> {
+        &self.registry
+    }
+
+    fn load_classifier_internal(&self, name: &str) -> Result<OnnxSequenceClassifier> {
+        let config = self.registry.get_model(name).ok_or_else(|| {
+            checkstream_core::Error::classifier(format!("Model '{}' not found in registry", name))
+        })?;
+
+        let (num_labels, labels) = match &config.architecture {
+            ArchitectureConfig::BertSequenceClassification { num_labels, labels }
+            | ArchitectureConfig::DistilBertSequenceClassification { num_labels, labels }
+            | ArchitectureConfig::RobertaSequenceClassification { num_labels, labels }
+            | ArchitectureConfig::DebertaSequenceClassification { num_labels, labels }
+            | ArchitectureConfig::XlmRobertaSequenceClassification { num_labels, labels }
+            | ArchitectureConfig::MiniLmSequenceClassification { num_labels, labels } => {
+                (*num_labels, labels.as_slice())
+            }
+            other => {
+                return Err(checkstream_core::Error::classifier(format!(
+                    "Model '{}': architecture {:?} is not supported by the ONNX loader",
+                    name, other
+                )))
+            }
+        };
+
+        if !config.inference.device.eq_ignore_ascii_case("cpu") {
+            return Err(checkstream_core::Error::classifier(format!(
+                "Model '{}': ONNX loader runs on CPU only (requested device '{}')",
+                name, config.inference.device
+            )));
+        }
+
+        let path = local_path(name, config)?;
+        let model_file = resolve_model_file(path, config.inference.quantization.as_ref())?;
+        let model_dir = if path.is_dir() {
+            path
+        } else {
+            path.parent().unwrap_or(Path::new("."))
+        };
+
+        tracing::info!(
+            "Loading ONNX model '{}' from {}",
+            name,
+            model_file.display()
+        );
+
+        let tokenizer = load_tokenizer(model_dir, config.inference.max_length)?;
+        let (model, inputs) = load_model(&model_file)?;
+
+        Ok(OnnxSequenceClassifier {
+            name: resolved_name(name, config),
+            tokenizer,
+            model,
+            inputs,
+            num_labels,
+            decoder: LogitDecoder::from_config(config, normalized_labels(num_labels, labels))?,
+        })
+    }
+}
+
+#[async_trait]
+impl ModelLoaderPlugin for OnnxModelLoader {
+    async fn load_classifier(&self, name: &str) -> Result<Box<dyn Classifier>> {
+        let classifier = self.load_classifier_internal(name)?;
+        let config = self.registry.get_model(name).ok_or_else(|| {
+            checkstream_core::Error::classifier(format!("Model '{}' not found in registry", name))
+        })?;
+
+        if config.inference.long_text.strategy == LongTextStrategy::Head {
+            return Ok(Box::new(classifier));
+        }
+
+        let tokenizer = classifier.tokenizer.clone();
+        with_long_text(Arc::new(classifier), tokenizer, config)
+    }
+
+    /// Local models with an ONNX export on disk; everything else is left to
+    /// other loaders so this one can sit in front of the Candle backend.
+    fn available_models(&self) -> Vec<String> {
+        self.registry
+            .models
+            .iter()
+            .filter(|(_, config)| match &config.source {
+                ModelSource::Local { path } => {
+                    resolve_model_file(path, config.inference.quantization.as_ref()).is_ok()
+                }
+                _ => false,
+            })
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+
+    fn backend(&self) -> &str {
+        "onnx"
+    }
+
+    fn batch_size(&self, name: &str) -> usize {
+        self.registry
+            .get_model(name)
+            .map_or(1, |config| config.inference.batch_size)
+    }
+}
+
+fn local_path<'a>(name: &str, config: &'a ModelConfig) -> Result<&'a Path> {
+    match &config.source {
+        ModelSource::Local { path } => {
+            if !path.exists() {
+                return Err(checkstream_core::Error::classifier(format!(
+                    "Model path does not exist: {}",
+                    path.display()
+                )));
+            }
+            Ok(path)
+        }
+        _ => Err(checkstream_core::Error::classifier(format!(
+            "Model '{}': ONNX loader only supports local model sources",
+            name
+        ))),
+    }
+}
+
+/// File names tried for a model, in order, given its quantization settings.
+///
+/// tract does not quantize models itself, so an enabled `QuantizationConfig`
+/// selects an export that was quantized ahead of time rather than quantizing
+/// at load time.
+fn candidate_files(quantization: Option<&QuantizationConfig>) -> Result<&'static [&'static str]> {
+    let Some(quantization) = quantization.filter(|q| q.enabled) else {
+        return Ok(&["model.onnx"]);
+    };
+
+    match quantization.dtype.to_lowercase().as_str() {
+        "int8" | "qint8" | "uint8" | "quint8" => Ok(&[
+            "model_quantized.onnx",
+            "model_int8.onnx",
+            "model_uint8.onnx",
+            "model.int8.onnx",
+        ]),
+        "float16" | "fp16" => Ok(&["model_fp16.onnx", "model.fp16.onnx"]),
+        other => Err(checkstream_core::Error::classifier(format!(
+            "Unsupported ONNX quantization dtype '{}' (expected int8, uint8 or float16)",
+            other
+        ))),
+    }
+}
+
+fn resolve_model_file(path: &Path, quantization: Option<&QuantizationConfig>) -> Result<PathBuf> {
+    let candidates = candidate_files(quantization)?;
+
+    if path.is_file() {
+        if path.extension().is_some_and(|ext| ext == "onnx") {
+            return Ok(path.to_path_buf());
+        }
+        return Err(checkstream_core::Error::classifier(format!(
+            "Not an ONNX model file: {}",
+            path.display()
+        )));
+    }
+
+    for subdir in MODEL_SUBDIRS {
+        for file in candidates {
+            let candidate = path.join(subdir).join(file);
+            if candidate.is_file() {
+                return Ok(candidate);
+            }
+        }
+    }
+
+    Err(checkstream_core::Error::classifier(format!(
+        "No ONNX model found in {} (tried {})",
+        path.display(),
+        candidates.join(", ")
+    )))
+}
+
+fn load_tokenizer(model_dir: &Path, max_length: usize) -> Result<Tokenizer> {
+    let tokenizer_path = model_dir.join("tokenizer.json");
+    let mut tokenizer = Tokenizer::from_file(&tokenizer_path).map_err(|e| {
+        checkstream_core::Error::classifier(format!(
+            "Failed to load {}: {}",
+            tokenizer_path.display(),
+            e
+        ))
+    })?;
+
+    tokenizer
+        .with_truncation(Some(TruncationParams {
+            max_length,
+            ..Default::default()
+        }))
+        .map_err(|e| {
+            checkstream_core::Error::classifier(format!("Invalid truncation settings: {}", e))
+        })?;
+    // Batches are padded to their longest member; keep a tokenizer-defined
+    // pad token (e.g. RoBERTa's) when there is one.
+    let padding = tokenizer.get_padding().cloned().unwrap_or_default();
+    tokenizer.with_padding(Some(PaddingParams {
+        strategy: PaddingStrategy::BatchLongest,
+        ..padding
+    }));
+
+    Ok(tokenizer)
+}
+
+type OnnxPlan = TypedRunnableModel<TypedModel>;
+
+/// Load and optimize an ONNX graph for `[batch, sequence]` int64 token inputs.
+fn load_model(model_file: &Path) -> Result<(OnnxPlan, Vec<String>)> {
+    let onnx_error = |e: TractError| {
+        checkstream_core::Error::classifier(format!(
+            "Failed to load ONNX model {}: {}",
+            model_file.display(),
+            e
+        ))
+    };
+
+    let mut model = tract_onnx::onnx()
+        .model_for_path(model_file)
+        .map_err(onnx_error)?;
+
+    let inputs = model
+        .input_outlets()
+        .map_err(onnx_error)?
+        .iter()
+        .map(|outlet| model.node(outlet.node).name.clone())
+        .collect::<Vec<_>>();
+
+    if let Some(unknown) = inputs
+        .iter()
+        .find(|input| !SUPPORTED_INPUTS.contains(&input.as_str()))
+    {
+        return Err(checkstream_core::Error::classifier(format!(
+            "ONNX model {} has unsupported input '{}' (expected {})",
+            model_file.display(),
+            unknown,
+            SUPPORTED_INPUTS.join(", ")
+        )));
+    }
+
+    let batch = model.symbols.sym("B");
+    let sequence = model.symbols.sym("S");
+    for ix in 0..inputs.len() {
+        model
+            .set_input_fact(
+                ix,
+                InferenceFact::dt_shape(
+                    i64::datum_type(),
+                    tvec!(batch.to_dim(), sequence.to_dim()),
+                ),
+            )
+            .map_err(onnx_error)?;
+    }
+
+    let plan = model
+        .into_optimized()
+        .and_then(|model| model.into_runnable())
+        .map_err(onnx_error)?;
+
+    Ok((plan, inputs))
+}
+
+fn resolved_name(name: &str, config: &ModelConfig) -> String {
+    if config.name.is_empty() {
+        name.to_string()
+    } else {
+        config.name.clone()
+    }
+}
+
+fn normalized_labels(num_labels: usize, labels: &[String]) -> Vec<String> {
+    if labels.is_empty() {
+        return match num_labels {
+            0 | 2 => vec!["negative".to_string(), "positive".to_string()],
+            1 => vec!["positive".to_string()],
+            n => (0..n).map(|idx| format!("label_{}", idx)).collect(),
+        };
+    }
+
+    let mut resolved = labels.to_vec();
+    for idx in resolved.len()..num_labels {
+        resolved.push(format!("label_{}", idx));
+    }
+    resolved
+}
+
+struct OnnxSequenceClassifier {
+    name: String,
+    tokenizer: Tokenizer,
+    model: OnnxPlan,
+    inputs: Vec<String>,
+    num_labels: usize,
+    decoder: LogitDecoder,
+}
+
+impl OnnxSequenceClassifier {
+    /// Run one padded forward pass, returning logits per text
+    fn logits(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+        let encodings = self
+            .tokenizer
+            .encode_batch(texts.to_vec(), true)
+            .map_err(|e| {
+                checkstream_core::Error::classifier(format!("Tokenization failed: {}", e))
+            })?;
+        let sequence_len = encodings.first().map_or(0, |encoding| encoding.len());
+
+        let inputs = self
+            .inputs
+            .iter()
+            .map(|input| {
+                let values: Vec<i64> = encodings
+                    .iter()
+                    .flat_map(|encoding| {
+                        let values = match input.as_str() {
+                            "input_ids" => encoding.get_ids(),
+                            "attention_mask" => encoding.get_attention_mask(),
+                            _ => encoding.get_type_ids(),
+                        };
+                        values.iter().map(|&v| v as i64)
+                    })
+                    .collect();
+                tract_ndarray::Array2::from_shape_vec((encodings.len(), sequence_len), values)
+                    .map(|array| array.into_tensor().into_tvalue())
+            })
+            .collect::<std::result::Result<TVec<_>, _>>()
+            .map_err(|e| {
+                checkstream_core::Error::classifier(format!("Failed to build input tensor: {}", e))
+            })?;
+
+        let outputs = self.model.run(inputs).map_err(|e| {
+            checkstream_core::Error::classifier(format!("ONNX inference failed: {}", e))
+        })?;
+
+        let logits = outputs
+            .first()
+            .ok_or_else(|| checkstream_core::Error::classifier("ONNX model produced no outputs"))?
+            .cast_to::<f32>()
+            .map_err(|e| {
+                checkstream_core::Error::classifier(format!("Unexpected logits type: {}", e))
+            })?
+            .as_slice::<f32>()
+            .map_err(|e| {
+                checkstream_core::Error::classifier(format!("Unexpected logits layout: {}", e))
+            })?
+            .to_vec();
+
+        let per_text = logits.len() / texts.len().max(1);
+        if per_text == 0
+            || logits.len() != per_text * texts.len()
+            || (self.num_labels > 0 && per_text != self.num_labels)
+        {
+            return Err(checkstream_core::Error::classifier(format!(
+                "ONNX model '{}' returned {} logits for {} texts, expected {} each",
+                self.name,
+                logits.len(),
+                texts.len(),
+                self.num_labels.max(1)
+            )));
+        }
+
+        Ok(logits.chunks(per_text).map(<[f32]>::to_vec).collect())
+    }
+
+    fn result_from_logits(&self, logits: &[f32], start: Instant) -> ClassificationResult {
+        let mut result = self.decoder.decode(logits);
+        result.metadata.model = Some(self.name.clone());
+        result.latency_us = start.elapsed().as_micros() as u64;
+        result
+    }
+}
+
+#[async_trait]
+impl Classifier for OnnxSequenceClassifier {
+    async fn classify(&self, text: &str) -> Result<ClassificationResult> {
+        let start = Instant::now();
+        let logits = self.logits(&[text])?;
+        Ok(self.result_from_logits(&logits[0], start))
+    }
+
+    async fn classify_batch(&self, texts: &[&str]) -> Result<Vec<ClassificationResult>> {
+        if texts.is_empty() {
+            return Ok(Vec::new());
+        }
+        let start = Instant::now();
+        let logits = self.logits(texts)?;
+        Ok(logits
+            .iter()
+            .map(|logits| self.result_from_logits(logits, start))
+            .collect())
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn tier(&self) -> ClassifierTier {
+        ClassifierTier::B
+    }
+}
@@ -0,0 +1,429 @@
+use checkstream_classifiers::model_config::ModelRegistry;
+use checkstream_classifiers::ModelLoaderPlugin;
+use checkstream_classifiers_onnx_plugin::OnnxModelLoader;
+use prost::Message;
+use std::collections::HashMap;
+use std::path::Path;
+use tokenizers::models::wordlevel::WordLevel;
+use tokenizers::pre_tokenizers::whitespace::Whitespace;
+use tokenizers::Tokenizer;
+use tract_onnx::pb;
+
+const VOCAB: [&str; 4] = ["[UNK]", "hello", "world", "attack"];
+
+/// Per-token `[safe, unsafe]` logits; the model max-pools them over the sequence.
+const EMBEDDINGS: [[f32; 2]; 4] = [[1.0, 0.0], [1.0, 0.0], [1.0, 0.0], [0.0, 4.0]];
+
+fn write_tokenizer(dir: &Path) {
+    let vocab: HashMap<String, u32> = VOCAB
+        .iter()
+        .enumerate()
+        .map(|(id, token)| (token.to_string(), id as u32))
+        .collect();
+    let model = WordLevel::builder()
+        .vocab(vocab)
+        .unk_token("[UNK]".to_string())
+        .build()
+        .unwrap();
+
+    let mut tokenizer = Tokenizer::new(model);
+    tokenizer.with_pre_tokenizer(Some(Whitespace {}));
+    tokenizer.save(dir.join("tokenizer.json"), false).unwrap();
+}
+
+fn token_input(name: &str) -> pb::ValueInfoProto {
+    use pb::tensor_shape_proto::{dimension::Value, Dimension};
+
+    let dim = |value| Dimension {
+        value: Some(value),
+        ..Default::default()
+    };
+    pb::ValueInfoProto {
+        name: name.to_string(),
+        r#type: Some(pb::TypeProto {
+            value: Some(pb::type_proto::Value::TensorType(pb::type_proto::Tensor {
+                elem_type: pb::tensor_proto::DataType::Int64 as i32,
+                shape: Some(pb::TensorShapeProto {
+                    dim: vec![
+                        dim(Value::DimParam("batch".to_string())),
+                        dim(Value::DimParam("sequence".to_string())),
+                    ],
+                }),
+            })),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+fn node(op_type: &str, inputs: &[&str], output: &str) -> pb::NodeProto {
+    pb::NodeProto {
+        op_type: op_type.to_string(),
+        name: output.to_string(),
+        input: inputs.iter().map(|s| s.to_string()).collect(),
+        output: vec![output.to_string()],
+        ..Default::default()
+    }
+}
+
+fn ints_attribute(name: &str, ints: Vec<i64>) -> pb::AttributeProto {
+    pb::AttributeProto {
+        name: name.to_string(),
+        r#type: pb::attribute_proto::AttributeType::Ints as i32,
+        ints,
+        ..Default::default()
+    }
+}
+
+fn int_attribute(name: &str, i: i64) -> pb::AttributeProto {
+    pb::AttributeProto {
+        name: name.to_string(),
+        r#type: pb::attribute_proto::AttributeType::Int as i32,
+        i,
+        ..Default::default()
+    }
+}
+
+fn tensor(name: &str, data_type: pb::tensor_proto::DataType, dims: Vec<i64>) -> pb::TensorProto {
+    pb::TensorProto {
+        name: name.to_string(),
+        data_type: data_type as i32,
+        dims,
+        ..Default::default()
+    }
+}
+
+/// How `write_model` stores its weights
+#[derive(Clone, Copy, PartialEq)]
+enum Export {
+    /// Plain fp32 initializers
+    Fp32,
+    /// int8 embedding table dequantized in the graph (`DequantizeLinear`)
+    StaticInt8,
+    /// fp32 embeddings followed by an int8 identity projection whose input is
+    /// quantized at run time, as ONNX Runtime's `quantize_dynamic` (and
+    /// Optimum's dynamic int8 exports) emit: `DynamicQuantizeLinear`,
+    /// `MatMulInteger`, then a rescale back to float
+    DynamicInt8,
+}
+
+/// Write a tiny sequence classifier: embedding lookup, then max over tokens.
+fn write_model(path: &Path, export: Export) {
+    use pb::tensor_proto::DataType;
+
+    let mut initializer = Vec::new();
+    let mut nodes = Vec::new();
+
+    if export == Export::StaticInt8 {
+        let mut weights = tensor("embeddings_q", DataType::Int8, vec![4, 2]);
+        weights.raw_data = EMBEDDINGS
+            .iter()
+            .flatten()
+            .map(|v| (v * 10.0) as i8 as u8)
+            .collect();
+        let mut scale = tensor("embeddings_scale", DataType::Float, vec![]);
+        scale.float_data = vec![0.1];
+        let mut zero_point = tensor("embeddings_zero_point", DataType::Int8, vec![]);
+        zero_point.raw_data = vec![0];
+        initializer.extend([weights, scale, zero_point]);
+        nodes.push(node(
+            "DequantizeLinear",
+            &["embeddings_q", "embeddings_scale", "embeddings_zero_point"],
+            "embeddings",
+        ));
+    } else {
+        let mut weights = tensor("embeddings", DataType::Float, vec![4, 2]);
+        weights.float_data = EMBEDDINGS.iter().flatten().copied().collect();
+        initializer.push(weights);
+    }
+
+    let mut gather = node("Gather", &["embeddings", "input_ids"], "hidden");
+    gather.attribute.push(int_attribute("axis", 0));
+    nodes.push(gather);
+
+    let pooled = if export == Export::DynamicInt8 {
+        let mut weights = tensor("projection_q", DataType::Int8, vec![2, 2]);
+        weights.raw_data = vec![10, 0, 0, 10];
+        let mut scale = tensor("projection_scale", DataType::Float, vec![]);
+        scale.float_data = vec![0.1];
+        let mut zero_point = tensor("projection_zero_point", DataType::Int8, vec![]);
+        zero_point.raw_data = vec![0];
+        initializer.extend([weights, scale, zero_point]);
+
+        let mut quantize = node("DynamicQuantizeLinear", &["hidden"], "hidden_q");
+        quantize.output = ["hidden_q", "hidden_scale", "hidden_zero_point"]
+            .map(String::from)
+            .to_vec();
+        let mut cast = node("Cast", &["projected_i32"], "projected_unscaled");
+        cast.attribute
+            .push(int_attribute("to", DataType::Float as i64));
+        nodes.extend([
+            quantize,
+            node(
+                "MatMulInteger",
+                &[
+                    "hidden_q",
+                    "projection_q",
+                    "hidden_zero_point",
+                    "projection_zero_point",
+                ],
+                "projected_i32",
+            ),
+            cast,
+            node(
+                "Mul",
+                &["hidden_scale", "projection_scale"],
+                "projected_scale",
+            ),
+            node(
+                "Mul",
+                &["projected_unscaled", "projected_scale"],
+                "projected",
+            ),
+        ]);
+        "projected"
+    } else {
+        "hidden"
+    };
+
+    let mut reduce = node("ReduceMax", &[pooled], "logits");
+    reduce.attribute.push(ints_attribute("axes", vec![1]));
+    reduce.attribute.push(int_attribute("keepdims", 0));
+    nodes.push(reduce);
+
+    let model = pb::ModelProto {
+        ir_version: 7,
+        opset_import: vec![pb::OperatorSetIdProto {
+            domain: String::new(),
+            version: 13,
+        }],
+        producer_name: "checkstream-tests".to_string(),
+        graph: Some(pb::GraphProto {
+            name: "tiny-classifier".to_string(),
+            node: nodes,
+            initializer,
+            input: vec![token_input("input_ids"), token_input("attention_mask")],
+            output: vec![pb::ValueInfoProto {
+                name: "logits".to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+
+    std::fs::write(path, model.encode_to_vec()).unwrap();
+}
+
+fn registry(model_path: &Path, inference: &str) -> ModelRegistry {
+    let yaml = format!(
+        r#"
+version: "1.0"
+models:
+  tiny:
+    source:
+      type: local
+      path: "{}"
+    architecture:
+      type: bert-sequence-classification
+      num_labels: 2
+      labels: ["safe", "unsafe"]
+    inference:
+{}
+  hub-model:
+    source:
+      type: huggingface
+      repo: "example/model"
+    architecture:
+      type: bert-sequence-classification
+      num_labels: 2
+"#,
+        model_path.display(),
+        inference
+    );
+    serde_yaml::from_str(&yaml).unwrap()
+}
+
+fn fp32_model_dir() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    write_tokenizer(dir.path());
+    write_model(&dir.path().join("model.onnx"), Export::Fp32);
+    dir
+}
+
+#[tokio::test]
+async fn classifies_with_local_onnx_model() {
+    let dir = fp32_model_dir();
+    let loader = OnnxModelLoader::from_registry(registry(dir.path(), "      max_length: 512"));
+
+    assert_eq!(loader.backend(), "onnx");
+    assert_eq!(loader.available_models(), vec!["tiny".to_string()]);
+
+    let classifier = loader.load_classifier("tiny").await.unwrap();
+
+    let benign = classifier.classify("hello world").await.unwrap();
+    assert_eq!(benign.label, "safe");
+    assert!(benign.score < 0.5);
+
+    let harmful = classifier.classify("hello attack").await.unwrap();
+    assert_eq!(harmful.label, "unsafe");
+    assert!(harmful.score > 0.9);
+
+    let scores = harmful.metadata.all_scores.unwrap();
+    assert_eq!(scores.len(), 2);
+    assert!((scores.iter().map(|(_, p)| p).sum::<f32>() - 1.0).abs() < 1e-5);
+}
+
+#[tokio::test]
+async fn classifies_padded_batches() {
+    let dir = fp32_model_dir();
+    let loader = OnnxModelLoader::from_registry(registry(dir.path(), "      batch_size: 8"));
+    assert_eq!(loader.batch_size("tiny"), 8);
+    assert_eq!(loader.batch_size("hub-model"), 1);
+
+    let classifier = loader.load_classifier("tiny").await.unwrap();
+    let results = classifier
+        .classify_batch(&["hello", "hello world attack", "world hello"])
+        .await
+        .unwrap();
+
+    let labels: Vec<_> = results.iter().map(|r| r.label.as_str()).collect();
+    assert_eq!(labels, vec!["safe", "unsafe", "safe"]);
+    let single = classifier.classify("hello world attack").await.unwrap();
+    assert!((single.score - results[1].score).abs() < 1e-6);
+}
+
+#[tokio::test]
+async fn truncates_input_to_max_length() {
+    let dir = fp32_model_dir();
+    let loader = OnnxModelLoader::from_registry(registry(dir.path(), "      max_length: 2"));
+    let classifier = loader.load_classifier("tiny").await.unwrap();
+
+    let result = classifier.classify("hello world attack").await.unwrap();
+    assert_eq!(result.label, "safe");
+}
+
+#[tokio::test]
+async fn long_text_strategies_cover_the_whole_input() {
+    let dir = fp32_model_dir();
+    let text = "hello world hello world hello attack";
+    let load = |long_text: &str| {
+        let loader = OnnxModelLoader::from_registry(registry(
+            dir.path(),
+            &format!("      max_length: 3\n      long_text:\n{}", long_text),
+        ));
+        async move { loader.load_classifier("tiny").await.unwrap() }
+    };
+
+    let head = load("        strategy: head").await;
+    assert_eq!(head.classify(text).await.unwrap().label, "safe");
+
+    let tail = load("        strategy: tail").await;
+    assert_eq!(tail.classify(text).await.unwrap().label, "unsafe");
+
+    let sliding = load("        strategy: sliding-window\n        stride: 2").await;
+    let result = sliding.classify(text).await.unwrap();
+    assert_eq!(result.label, "unsafe");
+    assert!(result
+        .metadata
+        .extra
+        .contains(&("windows".to_string(), "3".to_string())));
+    let span = &result.metadata.spans[0];
+    assert_eq!(&text[span.start..span.end], "hello attack");
+}
+
+#[tokio::test]
+async fn multi_label_output_thresholds_each_label() {
+    let dir = fp32_model_dir();
+    let loader = OnnxModelLoader::from_registry(registry(
+        dir.path(),
+        "      label_thresholds:\n        safe: 0.9\n    output:\n      output_type: multi-label",
+    ));
+    let classifier = loader.load_classifier("tiny").await.unwrap();
+
+    // Max-pooled logits [1, 0]: sigmoid gives safe 0.73 and unsafe 0.5
+    let benign = classifier.classify("hello world").await.unwrap();
+    assert_eq!(benign.label, "negative");
+    assert_eq!(benign.metadata.labels.len(), 2);
+    assert_eq!(benign.triggered_labels().count(), 0);
+
+    // Logits [1, 4]: unsafe 0.98 clears 0.5, safe 0.73 stays under 0.9
+    let harmful = classifier.classify("hello attack").await.unwrap();
+    assert_eq!(harmful.label, "unsafe");
+    let triggered: Vec<_> = harmful
+        .triggered_labels()
+        .map(|label| label.label.as_str())
+        .collect();
+    assert_eq!(triggered, vec!["unsafe"]);
+
+    let unknown = OnnxModelLoader::from_registry(registry(
+        dir.path(),
+        "      label_thresholds:\n        toxic: 0.9",
+    ));
+    let err = unknown.load_classifier("tiny").await.err().unwrap();
+    assert!(err.to_string().contains("toxic"));
+}
+
+#[tokio::test]
+async fn quantization_selects_int8_export() {
+    let dir = tempfile::tempdir().unwrap();
+    write_tokenizer(dir.path());
+    write_model(&dir.path().join("model_quantized.onnx"), Export::StaticInt8);
+
+    let quantized = OnnxModelLoader::from_registry(registry(
+        dir.path(),
+        "      quantization:\n        enabled: true\n        dtype: int8",
+    ));
+    let classifier = quantized.load_classifier("tiny").await.unwrap();
+    assert_eq!(classifier.classify("attack").await.unwrap().label, "unsafe");
+    assert_eq!(classifier.classify("hello").await.unwrap().label, "safe");
+
+    let unquantized = OnnxModelLoader::from_registry(registry(dir.path(), "      max_length: 512"));
+    assert!(unquantized.available_models().is_empty());
+    let err = unquantized.load_classifier("tiny").await.err().unwrap();
+    assert!(err.to_string().contains("model.onnx"));
+}
+
+#[tokio::test]
+async fn runs_dynamically_quantized_int8_export() {
+    let dir = tempfile::tempdir().unwrap();
+    write_tokenizer(dir.path());
+    write_model(
+        &dir.path().join("model_quantized.onnx"),
+        Export::DynamicInt8,
+    );
+
+    let loader = OnnxModelLoader::from_registry(registry(
+        dir.path(),
+        "      quantization:\n        enabled: true\n        dtype: int8",
+    ));
+    let classifier = loader.load_classifier("tiny").await.unwrap();
+
+    let harmful = classifier.classify("hello attack").await.unwrap();
+    assert_eq!(harmful.label, "unsafe");
+    assert!(harmful.score > 0.9);
+    assert_eq!(
+        classifier.classify("hello world").await.unwrap().label,
+        "safe"
+    );
+}
+
+#[tokio::test]
+async fn rejects_unsupported_settings() {
+    let dir = fp32_model_dir();
+
+    let gpu = OnnxModelLoader::from_registry(registry(dir.path(), "      device: cuda"));
+    let err = gpu.load_classifier("tiny").await.err().unwrap();
+    assert!(err.to_string().contains("CPU only"));
+
+    let fp4 = OnnxModelLoader::from_registry(registry(
+        dir.path(),
+        "      quantization:\n        enabled: true\n        dtype: fp4",
+    ));
+    let err = fp4.load_classifier("tiny").await.err().unwrap();
+    assert!(err.to_string().contains("fp4"));
+
+    let loader = OnnxModelLoader::from_registry(registry(dir.path(), "      max_length: 512"));
+    let err = loader.load_classifier("hub-model").await.err().unwrap();
+    assert!(err.to_string().contains("local model sources"));
+}